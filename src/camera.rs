@@ -1,68 +1,119 @@
+mod aperture;
+mod equirectangular;
+mod fisheye;
+mod orthographic;
+mod perspective;
+mod realistic;
+mod stereo;
+
+pub use aperture::{Aperture, ApertureMask};
+pub use equirectangular::EquirectangularCamera;
+pub use fisheye::{FisheyeCamera, FisheyeProjection};
+pub use orthographic::OrthographicCamera;
+pub use perspective::PerspectiveCamera;
+pub use realistic::{load_prescription, parse_prescription, LensElement, RealisticCamera};
+pub use stereo::{StereoCamera, StereoLayout};
+
 use crate::random::Random;
-use crate::{Degrees, Point3, Ray, Vec3};
+use crate::{Color, Point3, Ray, Vec3};
+
+/// A ray leaving the camera.
+pub struct CameraRay {
+    pub ray: Ray,
+    /// The factor the radiance arriving along the ray is multiplied with.
+    pub weight: Color,
+}
 
-pub struct Camera {
-    origin: Point3,
-    lower_left_corner: Point3,
-    horizontal: Vec3,
-    vertical: Vec3,
-    u: Vec3,
-    v: Vec3,
-    lens_radius: f32,
+impl From<Ray> for CameraRay {
+    fn from(ray: Ray) -> Self {
+        Self {
+            ray,
+            weight: Color::new(1., 1., 1.),
+        }
+    }
 }
 
-impl Camera {
-    /// Constructs a new camera.
+/// Maps image coordinates to the rays rendering them.
+pub trait Camera: Send + Sync {
+    /// Creates a ray through the specified image coordinates in range `0.0..=1.0`,
+    /// with `(0, 0)` being the lower left corner.
     ///
-    /// # Arguments
-    /// * `look_from`: The origin point of the camera.
-    /// * `look_at`: The point to look at.
-    /// * `view_up`: The up axis of the camera.
-    /// * `vfov`: The vertical field of view in degrees.
-    /// * `aspect_ratio`: The aspect ratio.
-    /// * `aperture`: The aperture size of the lens.
-    /// * `focus_distance`: The focus distance of the lens.
-    pub fn new(
-        look_from: Point3,
-        look_at: Point3,
-        view_up: Vec3,
-        vfov: Degrees,
-        aspect_ratio: f32,
-        aperture: f32,
-        focus_distance: f32,
-    ) -> Self {
-        let theta = vfov.0.to_radians();
-        let h = (theta * 0.5).tan();
-        let viewport_height = 2. * h;
-        let viewport_width = aspect_ratio * viewport_height;
+    /// Returns `None` if no light reaches the image at these coordinates.
+    fn get_ray(&self, s: f32, t: f32, rng: &Random) -> Option<CameraRay>;
 
-        let w = (look_from - look_at).as_unit_vector();
-        let u = view_up.cross(&w).as_unit_vector();
-        let v = w.cross(&u);
+    /// Creates a ray like [`Camera::get_ray`] that carries the specified wavelengths
+    /// in nanometers in its color channels, for spectral rendering.
+    fn get_spectral_ray(
+        &self,
+        s: f32,
+        t: f32,
+        wavelengths: Vec3,
+        rng: &Random,
+    ) -> Option<CameraRay> {
+        let sample = self.get_ray(s, t, rng)?;
+        Some(CameraRay {
+            ray: sample.ray.with_wavelengths(wavelengths),
+            ..sample
+        })
+    }
+}
 
-        let origin = look_from;
-        let horizontal = focus_distance * viewport_width * u;
-        let vertical = focus_distance * viewport_height * v;
-        let lower_left_corner = origin - horizontal.half() - vertical.half() - focus_distance * w;
+/// The times at which the camera shutter opens and closes.
+///
+/// Rays are emitted at random times within this interval.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Shutter {
+    pub open: f32,
+    pub close: f32,
+}
 
-        Self {
-            origin,
-            horizontal,
-            vertical,
-            lower_left_corner,
-            u,
-            v,
-            lens_radius: aperture * 0.5,
-        }
+impl Shutter {
+    pub fn new(open: f32, close: f32) -> Self {
+        Self { open, close }
     }
 
-    pub fn get_ray(&self, s: f32, t: f32, rng: &Random) -> Ray {
-        let rd = self.lens_radius * Vec3::random_in_unit_disk(rng);
-        let offset = self.u * rd.x() + self.v * rd.y();
+    pub fn sample(&self, rng: &Random) -> f32 {
+        self.open + rng.sample() * (self.close - self.open)
+    }
+}
+
+/// The placement and orientation of the camera.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct View {
+    pub look_from: Point3,
+    pub look_at: Point3,
+    pub view_up: Vec3,
+}
 
-        Ray::new(
-            self.origin + offset,
-            self.lower_left_corner + s * self.horizontal + t * self.vertical - self.origin - offset,
-        )
+impl View {
+    /// The unit vector pointing from the camera towards the point looked at.
+    pub fn forward(&self) -> Vec3 {
+        (self.look_at - self.look_from).as_unit_vector()
     }
 }
+
+/// How the distance to the plane in focus is determined.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Focus {
+    /// A fixed distance along the viewing direction.
+    Distance(f32),
+    /// Focuses on the plane through a point, facing the camera.
+    Point(Point3),
+    /// Focuses on whatever is seen through the pixel at `(x, y)`,
+    /// counted from the top left corner of the image.
+    Pixel(usize, usize),
+}
+
+impl Default for Focus {
+    fn default() -> Self {
+        Focus::Distance(10.)
+    }
+}
+
+/// Determines the right, up and backward axes of a camera.
+fn basis(look_from: Point3, look_at: Point3, view_up: Vec3) -> (Vec3, Vec3, Vec3) {
+    let w = (look_from - look_at).as_unit_vector();
+    let u = view_up.cross(&w).as_unit_vector();
+    let v = w.cross(&u);
+    (u, v, w)
+}
@@ -0,0 +1,161 @@
+use crate::pgm;
+use crate::random::Random;
+use crate::{Degrees, Vec3};
+use std::f32::consts::PI;
+use std::path::Path;
+
+/// The shape of the lens opening, which determines the shape of out-of-focus highlights.
+#[derive(Clone)]
+pub enum Aperture {
+    Circle,
+    /// A regular polygon formed by straight aperture blades.
+    Polygon {
+        blades: usize,
+        rotation: Degrees,
+    },
+    /// An arbitrary shape given by a grayscale image.
+    Mask(ApertureMask),
+}
+
+impl Aperture {
+    /// Samples a point on the aperture, scaled to fit into the unit disk.
+    ///
+    /// The returned point lies in the XY plane.
+    pub fn sample(&self, rng: &Random) -> Vec3 {
+        match self {
+            Aperture::Circle => Vec3::random_in_unit_disk(rng),
+            Aperture::Polygon { blades, rotation } => {
+                // Pick one of the triangles fanning out from the center,
+                // then a uniformly distributed point within it.
+                let blades = (*blades).max(3);
+                let sector = ((rng.sample() * blades as f32) as usize).min(blades - 1);
+                let angle = |i: usize| rotation.0.to_radians() + 2. * PI * i as f32 / blades as f32;
+                let (a0, a1) = (angle(sector), angle(sector + 1));
+                let v0 = Vec3::new(a0.cos(), a0.sin(), 0.);
+                let v1 = Vec3::new(a1.cos(), a1.sin(), 0.);
+
+                let (mut s, mut t) = (rng.sample(), rng.sample());
+                if s + t > 1. {
+                    s = 1. - s;
+                    t = 1. - t;
+                }
+                v0 * s + v1 * t
+            }
+            Aperture::Mask(mask) => mask.sample(rng),
+        }
+    }
+}
+
+/// An aperture shape given by the transmittance of each pixel of an image.
+///
+/// The image is stretched to the square enclosing the unit disk, and pixels
+/// outside of the disk are treated as opaque.
+#[derive(Clone)]
+pub struct ApertureMask {
+    width: usize,
+    height: usize,
+    /// The cumulative distribution of the pixel transmittances, row by row.
+    cdf: Vec<f32>,
+}
+
+impl ApertureMask {
+    /// Creates a mask from transmittances in range `0.0..=1.0`, row by row starting at the top.
+    ///
+    /// Returns `None` if no light passes through the mask.
+    pub fn new(width: usize, height: usize, values: &[f32]) -> Option<Self> {
+        assert_eq!(values.len(), width * height, "mask size mismatch");
+        let mut mask = Self {
+            width,
+            height,
+            cdf: Vec::with_capacity(values.len()),
+        };
+        let mut sum = 0.;
+        for (pixel, v) in values.iter().enumerate() {
+            let center = mask.point(pixel, 0.5, 0.5);
+            if center.len_squared() <= 1. {
+                sum += v.clamp(0., 1.);
+            }
+            mask.cdf.push(sum);
+        }
+        if sum <= 0. {
+            return None;
+        }
+        mask.cdf.iter_mut().for_each(|c| *c /= sum);
+        Some(mask)
+    }
+
+    /// Loads the mask from a grayscale PGM image, where white lets light pass.
+    pub fn load_pgm(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let image = pgm::load(path)?;
+        Self::new(image.width, image.height, &image.values).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "the aperture mask is black",
+            )
+        })
+    }
+
+    /// Maps a position within a pixel to the square enclosing the unit disk.
+    fn point(&self, pixel: usize, s: f32, t: f32) -> Vec3 {
+        let (x, y) = (pixel % self.width, pixel / self.width);
+        let size = self.width.max(self.height) as f32;
+        let u = (x as f32 + s) - 0.5 * self.width as f32;
+        let v = 0.5 * self.height as f32 - (y as f32 + t);
+        Vec3::new(u, v, 0.) * (2. / size)
+    }
+
+    fn sample(&self, rng: &Random) -> Vec3 {
+        let xi = rng.sample();
+        let pixel = self
+            .cdf
+            .partition_point(|&c| c <= xi)
+            .min(self.cdf.len() - 1);
+
+        // The pixel's center is inside the disk, so this usually succeeds at once.
+        loop {
+            let p = self.point(pixel, rng.sample(), rng.sample());
+            if p.len_squared() <= 1. {
+                return p;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    pub fn polygon_samples_stay_inside() {
+        let rng = Random::default();
+        let aperture = Aperture::Polygon {
+            blades: 6,
+            rotation: Degrees(15.),
+        };
+        // The inscribed circle of the hexagon has radius cos(30°).
+        let inner = (PI / 6.).cos();
+        let samples: Vec<Vec3> = (0..1000).map(|_| aperture.sample(&rng)).collect();
+        assert!(samples.iter().all(|p| p.len() <= 1. + 1e-5));
+        assert!(samples.iter().any(|p| p.len() > inner));
+    }
+
+    #[test]
+    pub fn mask_samples_transmitting_pixels() {
+        let rng = Random::default();
+        // Only the top right pixel lets light through.
+        let mask = ApertureMask::new(2, 2, &[0., 1., 0., 0.]).unwrap();
+        for _ in 0..100 {
+            let p = mask.sample(&rng);
+            assert!((0. ..=1.).contains(&p.x()));
+            assert!((0. ..=1.).contains(&p.y()));
+            assert!(p.len() <= 1.);
+        }
+        assert!(ApertureMask::new(1, 1, &[0.]).is_none());
+
+        // The corners of the image lie outside of the unit disk.
+        let mut corners = [0.; 25];
+        corners[0] = 1.;
+        corners[24] = 1.;
+        assert!(ApertureMask::new(5, 5, &corners).is_none());
+    }
+}
@@ -0,0 +1,105 @@
+use crate::camera::{Camera, CameraRay, Shutter};
+use crate::random::Random;
+use crate::{Point3, Ray, Vec3};
+use std::f32::consts::PI;
+
+/// A 360° panorama camera using the equirectangular projection.
+///
+/// The horizontal image axis covers the full circle of longitudes, starting
+/// and ending behind the camera, and the vertical axis the latitudes from
+/// straight down to straight up. Images should have an aspect ratio of 2:1.
+pub struct EquirectangularCamera {
+    origin: Point3,
+    forward: Vec3,
+    right: Vec3,
+    up: Vec3,
+    /// The distance of the eye from the center of projection, see [`Self::with_eye_offset`].
+    eye_offset: f32,
+    shutter: Shutter,
+}
+
+impl EquirectangularCamera {
+    /// Constructs a panorama centered on the horizontal direction towards `look_at`.
+    pub fn new(look_from: Point3, look_at: Point3, view_up: Vec3) -> Self {
+        let up = view_up.as_unit_vector();
+        let direction = look_at - look_from;
+        let forward = (direction - up * direction.dot(&up)).as_unit_vector();
+        Self {
+            origin: look_from,
+            forward,
+            right: forward.cross(&up),
+            up,
+            eye_offset: 0.,
+            shutter: Shutter::default(),
+        }
+    }
+
+    /// Moves the eye sideways relative to each viewing direction, as for
+    /// omni-directional stereo; negative offsets give the left eye.
+    ///
+    /// A pair of panoramas with offsets of plus and minus half the
+    /// interocular distance forms a stereo panorama for VR.
+    pub fn with_eye_offset(mut self, offset: f32) -> Self {
+        self.eye_offset = offset;
+        self
+    }
+
+    pub fn with_shutter(mut self, shutter: Shutter) -> Self {
+        self.shutter = shutter;
+        self
+    }
+}
+
+impl Camera for EquirectangularCamera {
+    fn get_ray(&self, s: f32, t: f32, rng: &Random) -> Option<CameraRay> {
+        let longitude = (s - 0.5) * 2. * PI;
+        let latitude = (t - 0.5) * PI;
+
+        let (sin_lon, cos_lon) = longitude.sin_cos();
+        let horizontal = self.right * sin_lon + self.forward * cos_lon;
+        let direction = horizontal * latitude.cos() + self.up * latitude.sin();
+
+        // The eyes sit on a circle, perpendicular to the horizontal viewing direction.
+        let tangent = self.right * cos_lon - self.forward * sin_lon;
+        let origin = self.origin + tangent * self.eye_offset;
+
+        Some(
+            Ray::new(origin, direction)
+                .with_time(self.shutter.sample(rng))
+                .into(),
+        )
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    pub fn projection_works() {
+        let rng = Random::default();
+        let camera = EquirectangularCamera::new(
+            Point3::default(),
+            Point3::new(0., 1., -1.),
+            Vec3::new(0., 1., 0.),
+        );
+
+        // The image center looks horizontally towards the target.
+        let center = camera.get_ray(0.5, 0.5, &rng).unwrap().ray;
+        assert!((center.direction - Vec3::new(0., 0., -1.)).len() < 1e-5);
+
+        let right = camera.get_ray(0.75, 0.5, &rng).unwrap().ray;
+        assert!((right.direction - Vec3::new(1., 0., 0.)).len() < 1e-5);
+
+        let up = camera.get_ray(0.3, 1., &rng).unwrap().ray;
+        assert!((up.direction - Vec3::new(0., 1., 0.)).len() < 1e-5);
+
+        // The eye moves to the right of the viewing direction.
+        let eye = camera
+            .with_eye_offset(0.5)
+            .get_ray(0.5, 0.5, &rng)
+            .unwrap()
+            .ray;
+        assert!((eye.origin - Point3::new(0.5, 0., 0.)).len() < 1e-5);
+    }
+}
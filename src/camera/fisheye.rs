@@ -0,0 +1,120 @@
+use crate::camera::{basis, Camera, CameraRay, Shutter};
+use crate::random::Random;
+use crate::{Degrees, Point3, Ray, Vec3};
+
+/// How the angle from the optical axis maps to the distance from the image center.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FisheyeProjection {
+    /// The distance is proportional to the angle, `r = f·θ`.
+    Equidistant,
+    /// Preserves areas, `r = 2f·sin(θ/2)`.
+    Equisolid,
+}
+
+/// A fisheye camera projecting a circular image.
+///
+/// The image circle fills the image height; pixels outside of it stay black.
+pub struct FisheyeCamera {
+    origin: Point3,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+    /// Half the field of view in radians.
+    max_angle: f32,
+    aspect_ratio: f32,
+    projection: FisheyeProjection,
+    shutter: Shutter,
+}
+
+impl FisheyeCamera {
+    /// Constructs a camera with the specified field of view across the image circle.
+    ///
+    /// The image circle spans the image height, so wider images stay black beside it
+    /// and narrower images cut it off at the sides.
+    pub fn new(
+        look_from: Point3,
+        look_at: Point3,
+        view_up: Vec3,
+        fov: Degrees,
+        aspect_ratio: f32,
+        projection: FisheyeProjection,
+    ) -> Self {
+        let (u, v, w) = basis(look_from, look_at, view_up);
+        Self {
+            origin: look_from,
+            u,
+            v,
+            w,
+            max_angle: 0.5 * fov.0.to_radians(),
+            aspect_ratio,
+            projection,
+            shutter: Shutter::default(),
+        }
+    }
+
+    pub fn with_shutter(mut self, shutter: Shutter) -> Self {
+        self.shutter = shutter;
+        self
+    }
+
+    /// Determines the angle from the optical axis at the normalized distance from the image center.
+    fn angle(&self, r: f32) -> f32 {
+        match self.projection {
+            FisheyeProjection::Equidistant => r * self.max_angle,
+            FisheyeProjection::Equisolid => {
+                2. * (r * (0.5 * self.max_angle).sin()).clamp(-1., 1.).asin()
+            }
+        }
+    }
+}
+
+impl Camera for FisheyeCamera {
+    /// Returns `None` outside the image circle.
+    fn get_ray(&self, s: f32, t: f32, rng: &Random) -> Option<CameraRay> {
+        let x = (2. * s - 1.) * self.aspect_ratio;
+        let y = 2. * t - 1.;
+        let r = (x * x + y * y).sqrt();
+        if r > 1. {
+            return None;
+        }
+
+        let theta = self.angle(r);
+        let (sin_phi, cos_phi) = if r > 0. { (y / r, x / r) } else { (0., 1.) };
+        let direction = -self.w * theta.cos() + (self.u * cos_phi + self.v * sin_phi) * theta.sin();
+
+        Some(
+            Ray::new(self.origin, direction)
+                .with_time(self.shutter.sample(rng))
+                .into(),
+        )
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    pub fn image_circle_covers_field_of_view() {
+        let rng = Random::default();
+        for projection in [FisheyeProjection::Equidistant, FisheyeProjection::Equisolid] {
+            let camera = FisheyeCamera::new(
+                Point3::default(),
+                Point3::new(0., 0., -1.),
+                Vec3::new(0., 1., 0.),
+                Degrees(180.),
+                1.,
+                projection,
+            );
+
+            let center = camera.get_ray(0.5, 0.5, &rng).unwrap().ray;
+            assert!((center.direction - Vec3::new(0., 0., -1.)).len() < 1e-5);
+
+            // At the top of the image circle, the ray points straight up.
+            let top = camera.get_ray(0.5, 1., &rng).unwrap().ray;
+            assert!((top.direction - Vec3::new(0., 1., 0.)).len() < 1e-5);
+
+            assert!(camera.get_ray(1., 1., &rng).is_none());
+        }
+    }
+}
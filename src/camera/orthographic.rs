@@ -0,0 +1,50 @@
+use crate::camera::{basis, Camera, CameraRay, Shutter};
+use crate::random::Random;
+use crate::{Point3, Ray, Vec3};
+
+/// A camera with parallel rays, as used for technical drawings.
+pub struct OrthographicCamera {
+    lower_left_corner: Point3,
+    horizontal: Vec3,
+    vertical: Vec3,
+    direction: Vec3,
+    shutter: Shutter,
+}
+
+impl OrthographicCamera {
+    /// Constructs a camera showing a view of `view_height` world units around `look_from`.
+    pub fn new(
+        look_from: Point3,
+        look_at: Point3,
+        view_up: Vec3,
+        view_height: f32,
+        aspect_ratio: f32,
+    ) -> Self {
+        let (u, v, w) = basis(look_from, look_at, view_up);
+        let horizontal = view_height * aspect_ratio * u;
+        let vertical = view_height * v;
+        Self {
+            lower_left_corner: look_from - horizontal.half() - vertical.half(),
+            horizontal,
+            vertical,
+            direction: -w,
+            shutter: Shutter::default(),
+        }
+    }
+
+    pub fn with_shutter(mut self, shutter: Shutter) -> Self {
+        self.shutter = shutter;
+        self
+    }
+}
+
+impl Camera for OrthographicCamera {
+    fn get_ray(&self, s: f32, t: f32, rng: &Random) -> Option<CameraRay> {
+        let origin = self.lower_left_corner + s * self.horizontal + t * self.vertical;
+        Some(
+            Ray::new(origin, self.direction)
+                .with_time(self.shutter.sample(rng))
+                .into(),
+        )
+    }
+}
@@ -0,0 +1,148 @@
+use crate::camera::{basis, Aperture, Camera, CameraRay, Shutter};
+use crate::random::Random;
+use crate::{Degrees, Point3, Ray, Vec3};
+
+/// A perspective camera with a thin lens, producing depth of field.
+pub struct PerspectiveCamera {
+    origin: Point3,
+    lower_left_corner: Point3,
+    horizontal: Vec3,
+    vertical: Vec3,
+    u: Vec3,
+    v: Vec3,
+    lens_radius: f32,
+    aperture: Aperture,
+    /// How far the lens barrel clips the aperture towards the image corners.
+    cats_eye: f32,
+    shutter: Shutter,
+}
+
+impl PerspectiveCamera {
+    /// Constructs a new camera.
+    ///
+    /// # Arguments
+    /// * `look_from`: The origin point of the camera.
+    /// * `look_at`: The point to look at.
+    /// * `view_up`: The up axis of the camera.
+    /// * `vfov`: The vertical field of view in degrees.
+    /// * `aspect_ratio`: The aspect ratio.
+    /// * `aperture`: The aperture size of the lens.
+    /// * `focus_distance`: The focus distance of the lens.
+    pub fn new(
+        look_from: Point3,
+        look_at: Point3,
+        view_up: Vec3,
+        vfov: Degrees,
+        aspect_ratio: f32,
+        aperture: f32,
+        focus_distance: f32,
+    ) -> Self {
+        let theta = vfov.0.to_radians();
+        let h = (theta * 0.5).tan();
+        let viewport_height = 2. * h;
+        let viewport_width = aspect_ratio * viewport_height;
+
+        let (u, v, w) = basis(look_from, look_at, view_up);
+
+        let origin = look_from;
+        let horizontal = focus_distance * viewport_width * u;
+        let vertical = focus_distance * viewport_height * v;
+        let lower_left_corner = origin - horizontal.half() - vertical.half() - focus_distance * w;
+
+        Self {
+            origin,
+            horizontal,
+            vertical,
+            lower_left_corner,
+            u,
+            v,
+            lens_radius: aperture * 0.5,
+            aperture: Aperture::Circle,
+            cats_eye: 0.,
+            shutter: Shutter::default(),
+        }
+    }
+
+    /// Constructs a camera from the parameters of a physical camera.
+    ///
+    /// Scene units are taken to be meters. The field of view follows from the
+    /// sensor height and the focal length, and the aperture diameter from the
+    /// focal length and the f-number.
+    ///
+    /// # Arguments
+    /// * `focal_length`: The focal length of the lens in millimeters.
+    /// * `f_number`: The ratio of the focal length to the aperture diameter.
+    /// * `sensor_size`: The width and height of the sensor in millimeters.
+    pub fn physical(
+        look_from: Point3,
+        look_at: Point3,
+        view_up: Vec3,
+        focal_length: f32,
+        f_number: f32,
+        sensor_size: (f32, f32),
+        focus_distance: f32,
+    ) -> Self {
+        let (sensor_width, sensor_height) = sensor_size;
+        let vfov = 2. * (0.5 * sensor_height / focal_length).atan();
+        let aperture = focal_length / f_number * 1e-3;
+        Self::new(
+            look_from,
+            look_at,
+            view_up,
+            Degrees(vfov.to_degrees()),
+            sensor_width / sensor_height,
+            aperture,
+            focus_distance,
+        )
+    }
+
+    pub fn with_aperture(mut self, aperture: Aperture) -> Self {
+        self.aperture = aperture;
+        self
+    }
+
+    /// Clips the aperture by the lens barrel, giving out-of-focus highlights
+    /// a cat's eye shape and darkening them towards the image corners.
+    ///
+    /// At a strength of `1.0`, the barrel is offset by the aperture radius
+    /// at the centers of the image edges.
+    pub fn with_cats_eye(mut self, strength: f32) -> Self {
+        self.cats_eye = strength;
+        self
+    }
+
+    pub fn with_shutter(mut self, shutter: Shutter) -> Self {
+        self.shutter = shutter;
+        self
+    }
+}
+
+impl Camera for PerspectiveCamera {
+    /// Returns `None` if the sampled point on the lens is blocked by the lens barrel.
+    fn get_ray(&self, s: f32, t: f32, rng: &Random) -> Option<CameraRay> {
+        let lens = self.aperture.sample(rng);
+
+        // Light reaching the image corners passes the barrel at an angle, which
+        // is modeled by a second circle moving away from the image center.
+        if self.cats_eye > 0. {
+            let barrel = Vec3::new(2. * s - 1., 2. * t - 1., 0.) * self.cats_eye;
+            if (lens - barrel).len_squared() > 1. {
+                return None;
+            }
+        }
+
+        let rd = self.lens_radius * lens;
+        let offset = self.u * rd.x() + self.v * rd.y();
+
+        Some(
+            Ray::new(
+                self.origin + offset,
+                self.lower_left_corner + s * self.horizontal + t * self.vertical
+                    - self.origin
+                    - offset,
+            )
+            .with_time(self.shutter.sample(rng))
+            .into(),
+        )
+    }
+}
@@ -0,0 +1,534 @@
+use crate::camera::{basis, Camera, CameraRay, Shutter};
+use crate::material::Dispersion;
+use crate::random::Random;
+use crate::{Color, Point3, Ray, Vec3};
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+/// One surface of a lens system, as listed in a lens prescription.
+///
+/// All lengths are in millimeters.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LensElement {
+    /// The radius of curvature; positive if the center of curvature lies towards
+    /// the film. Zero denotes a flat surface, or the aperture stop if `ior` is zero.
+    pub radius: f32,
+    /// The distance along the optical axis to the next surface.
+    pub thickness: f32,
+    /// The index of refraction at the helium d-line of the medium behind the
+    /// surface, or zero for the aperture stop.
+    pub ior: f32,
+    /// The diameter of the surface.
+    pub aperture: f32,
+    /// The Abbe number of the medium behind the surface, if it is dispersive.
+    pub abbe_number: Option<f32>,
+}
+
+impl LensElement {
+    fn is_stop(&self) -> bool {
+        self.ior == 0.
+    }
+
+    /// Determines the index of refraction behind the surface at the specified wavelength.
+    fn ior_at(&self, wavelength: f32) -> f32 {
+        if self.is_stop() {
+            return 1.;
+        }
+        match self.abbe_number {
+            // Fit Cauchy's equation through the d-line index and the Abbe number,
+            // i.e. the dispersion between the hydrogen F- and C-lines.
+            Some(v) => {
+                let (d, f, c) = (0.587_6f32, 0.486_1f32, 0.656_3f32);
+                let b = (self.ior - 1.) / (v * (1. / (f * f) - 1. / (c * c)));
+                let a = self.ior - b / (d * d);
+                Dispersion::Cauchy { a, b }.ior(wavelength)
+            }
+            None => self.ior,
+        }
+    }
+}
+
+/// Parses a lens prescription table.
+///
+/// Each line lists one surface from the front to the rear of the lens as
+/// `radius thickness ior aperture [abbe number]`; text after `#` is ignored.
+pub fn parse_prescription(table: &str) -> std::io::Result<Vec<LensElement>> {
+    let invalid = |message: String| Error::new(ErrorKind::InvalidData, message);
+    let mut elements = Vec::new();
+    for (number, line) in table.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default();
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let values = line
+            .split_whitespace()
+            .map(|v| v.parse::<f32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| invalid(format!("invalid number in line {}", number + 1)))?;
+        if values.len() != 4 && values.len() != 5 {
+            return Err(invalid(format!(
+                "expected 4 or 5 values in line {}",
+                number + 1
+            )));
+        }
+
+        elements.push(LensElement {
+            radius: values[0],
+            thickness: values[1],
+            ior: values[2],
+            aperture: values[3],
+            abbe_number: values.get(4).copied(),
+        });
+    }
+
+    if elements.is_empty() {
+        return Err(invalid("the prescription lists no surfaces".to_string()));
+    }
+    Ok(elements)
+}
+
+/// Loads a lens prescription table from a file, see [`parse_prescription`].
+pub fn load_prescription(path: impl AsRef<Path>) -> std::io::Result<Vec<LensElement>> {
+    parse_prescription(&std::fs::read_to_string(path)?)
+}
+
+/// A camera tracing rays through a system of spherical lens elements.
+///
+/// Rays start on the film and are refracted at each lens surface, so the
+/// distortion, vignetting and chromatic aberration of the lens design
+/// appear in the image. Scene units are taken to be meters.
+pub struct RealisticCamera {
+    elements: Vec<LensElement>,
+    /// The position of each surface along the optical axis, which points
+    /// from the scene towards the film.
+    positions: Vec<f32>,
+    film_position: f32,
+    film_size: (f32, f32),
+    /// The radius of the disk on the rear surface's plane covering every ray
+    /// that passes the lens from anywhere on the film.
+    pupil_radius: f32,
+    /// The fraction of rays from the film center passing the lens,
+    /// used to normalize the image brightness.
+    center_transmission: f32,
+    origin: Point3,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+    shutter: Shutter,
+}
+
+impl RealisticCamera {
+    /// The double Gauss design of US patent 2,673,491 by Tronnier, scaled to 50 mm.
+    ///
+    /// The Abbe numbers are not part of the patent's table and have been
+    /// chosen to match common glasses of the listed indices.
+    pub const DOUBLE_GAUSS: &'static str = "\
+        # radius  thickness  ior    aperture  abbe
+        29.475    3.76       1.67   25.2      47.2
+        84.83     0.12       1      25.2
+        19.275    4.025      1.67   23        47.2
+        40.77     3.275      1.699  23        30.1
+        12.75     5.705      1      18
+        0         4.5        0      17.1      # aperture stop
+        -14.495   1.18       1.603  17        38.0
+        40.77     6.065      1.658  20        57.3
+        -20.385   0.19       1      20
+        437.065   3.22       1.717  20        48.0
+        -39.73    0          1      20
+    ";
+
+    /// Constructs a camera focused on objects `focus_distance` meters in front of the lens.
+    ///
+    /// The `film_size` is given in millimeters. Returns `None` if the lens
+    /// cannot focus at the specified distance.
+    pub fn new(
+        look_from: Point3,
+        look_at: Point3,
+        view_up: Vec3,
+        elements: Vec<LensElement>,
+        film_size: (f32, f32),
+        focus_distance: f32,
+    ) -> Option<Self> {
+        let mut positions = Vec::with_capacity(elements.len());
+        let mut z = 0.;
+        for element in &elements {
+            positions.push(z);
+            z += element.thickness;
+        }
+
+        let (u, v, w) = basis(look_from, look_at, view_up);
+        let mut camera = Self {
+            elements,
+            positions,
+            film_position: 0.,
+            film_size,
+            pupil_radius: 0.,
+            center_transmission: 1.,
+            origin: look_from,
+            u,
+            v,
+            w,
+            shutter: Shutter::default(),
+        };
+
+        camera.film_position = camera.focus(focus_distance * 1e3)?;
+        camera.pupil_radius = camera.find_pupil_radius()?;
+        camera.center_transmission = camera.transmission(0., 0.);
+        Some(camera)
+    }
+
+    pub fn with_shutter(mut self, shutter: Shutter) -> Self {
+        self.shutter = shutter;
+        self
+    }
+
+    fn rear_position(&self) -> f32 {
+        *self.positions.last().unwrap_or(&0.)
+    }
+
+    /// Refracts a ray at one surface, returning the hit point and the refracted direction.
+    ///
+    /// `eta` is the ratio of the indices of refraction before and after the surface.
+    fn refract(
+        &self,
+        index: usize,
+        origin: &Point3,
+        direction: &Vec3,
+        eta: f32,
+    ) -> Option<(Point3, Vec3)> {
+        let element = &self.elements[index];
+        let z = self.positions[index];
+
+        let (t, normal) = if element.radius == 0. {
+            (
+                (z - origin.z()) / direction.z(),
+                Vec3::new(0., 0., -direction.z().signum()),
+            )
+        } else {
+            // Use the part of the sphere closest to the vertex.
+            let center = Point3::new(0., 0., z + element.radius);
+            let oc = *origin - center;
+            let half_b = oc.dot(direction);
+            let c = oc.len_squared() - element.radius * element.radius;
+            let discriminant = half_b * half_b - c;
+            if discriminant < 0. {
+                return None;
+            }
+            let closer = (direction.z() > 0.) != (element.radius < 0.);
+            let t = if closer {
+                -half_b - discriminant.sqrt()
+            } else {
+                -half_b + discriminant.sqrt()
+            };
+            let normal = (*origin + t * *direction - center) / element.radius;
+            let normal = if normal.dot(direction) > 0. {
+                -normal
+            } else {
+                normal
+            };
+            (t, normal)
+        };
+        if t <= 0. {
+            return None;
+        }
+
+        let p = *origin + t * *direction;
+        let radius = 0.5 * element.aperture;
+        if p.x() * p.x() + p.y() * p.y() > radius * radius {
+            return None;
+        }
+        if element.is_stop() || eta == 1. {
+            return Some((p, *direction));
+        }
+
+        let cos_i = (-*direction).dot(&normal).min(1.);
+        if eta * eta * (1. - cos_i * cos_i) > 1. {
+            return None;
+        }
+        Some((p, direction.refract(&normal, eta).as_unit_vector()))
+    }
+
+    /// The index of refraction in front of the surface at `index`.
+    fn ior_before(&self, index: usize, wavelength: f32) -> f32 {
+        match index {
+            0 => 1.,
+            _ => self.elements[index - 1].ior_at(wavelength),
+        }
+    }
+
+    /// Traces a ray from the film side through all surfaces towards the scene.
+    fn trace_from_film(
+        &self,
+        origin: Point3,
+        direction: Vec3,
+        wavelength: f32,
+    ) -> Option<(Point3, Vec3)> {
+        let (mut p, mut d) = (origin, direction.as_unit_vector());
+        for index in (0..self.elements.len()).rev() {
+            let eta = self.elements[index].ior_at(wavelength) / self.ior_before(index, wavelength);
+            let (next_p, next_d) = self.refract(index, &p, &d, eta)?;
+            p = next_p;
+            d = next_d;
+        }
+        Some((p, d))
+    }
+
+    /// Traces a ray from the scene side through all surfaces towards the film.
+    fn trace_from_scene(
+        &self,
+        origin: Point3,
+        direction: Vec3,
+        wavelength: f32,
+    ) -> Option<(Point3, Vec3)> {
+        let (mut p, mut d) = (origin, direction.as_unit_vector());
+        for index in 0..self.elements.len() {
+            let eta = self.ior_before(index, wavelength) / self.elements[index].ior_at(wavelength);
+            let (next_p, next_d) = self.refract(index, &p, &d, eta)?;
+            p = next_p;
+            d = next_d;
+        }
+        Some((p, d))
+    }
+
+    /// Finds the film position at which an object at `distance` millimeters
+    /// in front of the lens is in focus, using a paraxial ray.
+    fn focus(&self, distance: f32) -> Option<f32> {
+        let height = 0.01 * self.elements[0].aperture;
+        let object = Point3::new(0., 0., -distance);
+        let (p, d) = self.trace_from_scene(
+            object,
+            Point3::new(height, 0., 0.) - object,
+            Dispersion::RGB_WAVELENGTHS[1],
+        )?;
+        if d.x() >= 0. {
+            return None;
+        }
+        let film = p.z() - p.x() / d.x() * d.z();
+        if film <= self.rear_position() {
+            return None;
+        }
+        Some(film)
+    }
+
+    /// The effective focal length in millimeters, determined using a paraxial ray.
+    #[cfg(test)]
+    fn focal_length(&self) -> Option<f32> {
+        let height = 0.01 * self.elements[0].aperture;
+        let (_, d) = self.trace_from_scene(
+            Point3::new(height, 0., -1.),
+            Vec3::new(0., 0., 1.),
+            Dispersion::RGB_WAVELENGTHS[1],
+        )?;
+        Some(-height * d.z() / d.x())
+    }
+
+    /// Finds the radius of the rear pupil disk by tracing a grid of rays
+    /// from points across the film.
+    fn find_pupil_radius(&self) -> Option<f32> {
+        const FILM_STEPS: usize = 8;
+        const GRID_STEPS: usize = 48;
+
+        let rear = self.elements.last()?;
+        let rear_radius = 0.5 * rear.aperture;
+        let diagonal = 0.5 * (self.film_size.0.powi(2) + self.film_size.1.powi(2)).sqrt();
+
+        let mut pupil_radius: f32 = 0.;
+        for i in 0..=FILM_STEPS {
+            let film = Point3::new(
+                diagonal * i as f32 / FILM_STEPS as f32,
+                0.,
+                self.film_position,
+            );
+            for gy in 0..GRID_STEPS {
+                for gx in 0..GRID_STEPS {
+                    let x = rear_radius * (2. * (gx as f32 + 0.5) / GRID_STEPS as f32 - 1.);
+                    let y = rear_radius * (2. * (gy as f32 + 0.5) / GRID_STEPS as f32 - 1.);
+                    let target = Point3::new(x, y, self.rear_position());
+                    let passes = self
+                        .trace_from_film(film, target - film, Dispersion::RGB_WAVELENGTHS[1])
+                        .is_some();
+                    if passes {
+                        pupil_radius = pupil_radius.max((x * x + y * y).sqrt());
+                    }
+                }
+            }
+        }
+
+        if pupil_radius == 0. {
+            return None;
+        }
+        // Grow the disk by one grid cell to cover the gaps between the samples.
+        Some((pupil_radius + 2. * rear_radius / GRID_STEPS as f32).min(rear_radius))
+    }
+
+    /// Determines the fraction of rays from the film point passing the lens.
+    fn transmission(&self, x: f32, y: f32) -> f32 {
+        const STEPS: usize = 64;
+        let film = Point3::new(x, y, self.film_position);
+        let (mut inside, mut passing) = (0, 0);
+        for gy in 0..STEPS {
+            for gx in 0..STEPS {
+                let px = 2. * (gx as f32 + 0.5) / STEPS as f32 - 1.;
+                let py = 2. * (gy as f32 + 0.5) / STEPS as f32 - 1.;
+                if px * px + py * py > 1. {
+                    continue;
+                }
+                inside += 1;
+                let target = Point3::new(
+                    px * self.pupil_radius,
+                    py * self.pupil_radius,
+                    self.rear_position(),
+                );
+                if self
+                    .trace_from_film(film, target - film, Dispersion::RGB_WAVELENGTHS[1])
+                    .is_some()
+                {
+                    passing += 1;
+                }
+            }
+        }
+        (passing as f32 / inside.max(1) as f32).max(1e-3)
+    }
+}
+
+impl RealisticCamera {
+    /// Traces a ray from the film through the lens at the wavelength of a random
+    /// color channel, given the wavelengths in nanometers the channels represent.
+    ///
+    /// Chromatic aberration requires tracing a single wavelength per ray,
+    /// so only the traced channel is weighted, compensating for the others.
+    fn sample_ray(
+        &self,
+        s: f32,
+        t: f32,
+        channel_wavelengths: &Vec3,
+        rng: &Random,
+    ) -> Option<CameraRay> {
+        // The lens flips the image, so the film is mirrored around its center.
+        let film = Point3::new(
+            (0.5 - s) * self.film_size.0,
+            (0.5 - t) * self.film_size.1,
+            self.film_position,
+        );
+        let disk = Vec3::random_in_unit_disk(rng) * self.pupil_radius;
+        let target = Point3::new(disk.x(), disk.y(), self.rear_position());
+        let direction = (target - film).as_unit_vector();
+
+        let channel = ((rng.sample() * 3.) as usize).min(2);
+        let wavelength = channel_wavelengths.e[channel];
+        let (p, d) = self.trace_from_film(film, direction, wavelength)?;
+
+        // Radiance falls off with the fourth power of the cosine towards the axis.
+        let cos_theta = direction.z().abs();
+        let mut weight = Color::default();
+        weight.e[channel] = 3. * cos_theta.powi(4) / self.center_transmission;
+
+        // Lens space uses millimeters, with the scene lying towards -Z.
+        let to_world = |v: &Vec3| v.x() * self.u + v.y() * self.v + v.z() * self.w;
+        let origin = self.origin + to_world(&(p - Point3::new(0., 0., self.film_position))) * 1e-3;
+        let ray = Ray::new(origin, to_world(&d))
+            .with_time(self.shutter.sample(rng))
+            .with_wavelength(wavelength);
+
+        Some(CameraRay { ray, weight })
+    }
+}
+
+impl Camera for RealisticCamera {
+    /// Returns `None` if the ray is blocked inside the lens.
+    fn get_ray(&self, s: f32, t: f32, rng: &Random) -> Option<CameraRay> {
+        let [r, g, b] = Dispersion::RGB_WAVELENGTHS;
+        self.sample_ray(s, t, &Vec3::new(r, g, b), rng)
+    }
+
+    /// Traces the lens at one of the sampled wavelengths.
+    fn get_spectral_ray(
+        &self,
+        s: f32,
+        t: f32,
+        wavelengths: Vec3,
+        rng: &Random,
+    ) -> Option<CameraRay> {
+        let sample = self.sample_ray(s, t, &wavelengths, rng)?;
+        Some(CameraRay {
+            ray: sample.ray.with_wavelengths(wavelengths),
+            ..sample
+        })
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    fn double_gauss(focus_distance: f32) -> RealisticCamera {
+        RealisticCamera::new(
+            Point3::default(),
+            Point3::new(0., 0., -1.),
+            Vec3::new(0., 1., 0.),
+            parse_prescription(RealisticCamera::DOUBLE_GAUSS).unwrap(),
+            (36., 24.),
+            focus_distance,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    pub fn parse_prescription_works() {
+        let elements = parse_prescription(RealisticCamera::DOUBLE_GAUSS).unwrap();
+        assert_eq!(elements.len(), 11);
+        assert!(elements[5].is_stop());
+        assert_eq!(elements[3].abbe_number, Some(30.1));
+        assert_eq!(elements[4].abbe_number, None);
+
+        assert!(parse_prescription("1 2 3").is_err());
+        assert!(parse_prescription("# nothing").is_err());
+    }
+
+    #[test]
+    pub fn double_gauss_has_50mm_focal_length() {
+        let camera = double_gauss(10.);
+        let focal_length = camera.focal_length().unwrap();
+        assert!((focal_length - 50.).abs() < 2.5, "{}", focal_length);
+    }
+
+    #[test]
+    pub fn rays_from_film_center_converge_at_focus() {
+        let camera = double_gauss(2.);
+        let film = Point3::new(0., 0., camera.film_position);
+        for x in [-0.2, 0.1] {
+            let target = Point3::new(x * camera.pupil_radius, 0., camera.rear_position());
+            let (p, d) = camera
+                .trace_from_film(film, target - film, Dispersion::RGB_WAVELENGTHS[1])
+                .unwrap();
+            // Where the ray crosses the optical axis, measured in front of the lens.
+            let crossing = p.z() - p.x() / d.x() * d.z();
+            assert!((crossing + 2000.).abs() < 100., "{}", crossing);
+        }
+    }
+
+    #[test]
+    pub fn spectral_rays_trace_a_sampled_wavelength() {
+        let camera = double_gauss(10.);
+        let rng = Random::default();
+        let wavelengths = Vec3::new(420., 530., 640.);
+        for _ in 0..100 {
+            let sample = match camera.get_spectral_ray(0.9, 0.8, wavelengths, &rng) {
+                Some(sample) => sample,
+                None => continue,
+            };
+            assert_eq!(sample.ray.wavelengths, Some(wavelengths));
+            let channel = (0..3).find(|&i| sample.weight.e[i] > 0.).unwrap();
+            assert_eq!(sample.ray.wavelength, Some(wavelengths.e[channel]));
+            assert_eq!(sample.weight.e.iter().filter(|&&w| w > 0.).count(), 1);
+        }
+    }
+
+    #[test]
+    pub fn blue_light_refracts_stronger() {
+        let elements = parse_prescription(RealisticCamera::DOUBLE_GAUSS).unwrap();
+        assert!(elements[0].ior_at(465.) > elements[0].ior_at(610.));
+        assert!((elements[0].ior_at(587.6) - 1.67).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,95 @@
+use crate::camera::{Camera, CameraRay};
+use crate::random::Random;
+use crate::Vec3;
+
+/// The arrangement of the two views in a stereo image.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum StereoLayout {
+    /// The left eye's view in the left, the right eye's view in the right half.
+    SideBySide,
+    /// The left eye's view in the top, the right eye's view in the bottom half.
+    OverUnder,
+}
+
+/// Renders the views of two cameras, one per eye, into a single image.
+///
+/// The cameras should be set up for the aspect ratio of their half of the image.
+pub struct StereoCamera {
+    left: Box<dyn Camera>,
+    right: Box<dyn Camera>,
+    layout: StereoLayout,
+}
+
+impl StereoCamera {
+    pub fn new(left: Box<dyn Camera>, right: Box<dyn Camera>, layout: StereoLayout) -> Self {
+        Self {
+            left,
+            right,
+            layout,
+        }
+    }
+}
+
+impl Camera for StereoCamera {
+    fn get_ray(&self, s: f32, t: f32, rng: &Random) -> Option<CameraRay> {
+        match self.layout {
+            StereoLayout::SideBySide if s < 0.5 => self.left.get_ray(2. * s, t, rng),
+            StereoLayout::SideBySide => self.right.get_ray(2. * s - 1., t, rng),
+            StereoLayout::OverUnder if t >= 0.5 => self.left.get_ray(s, 2. * t - 1., rng),
+            StereoLayout::OverUnder => self.right.get_ray(s, 2. * t, rng),
+        }
+    }
+
+    fn get_spectral_ray(
+        &self,
+        s: f32,
+        t: f32,
+        wavelengths: Vec3,
+        rng: &Random,
+    ) -> Option<CameraRay> {
+        match self.layout {
+            StereoLayout::SideBySide if s < 0.5 => {
+                self.left.get_spectral_ray(2. * s, t, wavelengths, rng)
+            }
+            StereoLayout::SideBySide => {
+                self.right
+                    .get_spectral_ray(2. * s - 1., t, wavelengths, rng)
+            }
+            StereoLayout::OverUnder if t >= 0.5 => {
+                self.left.get_spectral_ray(s, 2. * t - 1., wavelengths, rng)
+            }
+            StereoLayout::OverUnder => self.right.get_spectral_ray(s, 2. * t, wavelengths, rng),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use crate::camera::OrthographicCamera;
+    use crate::Point3;
+
+    #[test]
+    pub fn views_are_split() {
+        let rng = Random::default();
+        let eye = |x: f32| -> Box<dyn Camera> {
+            Box::new(OrthographicCamera::new(
+                Point3::new(x, 0., 0.),
+                Point3::new(x, 0., -1.),
+                Vec3::new(0., 1., 0.),
+                1.,
+                1.,
+            ))
+        };
+
+        let camera = StereoCamera::new(eye(-1.), eye(1.), StereoLayout::SideBySide);
+        let left = camera.get_ray(0.25, 0.5, &rng).unwrap().ray;
+        let right = camera.get_ray(0.75, 0.5, &rng).unwrap().ray;
+        assert!((left.origin - Point3::new(-1., 0., 0.)).len() < 1e-6);
+        assert!((right.origin - Point3::new(1., 0., 0.)).len() < 1e-6);
+
+        let camera = StereoCamera::new(eye(-1.), eye(1.), StereoLayout::OverUnder);
+        let top = camera.get_ray(0.5, 0.75, &rng).unwrap().ray;
+        assert!((top.origin - Point3::new(-1., 0., 0.)).len() < 1e-6);
+    }
+}
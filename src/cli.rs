@@ -0,0 +1,713 @@
+//! Command line options.
+//!
+//! Usage: `raytracing [scene] [options]`, where the scene defaults to `random`.
+//!
+//! Options:
+//! * `--aperture <shape>`: `circle`, `polygon:<blades>[:<rotation in degrees>]` or `mask:<file.pgm>`.
+//! * `--cats-eye <strength>`: Clips the aperture by the lens barrel towards the image corners.
+//! * `--focal-length <mm>`: Uses a physical lens on a full frame sensor instead of the default field of view.
+//! * `--f-number <n>`: The f-number of the physical lens; defaults to `2.8`.
+//! * `--projection <projection>`: `perspective`, `orthographic[:<view height>]`, `equirectangular`,
+//!   `fisheye[:equidistant|equisolid][:<field of view in degrees>]` or `lens[:<prescription file>]`,
+//!   the latter tracing rays through a multi-element lens, by default a 50 mm double Gauss design.
+//! * `--stereo <layout>[:<interocular distance>]`: Renders one view per eye, laid out
+//!   `side-by-side` or `over-under`.
+//! * `--size <width>x<height>`: The image size in pixels; defaults to `1200x800`.
+//! * `--focus <focus>`: `<distance>`, `point:<x>,<y>,<z>`, `pixel:<x>,<y>` to focus on the scene
+//!   seen through a pixel counted from the top left corner, or `auto` for the center pixel.
+//!   Overrides the scene's focus.
+//! * `--filter <filter>[:<radius in pixels>]`: The pixel reconstruction filter; `box` (the default,
+//!   with a radius of 0.5), `tent` (1), `gaussian` (1.5), `mitchell` (2) or `lanczos` (3).
+//! * `--sampler <sampler>`: Generates the sample values using `independent` random numbers
+//!   (the default), `stratified` jittered samples, the `halton` sequence, the Owen-scrambled
+//!   `sobol` sequence or `blue-noise` shifted Sobol samples.
+//! * `--bloom <intensity>[:<threshold>[:<radius>]]`: Adds a glow around pixels brighter than the
+//!   threshold (default `1`), with a radius given as a fraction of the image height (default `0.02`).
+//! * `--glare <intensity>[:<streaks>[:<length>[:<rotation in degrees>]]]`: Adds star shaped streaks
+//!   (default `6`) around pixels brighter than `1`, fading over a fraction of the image height
+//!   (default `0.05`).
+//! * `--chromatic-aberration <scale>`: Separates the red and blue channels towards the corners.
+//! * `--vignette <strength>`: Darkens the corners following the cosine-fourth law.
+//! * `--grain <amount>`: Adds film grain with the specified relative standard deviation.
+//! * `--exposure <stops>`: Brightens or darkens the image before tone mapping.
+//! * `--tone-map <operator>`: `clamp` (the default), `reinhard[:<white luminance>]`, `aces` or `agx`.
+//! * `--transfer <function>`: Encodes the output using `srgb` (the default), `rec709` or `gamma:<gamma>`.
+//! * `--working-space <space>`: The color space rendering happens in; `rec709` (the default),
+//!   `acescg`, `display-p3` or `rec2020`.
+//! * `--output-space <space>`: The primaries of the output image; `srgb` (the default),
+//!   `display-p3` or `rec2020`. Combine with `--transfer rec709` for Rec. 2020 video.
+//! * `--spectral <on|off>`: Renders using hero wavelength sampling instead of RGB; off by default.
+//! * `--dither <on|off>`: Adds noise before quantizing to avoid banding; off by default.
+//! * `--terminal-preview <graphics>[:<columns>]`: Periodically draws the image into the terminal
+//!   instead of the progress bar, using `blocks` of 24-bit colors, the `kitty` graphics protocol,
+//!   or `auto` to detect kitty support. The image is 80 columns wide by default.
+//! * `--stats <on|off>`: Prints statistics about the rays traced and the time spent once done;
+//!   off by default.
+//! * `--stats-json <file>`: Writes these statistics to a JSON file.
+//! * `--heatmap <metric>[:<file>]`: Also writes a false color image of the cost per sample of
+//!   each pixel, measured in render `time`, object intersection `tests` or acceleration structure
+//!   `nodes` visited, to `heatmap.ppm` by default.
+//! * `--preview <on|off>`: Shows the image in a window while it renders, where dragging with the
+//!   left or right mouse button (or the arrow keys and `WASD`) orbits or pans the camera and the
+//!   mouse wheel (or `+` and `-`) zooms. Requires building with `--features preview`.
+
+use crate::camera::{
+    load_prescription, parse_prescription, Aperture, ApertureMask, FisheyeProjection, Focus,
+    LensElement, RealisticCamera, StereoLayout,
+};
+use crate::color_space::ColorSpace;
+use crate::film::Filter;
+use crate::heatmap::HeatmapMetric;
+use crate::post::{Bloom, Glare, PostEffects};
+use crate::terminal::TerminalGraphics;
+use crate::tonemap::{ToneMapping, TransferFunction};
+use crate::{Degrees, Point3};
+use std::io::{Error, ErrorKind};
+
+/// The camera projection.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Projection {
+    Perspective,
+    Orthographic {
+        view_height: f32,
+    },
+    Equirectangular,
+    Fisheye {
+        projection: FisheyeProjection,
+        fov: Degrees,
+    },
+    /// A realistic lens given by its prescription.
+    Lens(Vec<LensElement>),
+}
+
+/// The generator of sample values.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SamplerType {
+    Independent,
+    Stratified,
+    Halton,
+    Sobol,
+    BlueNoise,
+}
+
+pub struct Options {
+    pub scene: String,
+    pub aperture: Aperture,
+    pub cats_eye: f32,
+    /// The focal length in millimeters, if a physical lens is used.
+    pub focal_length: Option<f32>,
+    pub f_number: f32,
+    pub projection: Projection,
+    /// The stereo layout and interocular distance, if one view per eye is rendered.
+    pub stereo: Option<(StereoLayout, f32)>,
+    /// The image width and height in pixels.
+    pub size: (usize, usize),
+    /// The focus overriding the scene's, if any.
+    pub focus: Option<Focus>,
+    /// The exposure adjustment in stops.
+    pub exposure: f32,
+    pub tone_mapping: ToneMapping,
+    pub transfer: TransferFunction,
+    pub dither: bool,
+    pub working_space: ColorSpace,
+    pub output_space: ColorSpace,
+    pub spectral: bool,
+    pub filter: Filter,
+    pub sampler: SamplerType,
+    pub post_effects: PostEffects,
+    /// Whether to show the image in a window while it renders.
+    pub preview: bool,
+    /// How to draw the image into the terminal and how many columns wide, if at all.
+    pub terminal_preview: Option<(TerminalGraphics, usize)>,
+    /// Whether to print render statistics.
+    pub stats: bool,
+    /// The file to write render statistics to, if any.
+    pub stats_json: Option<String>,
+    /// What to measure the cost of the pixels in and the file to write the heatmap to, if any.
+    pub heatmap: Option<(HeatmapMetric, String)>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            scene: "random".to_string(),
+            aperture: Aperture::Circle,
+            cats_eye: 0.,
+            focal_length: None,
+            f_number: 2.8,
+            projection: Projection::Perspective,
+            stereo: None,
+            size: (1200, 800),
+            focus: None,
+            exposure: 0.,
+            tone_mapping: ToneMapping::Clamp,
+            transfer: TransferFunction::Srgb,
+            dither: false,
+            working_space: ColorSpace::Rec709,
+            output_space: ColorSpace::Rec709,
+            spectral: false,
+            filter: Filter::default(),
+            sampler: SamplerType::Independent,
+            post_effects: PostEffects::default(),
+            preview: false,
+            terminal_preview: None,
+            stats: false,
+            stats_json: None,
+            heatmap: None,
+        }
+    }
+}
+
+fn invalid_input(message: String) -> Error {
+    Error::new(ErrorKind::InvalidInput, message)
+}
+
+fn parse_number(option: &str, value: &str) -> std::io::Result<f32> {
+    value
+        .parse()
+        .map_err(|_| invalid_input(format!("invalid value for {}: {}", option, value)))
+}
+
+fn parse_aperture(value: &str) -> std::io::Result<Aperture> {
+    let mut parts = value.splitn(2, ':');
+    match (parts.next(), parts.next()) {
+        (Some("circle"), None) => Ok(Aperture::Circle),
+        (Some("polygon"), Some(arguments)) => {
+            let mut arguments = arguments.split(':');
+            let blades = arguments
+                .next()
+                .and_then(|blades| blades.parse::<usize>().ok())
+                .filter(|&blades| blades >= 3)
+                .ok_or_else(|| invalid_input(format!("invalid number of blades: {}", value)))?;
+            let rotation = match arguments.next() {
+                Some(rotation) => parse_number("--aperture", rotation)?,
+                None => 0.,
+            };
+            Ok(Aperture::Polygon {
+                blades,
+                rotation: Degrees(rotation),
+            })
+        }
+        (Some("mask"), Some(path)) => Ok(Aperture::Mask(ApertureMask::load_pgm(path)?)),
+        _ => Err(invalid_input(format!("unknown aperture: {}", value))),
+    }
+}
+
+fn parse_projection(value: &str) -> std::io::Result<Projection> {
+    let mut parts = value.split(':');
+    match parts.next() {
+        Some("perspective") => Ok(Projection::Perspective),
+        Some("orthographic") => Ok(Projection::Orthographic {
+            view_height: match parts.next() {
+                Some(height) => parse_number("--projection", height)?,
+                None => 5.,
+            },
+        }),
+        Some("equirectangular") => Ok(Projection::Equirectangular),
+        Some("fisheye") => {
+            let mut projection = FisheyeProjection::Equidistant;
+            let mut fov = Degrees(180.);
+            for part in parts {
+                match part {
+                    "equidistant" => projection = FisheyeProjection::Equidistant,
+                    "equisolid" => projection = FisheyeProjection::Equisolid,
+                    _ => fov = Degrees(parse_number("--projection", part)?),
+                }
+            }
+            Ok(Projection::Fisheye { projection, fov })
+        }
+        Some("lens") => Ok(Projection::Lens(match parts.next() {
+            Some(path) => load_prescription(path)?,
+            None => parse_prescription(RealisticCamera::DOUBLE_GAUSS)?,
+        })),
+        _ => Err(invalid_input(format!("unknown projection: {}", value))),
+    }
+}
+
+fn parse_stereo(value: &str) -> std::io::Result<(StereoLayout, f32)> {
+    let mut parts = value.split(':');
+    let layout = match parts.next() {
+        Some("side-by-side") => StereoLayout::SideBySide,
+        Some("over-under") => StereoLayout::OverUnder,
+        _ => return Err(invalid_input(format!("unknown stereo layout: {}", value))),
+    };
+    let interocular = match parts.next() {
+        Some(distance) => parse_number("--stereo", distance)?,
+        None => 0.065,
+    };
+    Ok((layout, interocular))
+}
+
+fn parse_terminal_preview(value: &str) -> std::io::Result<(TerminalGraphics, usize)> {
+    let mut parts = value.split(':');
+    let graphics = match parts.next() {
+        Some("blocks") => TerminalGraphics::Blocks,
+        Some("kitty") => TerminalGraphics::Kitty,
+        Some("auto") => TerminalGraphics::detect(),
+        _ => {
+            return Err(invalid_input(format!(
+                "unknown terminal graphics: {}",
+                value
+            )))
+        }
+    };
+    let columns = match parts.next() {
+        Some(columns) => columns
+            .parse()
+            .ok()
+            .filter(|&columns| columns > 0)
+            .ok_or_else(|| invalid_input(format!("invalid terminal preview width: {}", value)))?,
+        None => 80,
+    };
+    Ok((graphics, columns))
+}
+
+fn parse_heatmap(value: &str) -> std::io::Result<(HeatmapMetric, String)> {
+    let (metric, path) = value.split_once(':').unwrap_or((value, "heatmap.ppm"));
+    let metric = match metric {
+        "time" => HeatmapMetric::Time,
+        "tests" => HeatmapMetric::IntersectionTests,
+        "nodes" => HeatmapMetric::NodeVisits,
+        _ => return Err(invalid_input(format!("unknown heatmap metric: {}", value))),
+    };
+    Ok((metric, path.to_string()))
+}
+
+fn parse_size(value: &str) -> std::io::Result<(usize, usize)> {
+    value
+        .split_once('x')
+        .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+        .filter(|&(width, height)| width > 1 && height > 1)
+        .ok_or_else(|| invalid_input(format!("invalid image size: {}", value)))
+}
+
+/// Parses a focus; `auto` is returned as `None` since it depends on the image size.
+fn parse_focus(value: &str) -> std::io::Result<Option<Focus>> {
+    let invalid = || invalid_input(format!("invalid focus: {}", value));
+    let numbers = |values: &str| -> std::io::Result<Vec<f32>> {
+        values
+            .split(',')
+            .map(|v| parse_number("--focus", v))
+            .collect()
+    };
+    match value.split_once(':') {
+        None if value == "auto" => Ok(None),
+        None => Ok(Some(Focus::Distance(parse_number("--focus", value)?))),
+        Some(("point", point)) => match numbers(point)?[..] {
+            [x, y, z] => Ok(Some(Focus::Point(Point3::new(x, y, z)))),
+            _ => Err(invalid()),
+        },
+        Some(("pixel", pixel)) => pixel
+            .split_once(',')
+            .and_then(|(x, y)| Some(Focus::Pixel(x.parse().ok()?, y.parse().ok()?)))
+            .map(Some)
+            .ok_or_else(invalid),
+        _ => Err(invalid()),
+    }
+}
+
+fn parse_tone_mapping(value: &str) -> std::io::Result<ToneMapping> {
+    let mut parts = value.split(':');
+    match (parts.next(), parts.next()) {
+        (Some("clamp"), None) => Ok(ToneMapping::Clamp),
+        (Some("reinhard"), white) => Ok(ToneMapping::Reinhard {
+            white: white
+                .map(|white| parse_number("--tone-map", white))
+                .transpose()?,
+        }),
+        (Some("aces"), None) => Ok(ToneMapping::Aces),
+        (Some("agx"), None) => Ok(ToneMapping::Agx),
+        _ => Err(invalid_input(format!("unknown tone mapping: {}", value))),
+    }
+}
+
+fn parse_transfer(value: &str) -> std::io::Result<TransferFunction> {
+    match value.split_once(':') {
+        None if value == "srgb" => Ok(TransferFunction::Srgb),
+        None if value == "rec709" => Ok(TransferFunction::Rec709),
+        Some(("gamma", gamma)) => Ok(TransferFunction::Gamma(parse_number("--transfer", gamma)?)),
+        _ => Err(invalid_input(format!(
+            "unknown transfer function: {}",
+            value
+        ))),
+    }
+}
+
+fn parse_color_space(value: &str) -> std::io::Result<ColorSpace> {
+    match value {
+        "rec709" | "srgb" => Ok(ColorSpace::Rec709),
+        "acescg" => Ok(ColorSpace::AcesCg),
+        "display-p3" => Ok(ColorSpace::DisplayP3),
+        "rec2020" => Ok(ColorSpace::Rec2020),
+        _ => Err(invalid_input(format!("unknown color space: {}", value))),
+    }
+}
+
+fn parse_filter(value: &str) -> std::io::Result<Filter> {
+    let mut parts = value.split(':');
+    let name = parts.next().unwrap_or_default();
+    let radius = match parts.next() {
+        Some(radius) => match parse_number("--filter", radius)? {
+            radius if radius > 0. => Some(radius),
+            _ => return Err(invalid_input(format!("invalid filter radius: {}", value))),
+        },
+        None => None,
+    };
+    let filter = match name {
+        "box" => Filter::Box {
+            radius: radius.unwrap_or(0.5),
+        },
+        "tent" => Filter::Tent {
+            radius: radius.unwrap_or(1.),
+        },
+        "gaussian" => Filter::Gaussian {
+            radius: radius.unwrap_or(1.5),
+        },
+        "mitchell" => Filter::Mitchell {
+            radius: radius.unwrap_or(2.),
+        },
+        "lanczos" => Filter::Lanczos {
+            radius: radius.unwrap_or(3.),
+        },
+        _ => return Err(invalid_input(format!("unknown filter: {}", value))),
+    };
+    match parts.next() {
+        None => Ok(filter),
+        Some(_) => Err(invalid_input(format!("unknown filter: {}", value))),
+    }
+}
+
+fn parse_sampler(value: &str) -> std::io::Result<SamplerType> {
+    match value {
+        "independent" => Ok(SamplerType::Independent),
+        "stratified" => Ok(SamplerType::Stratified),
+        "halton" => Ok(SamplerType::Halton),
+        "sobol" => Ok(SamplerType::Sobol),
+        "blue-noise" => Ok(SamplerType::BlueNoise),
+        _ => Err(invalid_input(format!("unknown sampler: {}", value))),
+    }
+}
+
+/// Parses colon separated numbers, of which at least one and at most `max` are given.
+fn parse_numbers(option: &str, value: &str, max: usize) -> std::io::Result<Vec<f32>> {
+    let numbers = value
+        .split(':')
+        .map(|number| parse_number(option, number))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    if numbers.len() > max {
+        return Err(invalid_input(format!(
+            "too many values for {}: {}",
+            option, value
+        )));
+    }
+    Ok(numbers)
+}
+
+fn parse_bloom(value: &str) -> std::io::Result<Bloom> {
+    let numbers = parse_numbers("--bloom", value, 3)?;
+    Ok(Bloom {
+        intensity: numbers[0],
+        threshold: numbers.get(1).copied().unwrap_or(1.),
+        radius: numbers.get(2).copied().unwrap_or(0.02),
+    })
+}
+
+fn parse_glare(value: &str) -> std::io::Result<Glare> {
+    let numbers = parse_numbers("--glare", value, 4)?;
+    let streaks = numbers.get(1).copied().unwrap_or(6.);
+    if streaks < 1. || streaks.fract() != 0. {
+        return Err(invalid_input(format!(
+            "invalid number of streaks: {}",
+            value
+        )));
+    }
+    Ok(Glare {
+        intensity: numbers[0],
+        threshold: 1.,
+        streaks: streaks as usize,
+        length: numbers.get(2).copied().unwrap_or(0.05),
+        rotation: Degrees(numbers.get(3).copied().unwrap_or(0.)),
+    })
+}
+
+fn parse_switch(option: &str, value: &str) -> std::io::Result<bool> {
+    match value {
+        "on" => Ok(true),
+        "off" => Ok(false),
+        _ => Err(invalid_input(format!(
+            "expected on or off for {}: {}",
+            option, value
+        ))),
+    }
+}
+
+impl Options {
+    /// Parses the options from the command line arguments, excluding the program name.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> std::io::Result<Self> {
+        let mut options = Options::default();
+        let mut args = args.into_iter();
+        let mut scene = None;
+        let mut auto_focus = false;
+
+        while let Some(arg) = args.next() {
+            if !arg.starts_with("--") {
+                if scene.replace(arg.clone()).is_some() {
+                    return Err(invalid_input(format!("unexpected argument: {}", arg)));
+                }
+                continue;
+            }
+
+            let value = args
+                .next()
+                .ok_or_else(|| invalid_input(format!("missing value for {}", arg)))?;
+            match arg.as_str() {
+                "--aperture" => options.aperture = parse_aperture(&value)?,
+                "--cats-eye" => options.cats_eye = parse_number(&arg, &value)?,
+                "--focal-length" => options.focal_length = Some(parse_number(&arg, &value)?),
+                "--f-number" => options.f_number = parse_number(&arg, &value)?,
+                "--projection" => options.projection = parse_projection(&value)?,
+                "--stereo" => options.stereo = Some(parse_stereo(&value)?),
+                "--size" => options.size = parse_size(&value)?,
+                "--exposure" => options.exposure = parse_number(&arg, &value)?,
+                "--tone-map" => options.tone_mapping = parse_tone_mapping(&value)?,
+                "--transfer" => options.transfer = parse_transfer(&value)?,
+                "--dither" => options.dither = parse_switch(&arg, &value)?,
+                "--filter" => options.filter = parse_filter(&value)?,
+                "--sampler" => options.sampler = parse_sampler(&value)?,
+                "--bloom" => options.post_effects.bloom = Some(parse_bloom(&value)?),
+                "--glare" => options.post_effects.glare = Some(parse_glare(&value)?),
+                "--chromatic-aberration" => {
+                    options.post_effects.chromatic_aberration = parse_number(&arg, &value)?
+                }
+                "--vignette" => options.post_effects.vignette = parse_number(&arg, &value)?,
+                "--grain" => options.post_effects.grain = parse_number(&arg, &value)?,
+                "--spectral" => options.spectral = parse_switch(&arg, &value)?,
+                "--preview" => options.preview = parse_switch(&arg, &value)?,
+                "--stats" => options.stats = parse_switch(&arg, &value)?,
+                "--stats-json" => options.stats_json = Some(value),
+                "--heatmap" => options.heatmap = Some(parse_heatmap(&value)?),
+                "--terminal-preview" => {
+                    options.terminal_preview = Some(parse_terminal_preview(&value)?)
+                }
+                "--working-space" => options.working_space = parse_color_space(&value)?,
+                "--output-space" => options.output_space = parse_color_space(&value)?,
+                "--focus" => {
+                    options.focus = parse_focus(&value)?;
+                    auto_focus = options.focus.is_none();
+                }
+                _ => return Err(invalid_input(format!("unknown option: {}", arg))),
+            }
+        }
+
+        if let Some(scene) = scene {
+            options.scene = scene;
+        }
+        let (width, height) = options.size;
+        if auto_focus {
+            options.focus = Some(Focus::Pixel(width / 2, height / 2));
+        }
+        if let Some(Focus::Pixel(x, y)) = options.focus {
+            if x >= width || y >= height {
+                return Err(invalid_input(format!(
+                    "focus pixel {},{} is outside of the {}x{} image",
+                    x, y, width, height
+                )));
+            }
+        }
+        Ok(options)
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    fn parse(args: &[&str]) -> std::io::Result<Options> {
+        Options::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    pub fn parse_works() {
+        let options = parse(&[]).unwrap();
+        assert_eq!(options.scene, "random");
+        assert!(options.focal_length.is_none());
+
+        let options = parse(&[
+            "glass",
+            "--aperture",
+            "polygon:6:15",
+            "--focal-length",
+            "50",
+            "--cats-eye",
+            "0.5",
+        ])
+        .unwrap();
+        assert_eq!(options.scene, "glass");
+        assert_eq!(options.focal_length, Some(50.));
+        assert_eq!(options.cats_eye, 0.5);
+        assert!(matches!(
+            options.aperture,
+            Aperture::Polygon { blades: 6, rotation: Degrees(r) } if r == 15.
+        ));
+    }
+
+    #[test]
+    pub fn camera_options_work() {
+        let options = parse(&[
+            "--projection",
+            "fisheye:equisolid:150",
+            "--stereo",
+            "over-under",
+            "--size",
+            "640x480",
+        ])
+        .unwrap();
+        assert_eq!(
+            options.projection,
+            Projection::Fisheye {
+                projection: FisheyeProjection::Equisolid,
+                fov: Degrees(150.)
+            }
+        );
+        assert_eq!(options.stereo, Some((StereoLayout::OverUnder, 0.065)));
+        assert_eq!(options.size, (640, 480));
+
+        let options = parse(&["--projection", "orthographic:8"]).unwrap();
+        assert_eq!(
+            options.projection,
+            Projection::Orthographic { view_height: 8. }
+        );
+
+        let options = parse(&["--focus", "auto", "--size", "640x480"]).unwrap();
+        assert_eq!(options.focus, Some(Focus::Pixel(320, 240)));
+        let options = parse(&["--focus", "point:1,2,3"]).unwrap();
+        assert_eq!(options.focus, Some(Focus::Point(Point3::new(1., 2., 3.))));
+        let options = parse(&["--focus", "7.5"]).unwrap();
+        assert_eq!(options.focus, Some(Focus::Distance(7.5)));
+        assert!(parse(&["--focus", "pixel:640,10", "--size", "640x480"]).is_err());
+
+        let options = parse(&["--projection", "lens"]).unwrap();
+        assert!(matches!(options.projection, Projection::Lens(elements) if elements.len() == 11));
+    }
+
+    #[test]
+    pub fn color_options_work() {
+        let options = parse(&[
+            "--exposure",
+            "-1.5",
+            "--tone-map",
+            "reinhard:4",
+            "--transfer",
+            "gamma:1.8",
+            "--dither",
+            "on",
+            "--working-space",
+            "acescg",
+            "--output-space",
+            "display-p3",
+            "--spectral",
+            "on",
+            "--preview",
+            "on",
+            "--terminal-preview",
+            "kitty:120",
+            "--stats",
+            "on",
+            "--stats-json",
+            "stats.json",
+            "--heatmap",
+            "nodes",
+        ])
+        .unwrap();
+        assert_eq!(options.exposure, -1.5);
+        assert_eq!(
+            options.tone_mapping,
+            ToneMapping::Reinhard { white: Some(4.) }
+        );
+        assert_eq!(options.transfer, TransferFunction::Gamma(1.8));
+        assert!(options.dither);
+        assert_eq!(options.working_space, ColorSpace::AcesCg);
+        assert_eq!(options.output_space, ColorSpace::DisplayP3);
+        assert!(options.spectral);
+        assert!(options.preview);
+        assert_eq!(
+            options.terminal_preview,
+            Some((TerminalGraphics::Kitty, 120))
+        );
+        assert!(options.stats);
+        assert_eq!(options.stats_json.as_deref(), Some("stats.json"));
+        assert_eq!(
+            options.heatmap,
+            Some((HeatmapMetric::NodeVisits, "heatmap.ppm".to_string()))
+        );
+        let options = parse(&["--heatmap", "time:cost.ppm"]).unwrap();
+        assert_eq!(
+            options.heatmap,
+            Some((HeatmapMetric::Time, "cost.ppm".to_string()))
+        );
+
+        let options = parse(&["--tone-map", "agx", "--filter", "mitchell:1.5"]).unwrap();
+        assert_eq!(options.filter, Filter::Mitchell { radius: 1.5 });
+        assert_eq!(options.tone_mapping, ToneMapping::Agx);
+        let options = parse(&["--sampler", "blue-noise"]).unwrap();
+        assert_eq!(options.sampler, SamplerType::BlueNoise);
+        assert!(parse(&["--tone-map", "aces:2"]).is_err());
+        assert!(parse(&["--transfer", "gamma"]).is_err());
+        assert!(parse(&["--dither", "yes"]).is_err());
+        assert!(parse(&["--terminal-preview", "sixel"]).is_err());
+        assert!(parse(&["--heatmap", "memory"]).is_err());
+        assert!(parse(&["--terminal-preview", "blocks:0"]).is_err());
+        assert!(parse(&["--output-space", "adobe-rgb"]).is_err());
+        assert!(parse(&["--filter", "sinc"]).is_err());
+        assert!(parse(&["--sampler", "random"]).is_err());
+        assert!(parse(&["--filter", "tent:0"]).is_err());
+    }
+
+    #[test]
+    pub fn post_effect_options_work() {
+        let options = parse(&[]).unwrap();
+        assert!(options.post_effects.is_empty());
+
+        let options = parse(&[
+            "--bloom",
+            "0.2:2",
+            "--glare",
+            "0.1:8",
+            "--vignette",
+            "0.5",
+            "--grain",
+            "0.02",
+        ])
+        .unwrap();
+        let effects = options.post_effects;
+        assert_eq!(
+            effects.bloom,
+            Some(Bloom {
+                intensity: 0.2,
+                threshold: 2.,
+                radius: 0.02
+            })
+        );
+        assert_eq!(effects.glare.map(|glare| glare.streaks), Some(8));
+        assert_eq!(effects.vignette, 0.5);
+        assert_eq!(effects.grain, 0.02);
+        assert_eq!(effects.chromatic_aberration, 0.);
+
+        assert!(parse(&["--bloom", "0.2:1:0.1:5"]).is_err());
+        assert!(parse(&["--glare", "0.1:2.5"]).is_err());
+    }
+
+    #[test]
+    pub fn invalid_arguments_fail() {
+        assert!(parse(&["--f-number"]).is_err());
+        assert!(parse(&["--f-number", "fast"]).is_err());
+        assert!(parse(&["--aperture", "polygon:2"]).is_err());
+        assert!(parse(&["--unknown", "1"]).is_err());
+        assert!(parse(&["random", "glass"]).is_err());
+        assert!(parse(&["--projection", "fisheye:wide"]).is_err());
+        assert!(parse(&["--size", "640"]).is_err());
+        assert!(parse(&["--focus", "point:1,2"]).is_err());
+        assert!(parse(&["--focus", "pixel:-1,2"]).is_err());
+        assert!(parse(&["--projection", "lens:/nonexistent/lens.txt"]).is_err());
+    }
+}
@@ -0,0 +1,215 @@
+//! RGB color spaces and conversions between them.
+//!
+//! Colors in scenes and textures are authored in linear Rec. 709, i.e. using the sRGB
+//! primaries. They are converted into the working space the renderer multiplies colors in,
+//! and the rendered image is converted from the working space into the output space.
+
+use crate::Color;
+
+/// A row-major 3x3 matrix transforming colors.
+pub type Matrix3 = [[f32; 3]; 3];
+
+/// Multiplies a color with a matrix.
+pub fn transform(m: &Matrix3, c: &Color) -> Color {
+    Color::new(
+        m[0][0] * c.x() + m[0][1] * c.y() + m[0][2] * c.z(),
+        m[1][0] * c.x() + m[1][1] * c.y() + m[1][2] * c.z(),
+        m[2][0] * c.x() + m[2][1] * c.y() + m[2][2] * c.z(),
+    )
+}
+
+pub fn multiply(a: &Matrix3, b: &Matrix3) -> Matrix3 {
+    let mut m = [[0.; 3]; 3];
+    for (i, row) in m.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    m
+}
+
+pub fn invert(m: &Matrix3) -> Matrix3 {
+    let cofactor =
+        |r0: usize, r1: usize, c0: usize, c1: usize| m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0];
+    let adjugate = [
+        [
+            cofactor(1, 2, 1, 2),
+            -cofactor(0, 2, 1, 2),
+            cofactor(0, 1, 1, 2),
+        ],
+        [
+            -cofactor(1, 2, 0, 2),
+            cofactor(0, 2, 0, 2),
+            -cofactor(0, 1, 0, 2),
+        ],
+        [
+            cofactor(1, 2, 0, 1),
+            -cofactor(0, 2, 0, 1),
+            cofactor(0, 1, 0, 1),
+        ],
+    ];
+    let determinant =
+        m[0][0] * adjugate[0][0] + m[0][1] * adjugate[1][0] + m[0][2] * adjugate[2][0];
+    let mut inverse = adjugate;
+    for value in inverse.iter_mut().flatten() {
+        *value /= determinant;
+    }
+    inverse
+}
+
+/// Converts CIE xy chromaticity coordinates to XYZ with a luminance of one.
+fn xy_to_xyz((x, y): (f32, f32)) -> Color {
+    Color::new(x / y, 1., (1. - x - y) / y)
+}
+
+/// The CIE standard illuminant D65 all conversions are adapted to.
+const D65: (f32, f32) = (0.3127, 0.3290);
+
+/// A linear RGB color space.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ColorSpace {
+    /// The primaries of ITU-R BT.709, shared with sRGB.
+    Rec709,
+    /// The ACES AP1 primaries used for rendering and compositing.
+    AcesCg,
+    /// The DCI-P3 primaries with a D65 white point.
+    DisplayP3,
+    /// The primaries of ITU-R BT.2020 used for UHD video.
+    Rec2020,
+}
+
+impl ColorSpace {
+    /// The chromaticities of the red, green and blue primaries and of the white point.
+    fn chromaticities(&self) -> [(f32, f32); 4] {
+        match self {
+            ColorSpace::Rec709 => [(0.64, 0.33), (0.30, 0.60), (0.15, 0.06), D65],
+            ColorSpace::AcesCg => [
+                (0.713, 0.293),
+                (0.165, 0.830),
+                (0.128, 0.044),
+                (0.32168, 0.33767),
+            ],
+            ColorSpace::DisplayP3 => [(0.680, 0.320), (0.265, 0.690), (0.150, 0.060), D65],
+            ColorSpace::Rec2020 => [(0.708, 0.292), (0.170, 0.797), (0.131, 0.046), D65],
+        }
+    }
+
+    /// The matrix converting colors of this space to CIE XYZ, adapted to D65.
+    pub fn to_xyz(self) -> Matrix3 {
+        let [r, g, b, white] = self.chromaticities();
+        let (r, g, b) = (xy_to_xyz(r), xy_to_xyz(g), xy_to_xyz(b));
+        let primaries = [
+            [r.x(), g.x(), b.x()],
+            [r.y(), g.y(), b.y()],
+            [r.z(), g.z(), b.z()],
+        ];
+
+        // Scale the primaries so that RGB white maps to the white point.
+        let scale = transform(&invert(&primaries), &xy_to_xyz(white));
+        let mut m = primaries;
+        for row in m.iter_mut() {
+            for (value, scale) in row.iter_mut().zip(scale.e) {
+                *value *= scale;
+            }
+        }
+
+        if white == D65 {
+            return m;
+        }
+        multiply(&adaptation_to_d65(&xy_to_xyz(white)), &m)
+    }
+}
+
+/// The Bradford chromatic adaptation from the specified XYZ white to D65.
+pub fn adaptation_to_d65(white: &Color) -> Matrix3 {
+    const BRADFORD: Matrix3 = [
+        [0.8951, 0.2664, -0.1614],
+        [-0.7502, 1.7135, 0.0367],
+        [0.0389, -0.0685, 1.0296],
+    ];
+    let source = transform(&BRADFORD, white);
+    let target = transform(&BRADFORD, &xy_to_xyz(D65));
+    let cone_scale = [
+        [target.x() / source.x(), 0., 0.],
+        [0., target.y() / source.y(), 0.],
+        [0., 0., target.z() / source.z()],
+    ];
+    multiply(&invert(&BRADFORD), &multiply(&cone_scale, &BRADFORD))
+}
+
+/// A precomputed conversion between two color spaces.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ColorConversion {
+    matrix: Matrix3,
+}
+
+impl ColorConversion {
+    pub fn new(from: ColorSpace, to: ColorSpace) -> Self {
+        let matrix = if from == to {
+            [[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]]
+        } else {
+            multiply(&invert(&to.to_xyz()), &from.to_xyz())
+        };
+        Self { matrix }
+    }
+
+    pub fn apply(&self, color: &Color) -> Color {
+        transform(&self.matrix, color)
+    }
+}
+
+impl Default for ColorConversion {
+    fn default() -> Self {
+        Self::new(ColorSpace::Rec709, ColorSpace::Rec709)
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    fn assert_matrix_eq(a: &Matrix3, b: &Matrix3) {
+        for (a, b) in a.iter().flatten().zip(b.iter().flatten()) {
+            assert!((a - b).abs() < 2e-3, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    pub fn to_xyz_works() {
+        assert_matrix_eq(
+            &ColorSpace::Rec709.to_xyz(),
+            &[
+                [0.4124, 0.3576, 0.1805],
+                [0.2126, 0.7152, 0.0722],
+                [0.0193, 0.1192, 0.9505],
+            ],
+        );
+    }
+
+    #[test]
+    pub fn conversions_work() {
+        // The matrix from ACEScg to linear sRGB, as published with ACES.
+        let conversion = ColorConversion::new(ColorSpace::AcesCg, ColorSpace::Rec709);
+        assert_matrix_eq(
+            &conversion.matrix,
+            &[
+                [1.7051, -0.6218, -0.0833],
+                [-0.1303, 1.1408, -0.0105],
+                [-0.0240, -0.1290, 1.1530],
+            ],
+        );
+
+        // White stays white and conversions round-trip.
+        let white = Color::new(1., 1., 1.);
+        for space in [
+            ColorSpace::AcesCg,
+            ColorSpace::DisplayP3,
+            ColorSpace::Rec2020,
+        ] {
+            let there = ColorConversion::new(ColorSpace::Rec709, space);
+            let back = ColorConversion::new(space, ColorSpace::Rec709);
+            assert!((back.apply(&there.apply(&white)) - white).len() < 1e-4);
+            assert!((there.apply(&white) - white).len() < 1e-2, "{:?}", space);
+        }
+    }
+}
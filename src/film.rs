@@ -0,0 +1,299 @@
+//! The framebuffer collecting samples, weighted by a reconstruction filter.
+
+use crate::Color;
+use std::f32::consts::PI;
+
+/// A separable pixel reconstruction filter.
+///
+/// The radius is given in pixels; samples contribute to all pixels
+/// whose centers lie within the radius.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Filter {
+    /// Weighs all samples within the radius equally; a radius
+    /// of one half averages the samples of each pixel.
+    Box { radius: f32 },
+    /// Weighs samples falling off linearly towards the radius.
+    Tent { radius: f32 },
+    /// A Gaussian, shifted to reach zero at the radius.
+    Gaussian { radius: f32 },
+    /// The Mitchell-Netravali cubic with `B = C = 1/3`, whose negative lobes sharpen the image.
+    Mitchell { radius: f32 },
+    /// The sinc function windowed by a wider sinc, with as many lobes as the radius.
+    Lanczos { radius: f32 },
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Filter::Box { radius: 0.5 }
+    }
+}
+
+fn sinc(x: f32) -> f32 {
+    if x.abs() < 1e-5 {
+        return 1.;
+    }
+    (PI * x).sin() / (PI * x)
+}
+
+impl Filter {
+    pub fn radius(&self) -> f32 {
+        match *self {
+            Filter::Box { radius }
+            | Filter::Tent { radius }
+            | Filter::Gaussian { radius }
+            | Filter::Mitchell { radius }
+            | Filter::Lanczos { radius } => radius,
+        }
+    }
+
+    /// Evaluates the filter at the specified offset from the pixel center.
+    pub fn evaluate(&self, x: f32, y: f32) -> f32 {
+        self.evaluate_1d(x) * self.evaluate_1d(y)
+    }
+
+    fn evaluate_1d(&self, x: f32) -> f32 {
+        let radius = self.radius();
+        let x = x.abs();
+        if x > radius {
+            return 0.;
+        }
+
+        match self {
+            Filter::Box { .. } => 1.,
+            Filter::Tent { .. } => radius - x,
+            Filter::Gaussian { .. } => {
+                const ALPHA: f32 = 2.;
+                (-ALPHA * x * x).exp() - (-ALPHA * radius * radius).exp()
+            }
+            Filter::Mitchell { .. } => {
+                const B: f32 = 1. / 3.;
+                const C: f32 = 1. / 3.;
+                // The cubic is defined on -2..2.
+                let x = 2. * x / radius;
+                let value = if x > 1. {
+                    (-B - 6. * C) * x * x * x
+                        + (6. * B + 30. * C) * x * x
+                        + (-12. * B - 48. * C) * x
+                        + (8. * B + 24. * C)
+                } else {
+                    (12. - 9. * B - 6. * C) * x * x * x
+                        + (-18. + 12. * B + 6. * C) * x * x
+                        + (6. - 2. * B)
+                };
+                value / 6.
+            }
+            Filter::Lanczos { .. } => sinc(x) * sinc(x / radius),
+        }
+    }
+
+    /// The number of neighboring pixels in each direction a sample may contribute to.
+    fn extent(&self) -> usize {
+        (self.radius() - 0.5).ceil().max(0.) as usize
+    }
+}
+
+/// The accumulated samples of a pixel.
+#[derive(Debug, Copy, Clone, Default)]
+struct Pixel {
+    sum: Color,
+    weight: f32,
+}
+
+impl Pixel {
+    fn add(&mut self, other: &Pixel) {
+        self.sum += other.sum;
+        self.weight += other.weight;
+    }
+}
+
+/// The image being rendered, with pixel `(0, 0)` in the lower left corner.
+pub struct Film {
+    width: usize,
+    height: usize,
+    filter: Filter,
+    pixels: Vec<Pixel>,
+}
+
+impl Film {
+    pub fn new(width: usize, height: usize, filter: Filter) -> Self {
+        Self {
+            width,
+            height,
+            filter,
+            pixels: vec![Pixel::default(); width * height],
+        }
+    }
+
+    /// Creates a tile collecting the samples taken within pixel `(x, y)`,
+    /// to be added to the film once complete.
+    pub fn tile(&self, x: usize, y: usize) -> FilmTile {
+        let extent = self.filter.extent();
+        let size = 2 * extent + 1;
+        FilmTile {
+            x: x as isize - extent as isize,
+            y: y as isize - extent as isize,
+            size,
+            filter: self.filter,
+            pixels: vec![Pixel::default(); size * size],
+        }
+    }
+
+    pub fn add_tile(&mut self, tile: &FilmTile) {
+        for ty in 0..tile.size {
+            for tx in 0..tile.size {
+                let (x, y) = (tile.x + tx as isize, tile.y + ty as isize);
+                if x < 0 || y < 0 || x >= self.width as isize || y >= self.height as isize {
+                    continue;
+                }
+                self.pixels[y as usize * self.width + x as usize]
+                    .add(&tile.pixels[ty * tile.size + tx]);
+            }
+        }
+    }
+
+    /// The filtered color of the pixel.
+    pub fn pixel(&self, x: usize, y: usize) -> Color {
+        let pixel = &self.pixels[y * self.width + x];
+        if pixel.weight == 0. {
+            return Color::default();
+        }
+        pixel.sum / pixel.weight
+    }
+
+    /// Resolves the filtered image.
+    pub fn to_image(&self) -> Image {
+        let mut image = Image::new(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                image.set(x, y, self.pixel(x, y));
+            }
+        }
+        image
+    }
+}
+
+/// A high dynamic range image, with pixel `(0, 0)` in the lower left corner.
+#[derive(Clone)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![Color::default(); width * height],
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, color: Color) {
+        self.pixels[y * self.width + x] = color;
+    }
+
+    /// Interpolates bilinearly between the pixel centers, clamping at the borders.
+    pub fn sample(&self, x: f32, y: f32) -> Color {
+        let x = (x - 0.5).clamp(0., (self.width - 1) as f32);
+        let y = (y - 0.5).clamp(0., (self.height - 1) as f32);
+        let (x0, y0) = (x as usize, y as usize);
+        let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+        let (tx, ty) = (x - x0 as f32, y - y0 as f32);
+        let bottom = self.get(x0, y0) * (1. - tx) + self.get(x1, y0) * tx;
+        let top = self.get(x0, y1) * (1. - tx) + self.get(x1, y1) * tx;
+        bottom * (1. - ty) + top * ty
+    }
+
+    pub fn pixels_mut(&mut self) -> impl Iterator<Item = &mut Color> {
+        self.pixels.iter_mut()
+    }
+}
+
+/// The pixels of the film a single pixel's samples contribute to.
+pub struct FilmTile {
+    /// The film coordinates of the tile's lower left pixel.
+    x: isize,
+    y: isize,
+    size: usize,
+    filter: Filter,
+    pixels: Vec<Pixel>,
+}
+
+impl FilmTile {
+    /// Adds a sample at the specified film position in pixels.
+    ///
+    /// Samples that hit nothing must be added as black to keep the weights correct.
+    pub fn add_sample(&mut self, x: f32, y: f32, color: Color) {
+        for ty in 0..self.size {
+            for tx in 0..self.size {
+                let center_x = (self.x + tx as isize) as f32 + 0.5;
+                let center_y = (self.y + ty as isize) as f32 + 0.5;
+                let weight = self.filter.evaluate(x - center_x, y - center_y);
+                if weight != 0. {
+                    let pixel = &mut self.pixels[ty * self.size + tx];
+                    pixel.sum += weight * color;
+                    pixel.weight += weight;
+                }
+            }
+        }
+    }
+
+    /// Combines the samples of two tiles covering the same pixels.
+    pub fn merge(mut self, other: FilmTile) -> Self {
+        for (pixel, other) in self.pixels.iter_mut().zip(other.pixels.iter()) {
+            pixel.add(other);
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    pub fn filters_work() {
+        let filters = [
+            Filter::Box { radius: 0.5 },
+            Filter::Tent { radius: 1. },
+            Filter::Gaussian { radius: 1.5 },
+            Filter::Mitchell { radius: 2. },
+            Filter::Lanczos { radius: 3. },
+        ];
+        for filter in filters {
+            assert!(filter.evaluate(0., 0.) > 0.);
+            assert_eq!(filter.evaluate(filter.radius() + 0.1, 0.), 0.);
+            assert!(filter.evaluate(0.2, 0.) <= filter.evaluate(0., 0.));
+        }
+        // The Mitchell filter has negative lobes.
+        assert!(Filter::Mitchell { radius: 2. }.evaluate(1.5, 0.) < 0.);
+        assert!(Filter::Mitchell { radius: 2. }.evaluate(2., 0.).abs() < 1e-6);
+    }
+
+    #[test]
+    pub fn box_filter_averages_pixels() {
+        let mut film = Film::new(2, 1, Filter::default());
+        let mut tile = film.tile(0, 0);
+        tile.add_sample(0.25, 0.5, Color::new(1., 1., 1.));
+        tile.add_sample(0.75, 0.5, Color::new(0., 0., 0.));
+        film.add_tile(&tile);
+        assert_eq!(film.pixel(0, 0), Color::new(0.5, 0.5, 0.5));
+        assert_eq!(film.pixel(1, 0), Color::default());
+    }
+
+    #[test]
+    pub fn wide_filters_splat_into_neighbors() {
+        let mut film = Film::new(3, 3, Filter::Tent { radius: 1.5 });
+        let mut tile = film.tile(1, 1);
+        tile.add_sample(1.5, 1.5, Color::new(1., 0., 0.));
+        let other = film.tile(1, 1);
+        film.add_tile(&tile.merge(other));
+        assert_eq!(film.pixel(0, 1), Color::new(1., 0., 0.));
+        assert_eq!(film.pixel(2, 2), Color::new(1., 0., 0.));
+    }
+}
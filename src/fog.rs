@@ -0,0 +1,74 @@
+use crate::material::MaterialPtr;
+use crate::{HitRecord, Random, Ray};
+
+/// A homogeneous participating medium filling the scene.
+///
+/// By default the fog extends infinitely, in which case every ray eventually
+/// scatters and the background is never seen. A ceiling limits the fog to
+/// everything below a given height, e.g. for ground fog or haze.
+pub struct Fog {
+    density: f32,
+    /// The phase function, e.g. [`HenyeyGreenstein`](crate::material::HenyeyGreenstein).
+    phase_function: MaterialPtr,
+    /// The height (Y coordinate) above which there is no fog.
+    ceiling: f32,
+}
+
+impl Fog {
+    pub fn new(density: f32, phase_function: MaterialPtr) -> Self {
+        Self {
+            density,
+            phase_function,
+            ceiling: f32::INFINITY,
+        }
+    }
+
+    /// Limits the fog to the space below the specified height.
+    pub fn with_ceiling(mut self, ceiling: f32) -> Self {
+        self.ceiling = ceiling;
+        self
+    }
+
+    /// Determines the range of the ray parameter that lies within the fog.
+    fn extent(&self, ray: &Ray) -> Option<(f32, f32)> {
+        let height = ray.origin.y();
+        let dy = ray.direction.y();
+        let t_ceiling = (self.ceiling - height) / dy;
+
+        if height <= self.ceiling {
+            if dy > 0. {
+                Some((0., t_ceiling))
+            } else {
+                Some((0., f32::INFINITY))
+            }
+        } else if dy < 0. {
+            Some((t_ceiling, f32::INFINITY))
+        } else {
+            None
+        }
+    }
+
+    /// Samples a free-flight distance along the ray.
+    ///
+    /// Returns a scattering event if the ray interacts with the fog before
+    /// reaching `t_max`, i.e. before hitting the closest surface.
+    pub fn sample(&self, ray: &Ray, t_max: f32, rng: &Random) -> Option<HitRecord> {
+        if self.density <= 0. {
+            return None;
+        }
+
+        let (t_enter, t_exit) = self.extent(ray)?;
+        let t = t_enter - (1. - rng.sample()).ln() / self.density;
+        if t >= t_exit.min(t_max) {
+            return None;
+        }
+
+        Some(HitRecord::new_from_ray(
+            ray,
+            t,
+            ray.at(t),
+            -ray.direction,
+            self.phase_function.clone(),
+        ))
+    }
+}
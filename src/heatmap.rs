@@ -0,0 +1,183 @@
+//! False color images of how expensive each pixel is to render.
+
+use crate::film::Image;
+use crate::stats;
+use crate::Color;
+use std::time::Instant;
+
+/// What the cost of a pixel is measured in.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum HeatmapMetric {
+    /// The time spent, in microseconds.
+    Time,
+    /// The number of rays tested against objects.
+    IntersectionTests,
+    /// The number of bounding boxes of the acceleration structure tested.
+    NodeVisits,
+}
+
+impl HeatmapMetric {
+    /// Runs `f` on the current thread and measures its cost.
+    pub fn measure<T>(self, f: impl FnOnce() -> T) -> (T, f64) {
+        let counter = match self {
+            HeatmapMetric::Time => {
+                let start = Instant::now();
+                let result = f();
+                return (result, start.elapsed().as_secs_f64() * 1e6);
+            }
+            HeatmapMetric::IntersectionTests => stats::thread_intersection_tests,
+            HeatmapMetric::NodeVisits => stats::thread_node_visits,
+        };
+        let before = counter();
+        let result = f();
+        (result, (counter() - before) as f64)
+    }
+
+    pub fn unit(self) -> &'static str {
+        match self {
+            HeatmapMetric::Time => "µs",
+            HeatmapMetric::IntersectionTests => "intersection tests",
+            HeatmapMetric::NodeVisits => "node visits",
+        }
+    }
+}
+
+/// The cost of every pixel, with pixel `(0, 0)` in the lower left corner.
+pub struct Heatmap {
+    width: usize,
+    height: usize,
+    costs: Vec<f64>,
+}
+
+impl Heatmap {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            costs: vec![0.; width * height],
+        }
+    }
+
+    pub fn add(&mut self, x: usize, y: usize, cost: f64) {
+        self.costs[y * self.width + x] += cost;
+    }
+
+    /// The cost shown with the hottest color, which is the 99th percentile
+    /// so that a few outliers do not leave all other pixels cold.
+    ///
+    /// This is zero if the heatmap is empty or nothing had any cost.
+    pub fn full_scale(&self) -> f64 {
+        if self.costs.is_empty() {
+            return 0.;
+        }
+        let mut costs = self.costs.clone();
+        let index = (costs.len() - 1) * 99 / 100;
+        let (_, percentile, _) = costs.select_nth_unstable_by(index, |a, b| a.total_cmp(b));
+        *percentile
+    }
+
+    /// Maps the costs to colors that are already display encoded.
+    pub fn to_image(&self) -> Image {
+        let full_scale = self.full_scale();
+        let mut image = Image::new(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let cost = self.costs[y * self.width + x];
+                let t = if full_scale > 0. {
+                    cost / full_scale
+                } else {
+                    0.
+                };
+                image.set(x, y, turbo(t.min(1.) as f32));
+            }
+        }
+        image
+    }
+}
+
+/// Evaluates the Turbo colormap, going from near black over blue, green and orange to dark red,
+/// using the polynomial approximation by Ruofei Du.
+fn turbo(t: f32) -> Color {
+    let polynomial = |c: [f32; 6]| {
+        c.iter()
+            .rev()
+            .fold(0., |sum, coefficient| sum * t + coefficient)
+    };
+    let color = Color::new(
+        polynomial([
+            0.135_721_38,
+            4.615_392_6,
+            -42.660_324,
+            132.131_08,
+            -152.942_4,
+            59.286_38,
+        ]),
+        polynomial([
+            0.091_402_61,
+            2.194_188_4,
+            4.842_966_6,
+            -14.185_033,
+            4.277_298_6,
+            2.829_566,
+        ]),
+        polynomial([
+            0.106_673_3,
+            12.641_946,
+            -60.582_047,
+            110.362_77,
+            -89.903_11,
+            27.348_25,
+        ]),
+    );
+    Color::new(
+        color.x().clamp(0., 1.),
+        color.y().clamp(0., 1.),
+        color.z().clamp(0., 1.),
+    )
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    pub fn costs_are_measured() {
+        stats::enable();
+        let (result, tests) = HeatmapMetric::IntersectionTests.measure(|| {
+            stats::count_intersection_test();
+            stats::count_intersection_test();
+            7
+        });
+        assert_eq!((result, tests), (7, 2.));
+        let (_, visits) = HeatmapMetric::NodeVisits.measure(stats::count_intersection_test);
+        assert_eq!(visits, 0.);
+    }
+
+    #[test]
+    pub fn outliers_saturate() {
+        let mut heatmap = Heatmap::new(10, 10);
+        for y in 0..10 {
+            for x in 0..10 {
+                heatmap.add(x, y, 1.);
+            }
+        }
+        heatmap.add(3, 4, 99.);
+        assert_eq!(heatmap.full_scale(), 1.);
+
+        let image = heatmap.to_image();
+        assert_eq!(image.get(0, 0), image.get(3, 4));
+        // The hottest color is dark red.
+        let hot = image.get(0, 0);
+        assert!(hot.x() > 0.4 && hot.y() < 0.1 && hot.z() < 0.1);
+        let cold = turbo(0.1);
+        assert!(cold.z() > cold.x() && cold.z() > cold.y());
+    }
+
+    #[test]
+    pub fn empty_heatmaps_are_cold() {
+        assert_eq!(Heatmap::new(0, 0).full_scale(), 0.);
+        let heatmap = Heatmap::new(2, 2);
+        assert_eq!(heatmap.full_scale(), 0.);
+        assert_eq!(heatmap.to_image().get(1, 1), turbo(0.));
+    }
+}
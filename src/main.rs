@@ -1,115 +1,404 @@
 mod camera;
+mod cli;
+mod color_space;
+mod film;
+mod fog;
+mod heatmap;
 mod material;
+mod noise;
 mod objects;
+mod pgm;
+mod post;
+#[cfg(feature = "preview")]
+mod preview;
 mod primitives;
 mod random;
+mod sampler;
+mod scenes;
+mod sdf;
+mod spectrum;
+mod stats;
+mod terminal;
+mod texture;
+mod tonemap;
 
-use camera::Camera;
+use camera::{
+    Camera, EquirectangularCamera, FisheyeCamera, Focus, OrthographicCamera, PerspectiveCamera,
+    RealisticCamera, Shutter, StereoCamera, StereoLayout, View,
+};
+use cli::{Options, Projection, SamplerType};
 
-use material::{Dielectric, Lambertian, Material, MaterialPtr, Metal};
-use objects::{HitRecord, Hittable, HittableList, Sphere};
+use material::Material;
+use objects::{HitRecord, Hittable};
 use primitives::{Color, Point3, Ray, Vec3};
 
+use film::{Film, FilmTile, Image};
+use heatmap::{Heatmap, HeatmapMetric};
+use post::PostEffects;
 use random::Random;
+use sampler::{BlueNoise, Halton, Independent, SamplerPtr, Sobol, Stratified};
+use scenes::Scene;
+use spectrum::SpectralSampler;
+use stats::{Counters, RayType, Report};
+use terminal::TerminalPreview;
+use tonemap::{ColorPipeline, TransferFunction};
 
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::{HumanDuration, ProgressBar, ProgressDrawTarget, ProgressStyle};
 use num_traits::Float;
 use rayon::prelude::*;
-use space_partitioning::quadtree::QuadRect;
 use std::fs::File;
 use std::io::prelude::*;
+use std::io::BufWriter;
+use std::ops::Range;
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Instant;
 
 const GRID_SCALE: f32 = 100.0;
 
+const SAMPLES_PER_PIXEL: usize = 500;
+const MAX_RAY_DEPTH: usize = 50;
+
+/// The file the rendered image is written to.
+const OUTPUT_FILE: &str = "test.ppm";
+
+const VIEW: View = View {
+    look_from: Point3::new(13., 2., 3.),
+    look_at: Point3::new(0., 0., 0.),
+    view_up: Vec3::new(0., 1., 0.),
+};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Degrees(pub f32);
 
-fn ray_color(ray: &Ray, world: &Box<dyn Hittable>, rng: &Random, depth: usize) -> Color {
-    if depth <= 0 {
+fn ray_color(ray: &Ray, scene: &Scene, rng: &Random, depth: usize) -> Color {
+    if depth == 0 {
         return Color::default();
     }
 
-    if let Some(hit) = world.hit(ray, 0.001, f32::infinity()) {
+    let hit = scene.world.sample_hit(ray, 0.001, f32::infinity(), rng);
+
+    // Fog filling the scene may scatter the ray before it reaches the closest surface.
+    let hit = match &scene.fog {
+        Some(fog) => {
+            let t_max = hit.as_ref().map_or(f32::infinity(), |hit| hit.t);
+            fog.sample(ray, t_max, rng).or(hit)
+        }
+        None => hit,
+    };
+
+    if let Some(hit) = hit {
+        let emitted = input_color(scene, ray, &hit.material.emitted(hit.u, hit.v, &hit.point));
         if let Some(scattered) = hit.material.scatter(ray, &hit, rng) {
-            return scattered.attenuation * ray_color(&scattered.ray, world, rng, depth - 1);
+            stats::count_ray(RayType::Bounce);
+            stats::count_scatter(hit.material.kind());
+            let mut attenuation = input_color(scene, ray, &scattered.attenuation);
+
+            // A wavelength dependent material restricted the path to the wavelength of one
+            // channel; drop the other channels and compensate for them being dropped.
+            if let (None, Some(lambda)) = (ray.wavelength, scattered.ray.wavelength) {
+                let mask = ray
+                    .channel_wavelengths()
+                    .e
+                    .map(|w| if w == lambda { 3. } else { 0. });
+                attenuation = attenuation * Color::new(mask[0], mask[1], mask[2]);
+            }
+
+            return emitted + attenuation * ray_color(&scattered.ray, scene, rng, depth - 1);
         }
+        return emitted;
     }
 
     // A simple gradient function for the background.
     // The color is blended between blue and white depending on the ray's Y coordinate.
     let unit_direction = ray.direction.as_unit_vector();
     let t = 0.5 * (unit_direction.y() + 1.0);
-    (1.0 - t) * Color::new(1., 1., 1.) + t * Color::new(0.5, 0.7, 1.0)
+    let background = (1.0 - t) * Color::new(1., 1., 1.) + t * Color::new(0.5, 0.7, 1.0);
+    input_color(scene, ray, &background)
 }
 
-fn random_scene(rng: &mut Random) -> HittableList {
-    let mut world = HittableList::new(QuadRect::new(
-        (-15. * GRID_SCALE) as i32,
-        (-15. * GRID_SCALE) as i32,
-        (30. * GRID_SCALE) as i32,
-        (30. * GRID_SCALE) as i32,
-    ));
-
-    let ground_material: MaterialPtr =
-        Arc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5), 1.0)));
-    world.add(Box::new(Sphere::new(
-        Point3::new(0., -1000., 0.),
-        1000.,
-        ground_material,
-    )));
-
-    for a in -11..11 {
-        for b in -11..11 {
-            let choose_mat = rng.sample();
-            let center = Point3::new(
-                a as f32 + 0.9 * rng.sample(),
-                0.2,
-                b as f32 + 0.9 * rng.sample(),
-            );
+/// Converts a color authored in Rec. 709 into the working space, or samples its
+/// uplifted spectrum at the wavelengths the ray carries in spectral mode.
+fn input_color(scene: &Scene, ray: &Ray, color: &Color) -> Color {
+    match &ray.wavelengths {
+        Some(wavelengths) => spectrum::uplift(color, wavelengths),
+        None => scene.input_conversion.apply(color),
+    }
+}
+
+/// Creates the camera, or the pair of cameras for stereo images, selected by the options.
+fn create_camera(
+    options: &Options,
+    view: &View,
+    aspect_ratio: f32,
+    focus_distance: f32,
+    shutter: Shutter,
+) -> std::io::Result<Box<dyn Camera>> {
+    match options.stereo {
+        None => create_eye(options, view, aspect_ratio, 0., focus_distance, shutter),
+        Some((layout, interocular)) => {
+            let aspect_ratio = match layout {
+                StereoLayout::SideBySide => 0.5 * aspect_ratio,
+                StereoLayout::OverUnder => 2. * aspect_ratio,
+            };
+            Ok(Box::new(StereoCamera::new(
+                create_eye(
+                    options,
+                    view,
+                    aspect_ratio,
+                    -0.5 * interocular,
+                    focus_distance,
+                    shutter,
+                )?,
+                create_eye(
+                    options,
+                    view,
+                    aspect_ratio,
+                    0.5 * interocular,
+                    focus_distance,
+                    shutter,
+                )?,
+                layout,
+            )))
+        }
+    }
+}
 
-            if (center - Point3::new(4., 0.2, 0.)).len() > 0.9 {
-                if choose_mat < 0.8 {
-                    // Diffuse
-                    let albedo = Color::random(rng) * Color::random(rng);
-                    let material: MaterialPtr = Arc::new(Box::new(Lambertian::new(albedo, 1.0)));
-                    world.add(Box::new(Sphere::new(center, 0.2, material)));
-                } else if choose_mat < 0.95 {
-                    // Metal
-                    let albedo = Color::random(rng) * 0.5 + 0.5;
-                    let fuzz = rng.sample() * 0.5;
-                    let material: MaterialPtr = Arc::new(Box::new(Metal::new(albedo, fuzz)));
-                    world.add(Box::new(Sphere::new(center, 0.2, material)));
-                } else {
-                    // Glass
-                    let material: MaterialPtr = Arc::new(Box::new(Dielectric::new(1.5)));
-                    world.add(Box::new(Sphere::new(center, 0.2, material)));
-                }
+/// Creates a camera moved sideways by `eye_offset`.
+fn create_eye(
+    options: &Options,
+    view: &View,
+    aspect_ratio: f32,
+    eye_offset: f32,
+    focus_distance: f32,
+    shutter: Shutter,
+) -> std::io::Result<Box<dyn Camera>> {
+    const APERTURE: f32 = 0.1;
+    const SENSOR_HEIGHT: f32 = 24.;
+
+    // Parallel eyes, both looking straight ahead.
+    let right = view
+        .view_up
+        .cross(&(view.look_from - view.look_at))
+        .as_unit_vector();
+    let look_from = view.look_from + right * eye_offset;
+    let look_at = view.look_at + right * eye_offset;
+    let view_up = view.view_up;
+
+    Ok(match &options.projection {
+        Projection::Perspective => Box::new(
+            match options.focal_length {
+                Some(focal_length) => PerspectiveCamera::physical(
+                    look_from,
+                    look_at,
+                    view_up,
+                    focal_length,
+                    options.f_number,
+                    (SENSOR_HEIGHT * aspect_ratio, SENSOR_HEIGHT),
+                    focus_distance,
+                ),
+                None => PerspectiveCamera::new(
+                    look_from,
+                    look_at,
+                    view_up,
+                    Degrees(20.),
+                    aspect_ratio,
+                    APERTURE,
+                    focus_distance,
+                ),
             }
+            .with_aperture(options.aperture.clone())
+            .with_cats_eye(options.cats_eye)
+            .with_shutter(shutter),
+        ),
+        Projection::Orthographic { view_height } => Box::new(
+            OrthographicCamera::new(look_from, look_at, view_up, *view_height, aspect_ratio)
+                .with_shutter(shutter),
+        ),
+        Projection::Equirectangular => Box::new(
+            EquirectangularCamera::new(view.look_from, view.look_at, view_up)
+                .with_eye_offset(eye_offset)
+                .with_shutter(shutter),
+        ),
+        Projection::Fisheye { projection, fov } => Box::new(
+            FisheyeCamera::new(look_from, look_at, view_up, *fov, aspect_ratio, *projection)
+                .with_shutter(shutter),
+        ),
+        Projection::Lens(elements) => Box::new(
+            RealisticCamera::new(
+                look_from,
+                look_at,
+                view_up,
+                elements.clone(),
+                (SENSOR_HEIGHT * aspect_ratio, SENSOR_HEIGHT),
+                focus_distance,
+            )
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("the lens cannot focus at {} units", focus_distance),
+                )
+            })?
+            .with_shutter(shutter),
+        ),
+    })
+}
+
+/// Determines the distance along the viewing direction to the plane in focus.
+fn focus_distance(
+    focus: Focus,
+    options: &Options,
+    view: &View,
+    scene: &Scene,
+    rng: &Random,
+) -> std::io::Result<f32> {
+    let forward = view.forward();
+    match focus {
+        Focus::Distance(distance) => Ok(distance),
+        Focus::Point(point) => Ok((point - view.look_from).dot(&forward)),
+        Focus::Pixel(x, y) => {
+            const RAYS: usize = 16;
+            let (width, height) = options.size;
+            let aspect_ratio = width as f32 / height as f32;
+
+            // Cast rays through the pixel using a camera focused at the default distance,
+            // which only affects where within the pixel the rays end up.
+            let camera = create_camera(options, view, aspect_ratio, 10., Shutter::default())?;
+            debug_assert!(
+                x < width && y < height,
+                "the focus pixel is outside of the image"
+            );
+            let u = (x as f32 + 0.5) / (width as f32 - 1.);
+            let v = ((height - 1 - y) as f32 + 0.5) / (height as f32 - 1.);
+            let distances = (0..RAYS)
+                .filter_map(|_| camera.get_ray(u, v, rng))
+                .filter_map(|sample| scene.world.hit(&sample.ray, 0.001, f32::infinity()))
+                .map(|hit| (hit.point - view.look_from).dot(&forward))
+                .collect::<Vec<_>>();
+            if distances.is_empty() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("nothing to focus on at pixel {}, {}", x, y),
+                ));
+            }
+            Ok(distances.iter().sum::<f32>() / distances.len() as f32)
         }
     }
+}
+
+/// Creates the sampler generating the sample values of all pixels.
+fn create_sampler(sampler: SamplerType, samples_per_pixel: usize) -> SamplerPtr {
+    match sampler {
+        SamplerType::Independent => Arc::new(Independent),
+        SamplerType::Stratified => Arc::new(Stratified::new(samples_per_pixel)),
+        SamplerType::Halton => Arc::new(Halton),
+        SamplerType::Sobol => Arc::new(Sobol),
+        SamplerType::BlueNoise => Arc::new(BlueNoise::new()),
+    }
+}
+
+/// Traces the samples of the pixels of an image.
+struct Renderer<'a> {
+    scene: &'a Scene,
+    camera: &'a dyn Camera,
+    sampler: SamplerPtr,
+    spectral: Option<&'a SpectralSampler>,
+    /// The image width and height in pixels.
+    size: (usize, usize),
+    /// What the cost of the pixels is measured in, if at all.
+    heatmap: Option<HeatmapMetric>,
+}
+
+impl Renderer<'_> {
+    /// Traces the samples with the indices in `samples` for every pixel in row `j`,
+    /// returning the tiles to add to the film along with the cost of each pixel.
+    fn render_row(&self, film: &Film, j: usize, samples: Range<usize>) -> Vec<(FilmTile, f64)> {
+        (0..self.size.0)
+            .into_par_iter()
+            .map(|i| {
+                samples
+                    .clone()
+                    .into_par_iter()
+                    .fold(
+                        || (film.tile(i, j), 0.),
+                        |(mut tile, cost), index| {
+                            let ((x, y, color), sample_cost) = match self.heatmap {
+                                Some(metric) => metric.measure(|| self.render_sample(i, j, index)),
+                                None => (self.render_sample(i, j, index), 0.),
+                            };
+                            tile.add_sample(x, y, color);
+                            (tile, cost + sample_cost)
+                        },
+                    )
+                    .reduce(
+                        || (film.tile(i, j), 0.),
+                        |(tile, cost), (other, other_cost)| (tile.merge(other), cost + other_cost),
+                    )
+            })
+            .collect()
+    }
+
+    /// Traces a sample of pixel `(i, j)`, returning its image position and color.
+    fn render_sample(&self, i: usize, j: usize, index: usize) -> (f32, f32, Color) {
+        let (image_width, image_height) = self.size;
+        let rng = Random::for_sample(self.sampler.clone(), (i, j), index);
+        let (x_offset, y_offset) = rng.sample_2d();
+        let x = i as f32 + x_offset;
+        let y = j as f32 + y_offset;
+
+        let u = x / (image_width as f32 - 1.);
+        let v = y / (image_height as f32 - 1.);
+
+        // Samples not reaching the image still count as black.
+        let color = match self.spectral {
+            // The wavelengths are sampled first, as cameras may trace them through a lens.
+            Some(sampler) => {
+                let wavelengths = sampler.sample_wavelengths(&rng);
+                self.camera
+                    .get_spectral_ray(u, v, wavelengths, &rng)
+                    .map(|sample| {
+                        let radiance = sample.weight * self.trace(&sample.ray, &rng);
+                        sampler.to_rgb(&radiance, &wavelengths)
+                    })
+            }
+            None => self
+                .camera
+                .get_ray(u, v, &rng)
+                .map(|sample| sample.weight * self.trace(&sample.ray, &rng)),
+        };
+        (x, y, color.unwrap_or_default())
+    }
 
-    let material1: MaterialPtr = Arc::new(Box::new(Dielectric::new(1.5)));
-    world.add(Box::new(Sphere::new(
-        Point3::new(0., 1., 0.),
-        1.0,
-        material1,
-    )));
-
-    let material2: MaterialPtr = Arc::new(Box::new(Lambertian::new(Color::new(0.4, 0.2, 0.1), 1.)));
-    world.add(Box::new(Sphere::new(
-        Point3::new(-4., 1., 0.),
-        1.0,
-        material2,
-    )));
-
-    let material3: MaterialPtr = Arc::new(Box::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.0)));
-    world.add(Box::new(Sphere::new(
-        Point3::new(4., 1., 0.),
-        1.0,
-        material3,
-    )));
-
-    world
+    /// Determines the radiance arriving along a camera ray.
+    fn trace(&self, ray: &Ray, rng: &Random) -> Color {
+        stats::count_ray(RayType::Camera);
+        ray_color(ray, self.scene, rng, MAX_RAY_DEPTH)
+    }
+}
+
+/// Resolves the film into the final image.
+fn develop(film: &Film, post_effects: &PostEffects, rng: &Random) -> Image {
+    let mut image = film.to_image();
+    if !post_effects.is_empty() {
+        post_effects.apply(&mut image, rng);
+    }
+    image
+}
+
+/// Writes the image as a plain PPM file.
+fn write_image(
+    path: impl AsRef<Path>,
+    image: &Image,
+    pipeline: &ColorPipeline,
+) -> std::io::Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    writeln!(file, "P3\n{} {}\n255\n", image.width, image.height)?;
+    for j in (0..image.height).rev() {
+        for i in 0..image.width {
+            write!(file, "{}", image.get(i, j).write_color(pipeline))?;
+        }
+    }
+    Ok(())
 }
 
 fn main() -> std::io::Result<()> {
@@ -123,35 +412,70 @@ fn main() -> std::io::Result<()> {
     */
 
     // Set up the image.
-    const ASPECT_RATIO: f32 = 3.0 / 2.0;
-    const IMAGE_WIDTH: usize = 1200;
-    const IMAGE_HEIGHT: usize = (IMAGE_WIDTH as f32 / ASPECT_RATIO) as usize;
-    const SAMPLES_PER_PIXEL: usize = 500;
-    const MAX_RAY_DEPTH: usize = 50;
-    const GAMMA: f32 = 1.8;
+    let options = Options::parse(std::env::args().skip(1))?;
+    let (image_width, image_height) = options.size;
+    let aspect_ratio = image_width as f32 / image_height as f32;
+    let pipeline = ColorPipeline::default()
+        .with_exposure(options.exposure)
+        .with_tone_mapping(options.tone_mapping)
+        .with_transfer(options.transfer)
+        .with_dithering(options.dither)
+        .with_color_spaces(options.working_space, options.output_space);
+
+    // Only count what is reported, either as statistics or in the heatmap.
+    let heatmap_counts = options
+        .heatmap
+        .as_ref()
+        .is_some_and(|(metric, _)| *metric != HeatmapMetric::Time);
+    if options.stats || options.stats_json.is_some() || heatmap_counts {
+        stats::enable();
+    }
 
     // Set up the world.
-    let world: Box<dyn Hittable> = Box::new(random_scene(&mut rng));
+    let start = Instant::now();
+    let scene =
+        scenes::by_name(&options.scene, &mut rng)?.with_working_space(options.working_space);
+    let scene_time = start.elapsed();
+
+    let sampler = create_sampler(options.sampler, SAMPLES_PER_PIXEL);
+    let spectral = options
+        .spectral
+        .then(|| SpectralSampler::new(options.working_space));
 
     // Set up the camera.
-    const LOOK_FROM: Point3 = Point3::new(13., 2., 3.);
-    const LOOK_AT: Point3 = Point3::new(0., 0., 0.);
-    const VIEW_UP: Vec3 = Vec3::new(0., 1., 0.);
-    const APERTURE: f32 = 0.1;
-    let dist_to_focus = 10.;
-
-    let camera = Camera::new(
-        LOOK_FROM,
-        LOOK_AT,
-        VIEW_UP,
-        Degrees(20.),
-        ASPECT_RATIO,
-        APERTURE,
-        dist_to_focus,
-    );
+    let focus = options.focus.unwrap_or(scene.focus);
+    let focus_distance = focus_distance(focus, &options, &VIEW, &scene, &rng)?;
+
+    if options.preview {
+        #[cfg(feature = "preview")]
+        return preview::render(
+            &options,
+            &scene,
+            sampler,
+            spectral.as_ref(),
+            focus_distance,
+            &pipeline,
+            &rng,
+        );
+        #[cfg(not(feature = "preview"))]
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "the preview window requires building with `--features preview`",
+        ));
+    }
+
+    let camera = create_camera(&options, &VIEW, aspect_ratio, focus_distance, scene.shutter)?;
+    let renderer = Renderer {
+        scene: &scene,
+        camera: camera.as_ref(),
+        sampler,
+        spectral: spectral.as_ref(),
+        size: options.size,
+        heatmap: options.heatmap.as_ref().map(|(metric, _)| *metric),
+    };
 
     // Prepare progress bar.
-    let bar = ProgressBar::new(IMAGE_HEIGHT as _);
+    let bar = ProgressBar::new(image_height as _);
     bar.set_style(
         ProgressStyle::default_bar()
             .template(
@@ -160,40 +484,126 @@ fn main() -> std::io::Result<()> {
             .progress_chars("##-"),
     );
 
-    // Render.
-    let mut file = File::create("test.ppm")?;
-    writeln!(file, "P3\n{} {}\n255\n", IMAGE_WIDTH, IMAGE_HEIGHT)?;
+    // The terminal preview shows the progress instead of the bar.
+    let mut terminal_preview = options
+        .terminal_preview
+        .map(|(graphics, columns)| TerminalPreview::new(graphics, columns));
+    if terminal_preview.is_some() {
+        bar.set_draw_target(ProgressDrawTarget::hidden());
+    }
+    let status = |bar: &ProgressBar| {
+        format!(
+            "[{}] {}/{} rows",
+            HumanDuration(bar.elapsed()),
+            bar.position(),
+            image_height
+        )
+    };
 
-    for j in (0..IMAGE_HEIGHT).rev() {
+    // Render.
+    let start = Instant::now();
+    let mut film = Film::new(image_width, image_height, options.filter);
+    let mut heatmap = options
+        .heatmap
+        .as_ref()
+        .map(|_| Heatmap::new(image_width, image_height));
+    for j in (0..image_height).rev() {
         bar.inc(1);
 
-        for pixel_color in (0..IMAGE_WIDTH)
-            .into_par_iter()
-            .map(|i| {
-                (0..SAMPLES_PER_PIXEL)
-                    .into_par_iter()
-                    .fold(Color::default, |sum, _idx| {
-                        let u_rnd = rng.sample();
-                        let v_rnd = rng.sample();
-
-                        let u = (i as f32 + u_rnd) / (IMAGE_WIDTH as f32 - 1.);
-                        let v = (j as f32 + v_rnd) / (IMAGE_HEIGHT as f32 - 1.);
-
-                        let r = camera.get_ray(u, v, &rng);
-                        sum + ray_color(&r, &world, &rng, MAX_RAY_DEPTH)
-                    })
-                    .reduce(Color::default, |sum, next| sum + next)
-            })
-            .collect::<Vec<Color>>()
+        for (i, (tile, cost)) in renderer
+            .render_row(&film, j, 0..SAMPLES_PER_PIXEL)
+            .iter()
+            .enumerate()
         {
-            write!(
-                file,
-                "{}",
-                pixel_color.write_color(SAMPLES_PER_PIXEL, GAMMA)
-            )?;
+            film.add_tile(tile);
+            if let Some(heatmap) = &mut heatmap {
+                heatmap.add(i, j, cost / SAMPLES_PER_PIXEL as f64);
+            }
+        }
+
+        if let Some(preview) = &mut terminal_preview {
+            if preview.is_due() {
+                preview.draw(&film.to_image(), &pipeline, &status(&bar))?;
+            }
         }
     }
 
     bar.finish();
+    let render_time = start.elapsed();
+
+    let start = Instant::now();
+    let image = develop(&film, &options.post_effects, &rng);
+    if let Some(preview) = &mut terminal_preview {
+        preview.draw(&image, &pipeline, &status(&bar))?;
+    }
+    write_image(OUTPUT_FILE, &image, &pipeline)?;
+    if let (Some((metric, path)), Some(heatmap)) = (&options.heatmap, &heatmap) {
+        // The colors of the heatmap are already display encoded.
+        let pipeline = ColorPipeline::default().with_transfer(TransferFunction::Gamma(1.));
+        write_image(path, &heatmap.to_image(), &pipeline)?;
+        eprintln!(
+            "The heatmap in {} saturates at {:.2} {} per sample.",
+            path,
+            heatmap.full_scale(),
+            metric.unit()
+        );
+    }
+    let output_time = start.elapsed();
+
+    if options.stats || options.stats_json.is_some() {
+        let acceleration_time = stats::acceleration_build_time();
+        let report = Report {
+            counters: Counters::collect(),
+            phases: vec![
+                ("scene", scene_time.saturating_sub(acceleration_time)),
+                ("acceleration_structure", acceleration_time),
+                ("render", render_time),
+                ("output", output_time),
+            ],
+        };
+        if options.stats {
+            eprint!("{}", report);
+        }
+        if let Some(path) = &options.stats_json {
+            std::fs::write(path, report.to_json())?;
+        }
+    }
     Ok(())
 }
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use crate::color_space::ColorSpace;
+    use crate::objects::HittableList;
+    use space_partitioning::quadtree::QuadRect;
+
+    #[test]
+    pub fn spectral_rendering_through_a_lens_works() {
+        let options = Options::parse(["--projection", "lens"].map(String::from)).unwrap();
+        let camera = create_camera(&options, &VIEW, 1., 10., Shutter::default()).unwrap();
+        let scene = Scene::new(HittableList::new(QuadRect::new(-1, -1, 2, 2)));
+        let spectral = SpectralSampler::new(ColorSpace::Rec709);
+        let render = |spectral: Option<&SpectralSampler>| {
+            let renderer = Renderer {
+                scene: &scene,
+                camera: camera.as_ref(),
+                sampler: create_sampler(SamplerType::Independent, 1),
+                spectral,
+                size: (3, 3),
+                heatmap: None,
+            };
+            const SAMPLES: usize = 4000;
+            (0..SAMPLES)
+                .map(|index| renderer.render_sample(1, 1, index).2)
+                .fold(Color::default(), |sum, color| sum + color)
+                / SAMPLES as f32
+        };
+
+        // The sky looked at is blended between white and blue.
+        let rgb = render(None);
+        let spectral = render(Some(&spectral));
+        assert!(rgb.z() > rgb.x());
+        assert!((spectral - rgb).len() < 0.1, "{:?} != {:?}", spectral, rgb);
+    }
+}
@@ -1,11 +1,22 @@
+mod conductor;
 mod dielectric;
+mod henyey_greenstein;
+mod isotropic;
 mod lambertian;
 mod metal;
+mod microfacet;
+mod principled;
+mod rough_dielectric;
 
-use crate::{Color, HitRecord, Random, Ray};
-pub use dielectric::Dielectric;
+use crate::{Color, HitRecord, Point3, Random, Ray};
+pub use conductor::{ComplexIor, Conductor};
+pub use dielectric::{Dielectric, Dispersion};
+pub use henyey_greenstein::HenyeyGreenstein;
+pub use isotropic::Isotropic;
 pub use lambertian::Lambertian;
 pub use metal::Metal;
+pub use principled::{Parameter, Principled};
+pub use rough_dielectric::RoughDielectric;
 use std::sync::Arc;
 
 pub type MaterialPtr = Arc<Box<dyn Material>>;
@@ -17,4 +28,52 @@ pub struct ScatteredRay {
 
 pub trait Material: Send + Sync {
     fn scatter(&self, ray: &Ray, hit: &HitRecord, rng: &Random) -> Option<ScatteredRay>;
+
+    /// The light emitted by the material at the specified surface coordinates.
+    fn emitted(&self, _u: f32, _v: f32, _p: &Point3) -> Color {
+        Color::default()
+    }
+
+    /// The type of the material, e.g. for statistics.
+    fn kind(&self) -> MaterialKind;
+}
+
+/// The types of materials, usable as an index into per-type tables.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MaterialKind {
+    Lambertian,
+    Metal,
+    Dielectric,
+    RoughDielectric,
+    Conductor,
+    Principled,
+    Isotropic,
+    HenyeyGreenstein,
+}
+
+impl MaterialKind {
+    pub const COUNT: usize = 8;
+    pub const ALL: [MaterialKind; Self::COUNT] = [
+        MaterialKind::Lambertian,
+        MaterialKind::Metal,
+        MaterialKind::Dielectric,
+        MaterialKind::RoughDielectric,
+        MaterialKind::Conductor,
+        MaterialKind::Principled,
+        MaterialKind::Isotropic,
+        MaterialKind::HenyeyGreenstein,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MaterialKind::Lambertian => "Lambertian",
+            MaterialKind::Metal => "Metal",
+            MaterialKind::Dielectric => "Dielectric",
+            MaterialKind::RoughDielectric => "RoughDielectric",
+            MaterialKind::Conductor => "Conductor",
+            MaterialKind::Principled => "Principled",
+            MaterialKind::Isotropic => "Isotropic",
+            MaterialKind::HenyeyGreenstein => "HenyeyGreenstein",
+        }
+    }
 }
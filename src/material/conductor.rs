@@ -0,0 +1,80 @@
+use crate::material::microfacet::{fresnel_conductor, TrowbridgeReitz};
+use crate::material::{MaterialKind, ScatteredRay};
+use crate::primitives::Onb;
+use crate::{Color, HitRecord, Material, Random, Ray};
+
+/// A complex index of refraction `eta + i k` given per color channel.
+#[derive(Debug, Copy, Clone)]
+pub struct ComplexIor {
+    /// The real part, i.e. the regular index of refraction.
+    pub eta: Color,
+    /// The imaginary part, i.e. the extinction coefficient.
+    pub k: Color,
+}
+
+impl ComplexIor {
+    /// Gold, sampled at 650, 550 and 450 nm.
+    pub const GOLD: ComplexIor = ComplexIor {
+        eta: Color::new(0.143, 0.374, 1.442),
+        k: Color::new(3.983, 2.385, 1.603),
+    };
+
+    /// Copper, sampled at 650, 550 and 450 nm.
+    pub const COPPER: ComplexIor = ComplexIor {
+        eta: Color::new(0.200, 0.924, 1.102),
+        k: Color::new(3.912, 2.452, 2.142),
+    };
+
+    /// Aluminium, sampled at 650, 550 and 450 nm.
+    pub const ALUMINIUM: ComplexIor = ComplexIor {
+        eta: Color::new(1.657, 0.880, 0.521),
+        k: Color::new(9.224, 6.270, 4.837),
+    };
+}
+
+/// A rough metal using a GGX microfacet distribution.
+///
+/// Unlike [`Metal`](crate::material::Metal), the reflected directions are drawn from the
+/// distribution of visible normals and weighted by the Fresnel and Smith
+/// shadowing-masking terms, so the material does not gain energy at grazing angles.
+pub struct Conductor {
+    ior: ComplexIor,
+    distribution: TrowbridgeReitz,
+}
+
+impl Conductor {
+    pub fn new(ior: ComplexIor, roughness: f32) -> Self {
+        Self {
+            ior,
+            distribution: TrowbridgeReitz::from_roughness(roughness),
+        }
+    }
+}
+
+impl Material for Conductor {
+    fn scatter(&self, ray: &Ray, hit: &HitRecord, rng: &Random) -> Option<ScatteredRay> {
+        let frame = Onb::from_w(&hit.normal);
+        let wo = frame.world_to_local(&-ray.direction);
+        if wo.z() <= 0. {
+            return None;
+        }
+
+        let m = self.distribution.sample_visible_normal(&wo, rng);
+        let wi = (-wo).reflect(&m);
+        if wi.z() <= 0. {
+            return None;
+        }
+
+        let fresnel = fresnel_conductor(wo.dot(&m), &self.ior.eta, &self.ior.k);
+        let masking = self.distribution.g2(&wo, &wi) / self.distribution.g1(&wo);
+
+        Some(ScatteredRay {
+            ray: ray.spawn(hit.point, frame.local_to_world(&wi)),
+            attenuation: fresnel * masking,
+        })
+    }
+
+    fn kind(&self) -> MaterialKind {
+        MaterialKind::Conductor
+    }
+}
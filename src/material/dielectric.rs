@@ -1,15 +1,92 @@
-use crate::material::ScatteredRay;
+use crate::material::{MaterialKind, ScatteredRay};
 use crate::{Color, HitRecord, Material, Random, Ray, Vec3};
 use num_traits::Pow;
 
+/// Wavelength dependent index of refraction models.
+///
+/// Wavelengths are given in nanometers, while the coefficients
+/// use micrometers as is customary in optics catalogs.
+#[derive(Debug, Copy, Clone)]
+pub enum Dispersion {
+    /// Cauchy's equation, `n(λ) = A + B / λ²`.
+    Cauchy { a: f32, b: f32 },
+    /// The three-term Sellmeier equation, `n²(λ) = 1 + Σ Bᵢ λ² / (λ² - Cᵢ)`.
+    Sellmeier { b: [f32; 3], c: [f32; 3] },
+}
+
+impl Dispersion {
+    /// Schott N-BK7 borosilicate crown glass.
+    pub const BK7: Dispersion = Dispersion::Sellmeier {
+        b: [1.039_612, 0.231_792_3, 1.010_469_5],
+        c: [0.006_000_699, 0.020_017_914, 103.560_66],
+    };
+
+    /// Schott SF11 dense flint glass, which disperses much stronger than BK7.
+    pub const SF11: Dispersion = Dispersion::Sellmeier {
+        b: [1.737_597, 0.313_747_34, 1.898_781],
+        c: [0.013_188_707, 0.062_306_814, 155.236_3],
+    };
+
+    /// The wavelengths in nanometers representing the red, green and blue channels.
+    pub const RGB_WAVELENGTHS: [f32; 3] = [610., 550., 465.];
+
+    /// Determines the index of refraction at the specified wavelength in nanometers.
+    pub fn ior(&self, wavelength: f32) -> f32 {
+        let lambda = wavelength * 1e-3;
+        let lambda2 = lambda * lambda;
+        match self {
+            Dispersion::Cauchy { a, b } => a + b / lambda2,
+            Dispersion::Sellmeier { b, c } => (1.
+                + b[0] * lambda2 / (lambda2 - c[0])
+                + b[1] * lambda2 / (lambda2 - c[1])
+                + b[2] * lambda2 / (lambda2 - c[2]))
+                .sqrt(),
+        }
+    }
+}
+
 pub struct Dielectric {
     /// The index of refraction.
     ir: f32,
+    /// The absorption coefficient per unit distance travelled inside the medium.
+    absorption: Color,
+    /// The optional wavelength dependency of the index of refraction.
+    dispersion: Option<Dispersion>,
 }
 
 impl Dielectric {
     pub fn new(ir: f32) -> Self {
-        Self { ir }
+        Self {
+            ir,
+            absorption: Color::default(),
+            dispersion: None,
+        }
+    }
+
+    /// Sets the absorption coefficient applied using the Beer–Lambert law.
+    pub fn with_absorption(mut self, absorption: Color) -> Self {
+        self.absorption = absorption;
+        self
+    }
+
+    /// Sets the absorption such that light travelling `distance` units
+    /// through the medium is tinted by `color`.
+    pub fn with_transmittance(self, color: Color, distance: f32) -> Self {
+        let absorption = |c: f32| -c.clamp(1e-6, 1.).ln() / distance;
+        self.with_absorption(Color::new(
+            absorption(color.x()),
+            absorption(color.y()),
+            absorption(color.z()),
+        ))
+    }
+
+    /// Makes the index of refraction wavelength dependent.
+    ///
+    /// The constant index of refraction is replaced by the one at the green wavelength.
+    pub fn with_dispersion(mut self, dispersion: Dispersion) -> Self {
+        self.ir = dispersion.ior(Dispersion::RGB_WAVELENGTHS[1]);
+        self.dispersion = Some(dispersion);
+        self
     }
 
     fn reflectance(cosine: f32, ref_idx: f32) -> f32 {
@@ -22,13 +99,31 @@ impl Dielectric {
 
 impl Material for Dielectric {
     fn scatter(&self, ray: &Ray, hit: &HitRecord, rng: &Random) -> Option<ScatteredRay> {
-        let attenuation: Color = Vec3::new(1., 1., 1.);
-        let refraction_ratio = if hit.is_front_facing {
-            1. / self.ir
-        } else {
-            self.ir
+        let mut attenuation: Color = Vec3::new(1., 1., 1.);
+        let mut wavelength = ray.wavelength;
+
+        let ir = match (&self.dispersion, wavelength) {
+            (None, _) => self.ir,
+            (Some(dispersion), Some(lambda)) => dispersion.ior(lambda),
+            (Some(dispersion), None) => {
+                // Restrict the path to the wavelength of one randomly selected color channel;
+                // the renderer drops the other channels once it sees the restriction.
+                let channel = ((rng.sample() * 3.) as usize).min(2);
+                let lambda = ray.channel_wavelengths().e[channel];
+                wavelength = Some(lambda);
+                dispersion.ior(lambda)
+            }
         };
 
+        // Leaving the medium, apply the absorption over the distance travelled inside.
+        if !hit.is_front_facing {
+            let absorbed = self.absorption * -hit.t;
+            attenuation = attenuation
+                * Color::new(absorbed.x().exp(), absorbed.y().exp(), absorbed.z().exp());
+        }
+
+        let refraction_ratio = if hit.is_front_facing { 1. / ir } else { ir };
+
         let unit_direction = ray.direction.as_unit_vector();
         let cos_theta = (-unit_direction).dot(&hit.normal).min(1.0);
         let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
@@ -41,9 +136,39 @@ impl Material for Dielectric {
                 unit_direction.refract(&hit.normal, refraction_ratio)
             };
 
+        let mut scattered = ray.spawn(hit.point, direction);
+        if let Some(lambda) = wavelength {
+            scattered = scattered.with_wavelength(lambda);
+        }
+
         Some(ScatteredRay {
-            ray: Ray::new(hit.point, direction),
+            ray: scattered,
             attenuation,
         })
     }
+
+    fn kind(&self) -> MaterialKind {
+        MaterialKind::Dielectric
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    pub fn sellmeier_matches_catalog_value() {
+        // N-BK7 has n_d = 1.5168 at the helium d-line.
+        assert!((Dispersion::BK7.ior(587.6) - 1.5168).abs() < 1e-4);
+    }
+
+    #[test]
+    pub fn blue_light_refracts_stronger() {
+        let cauchy = Dispersion::Cauchy {
+            a: 1.458,
+            b: 0.00354,
+        };
+        assert!(cauchy.ior(465.) > cauchy.ior(610.));
+        assert!(Dispersion::SF11.ior(465.) > Dispersion::SF11.ior(610.));
+    }
 }
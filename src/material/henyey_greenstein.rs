@@ -0,0 +1,88 @@
+use crate::material::{MaterialKind, ScatteredRay};
+use crate::primitives::Onb;
+use crate::{Color, HitRecord, Material, Random, Ray, Vec3};
+use std::f32::consts::PI;
+
+/// The Henyey–Greenstein phase function for anisotropic scattering in participating media.
+pub struct HenyeyGreenstein {
+    albedo: Color,
+    /// The asymmetry parameter in range `-1.0..1.0`; positive values scatter forward,
+    /// negative values backward, and zero is isotropic.
+    g: f32,
+}
+
+impl HenyeyGreenstein {
+    pub fn new(albedo: Color, g: f32) -> Self {
+        Self {
+            albedo,
+            g: g.clamp(-0.999, 0.999),
+        }
+    }
+
+    /// Samples the cosine of the angle between the incoming and the scattered direction.
+    fn sample_cos_theta(&self, rng: &Random) -> f32 {
+        let xi = rng.sample();
+        if self.g.abs() < 1e-3 {
+            return 1. - 2. * xi;
+        }
+
+        let g = self.g;
+        let term = (1. - g * g) / (1. - g + 2. * g * xi);
+        ((1. + g * g - term * term) / (2. * g)).clamp(-1., 1.)
+    }
+}
+
+impl Material for HenyeyGreenstein {
+    fn scatter(&self, ray: &Ray, hit: &HitRecord, rng: &Random) -> Option<ScatteredRay> {
+        let cos_theta = self.sample_cos_theta(rng);
+        let sin_theta = (1. - cos_theta * cos_theta).max(0.).sqrt();
+        let phi = 2. * PI * rng.sample();
+
+        let frame = Onb::from_w(&ray.direction);
+        let direction = frame.local_to_world(&Vec3::new(
+            sin_theta * phi.cos(),
+            sin_theta * phi.sin(),
+            cos_theta,
+        ));
+
+        Some(ScatteredRay {
+            ray: ray.spawn(hit.point, direction),
+            attenuation: self.albedo,
+        })
+    }
+
+    fn kind(&self) -> MaterialKind {
+        MaterialKind::HenyeyGreenstein
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use crate::Point3;
+    use std::sync::Arc;
+
+    /// Averages the scattered directions of a ray travelling along X.
+    fn mean_direction(g: f32) -> Vec3 {
+        let material: Arc<Box<dyn Material>> =
+            Arc::new(Box::new(HenyeyGreenstein::new(Color::new(1., 1., 1.), g)));
+        let ray = Ray::new(Point3::default(), Vec3::new(1., 0., 0.));
+        let hit = HitRecord::new_from_ray(&ray, 1., ray.at(1.), -ray.direction, material.clone());
+        let rng = Random::default();
+        let n = 20_000;
+        let sum = (0..n)
+            .map(|_| material.scatter(&ray, &hit, &rng).unwrap().ray.direction)
+            .fold(Vec3::default(), |sum, direction| sum + direction);
+        sum / n as f32
+    }
+
+    #[test]
+    pub fn mean_cosine_is_the_asymmetry() {
+        for g in [-0.5, 0.3, 0.8] {
+            let mean = mean_direction(g);
+            assert!((mean.x() - g).abs() < 0.02, "{} != {}", mean.x(), g);
+        }
+        // Without asymmetry, scattering is isotropic.
+        assert!(mean_direction(0.).len() < 0.03);
+    }
+}
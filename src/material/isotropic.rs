@@ -0,0 +1,26 @@
+use crate::material::{MaterialKind, ScatteredRay};
+use crate::{Color, HitRecord, Material, Random, Ray, Vec3};
+
+/// A phase function scattering uniformly in all directions, used inside participating media.
+pub struct Isotropic {
+    albedo: Color,
+}
+
+impl Isotropic {
+    pub fn new(albedo: Color) -> Self {
+        Self { albedo }
+    }
+}
+
+impl Material for Isotropic {
+    fn scatter(&self, ray: &Ray, hit: &HitRecord, rng: &Random) -> Option<ScatteredRay> {
+        Some(ScatteredRay {
+            ray: ray.spawn(hit.point, Vec3::random_unit(rng)),
+            attenuation: self.albedo,
+        })
+    }
+
+    fn kind(&self) -> MaterialKind {
+        MaterialKind::Isotropic
+    }
+}
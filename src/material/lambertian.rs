@@ -1,4 +1,4 @@
-use crate::material::ScatteredRay;
+use crate::material::{MaterialKind, ScatteredRay};
 use crate::{Color, HitRecord, Material, Random, Ray, Vec3};
 
 pub struct Lambertian {
@@ -16,7 +16,7 @@ impl Lambertian {
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, _ray: &Ray, hit: &HitRecord, rng: &Random) -> Option<ScatteredRay> {
+    fn scatter(&self, ray: &Ray, hit: &HitRecord, rng: &Random) -> Option<ScatteredRay> {
         let p = rng.sample();
         if p > self.scatter_probability {
             return None;
@@ -29,10 +29,14 @@ impl Material for Lambertian {
             scatter_direction = hit.normal;
         }
 
-        let scattered = Ray::new(hit.point, scatter_direction);
+        let scattered = ray.spawn(hit.point, scatter_direction);
         Some(ScatteredRay {
             ray: scattered,
             attenuation: self.albedo / self.scatter_probability,
         })
     }
+
+    fn kind(&self) -> MaterialKind {
+        MaterialKind::Lambertian
+    }
 }
@@ -1,4 +1,4 @@
-use crate::material::ScatteredRay;
+use crate::material::{MaterialKind, ScatteredRay};
 use crate::{Color, HitRecord, Material, Random, Ray, Vec3};
 
 pub struct Metal {
@@ -18,7 +18,7 @@ impl Metal {
 impl Material for Metal {
     fn scatter(&self, ray: &Ray, hit: &HitRecord, rng: &Random) -> Option<ScatteredRay> {
         let reflected = ray.direction.as_unit_vector().reflect(&hit.normal);
-        let scattered = Ray::new(
+        let scattered = ray.spawn(
             hit.point,
             reflected + self.fuzziness * Vec3::random_in_unit_sphere(rng),
         );
@@ -32,4 +32,8 @@ impl Material for Metal {
             attenuation: self.albedo,
         })
     }
+
+    fn kind(&self) -> MaterialKind {
+        MaterialKind::Metal
+    }
 }
@@ -0,0 +1,168 @@
+//! Microfacet distribution and Fresnel helpers shared by the rough materials.
+//!
+//! All directions are expected in the local shading frame, i.e. with the
+//! surface normal pointing along `+z` (see [`Onb`](crate::primitives::Onb)).
+
+use crate::{Color, Random, Vec3};
+use std::f32::consts::PI;
+
+/// The GGX / Trowbridge-Reitz microfacet distribution with
+/// Smith height-correlated shadowing-masking.
+#[derive(Debug, Copy, Clone)]
+pub struct TrowbridgeReitz {
+    alpha: f32,
+}
+
+impl TrowbridgeReitz {
+    /// The smallest roughness we allow; anything below becomes numerically unstable.
+    const MIN_ALPHA: f32 = 1e-4;
+
+    /// Creates the distribution from a perceptual roughness in range `0.0..=1.0`.
+    pub fn from_roughness(roughness: f32) -> Self {
+        let roughness = roughness.clamp(0., 1.);
+        Self {
+            alpha: (roughness * roughness).max(Self::MIN_ALPHA),
+        }
+    }
+
+    /// The Smith auxiliary function `Λ(w)`.
+    pub fn lambda(&self, w: &Vec3) -> f32 {
+        let cos2 = w.z() * w.z();
+        if cos2 <= 0. {
+            return f32::INFINITY;
+        }
+        let tan2 = (1. - cos2).max(0.) / cos2;
+        0.5 * ((1. + self.alpha * self.alpha * tan2).sqrt() - 1.)
+    }
+
+    /// The Smith masking function for a single direction.
+    pub fn g1(&self, w: &Vec3) -> f32 {
+        1. / (1. + self.lambda(w))
+    }
+
+    /// The height-correlated Smith shadowing-masking function.
+    pub fn g2(&self, wo: &Vec3, wi: &Vec3) -> f32 {
+        1. / (1. + self.lambda(wo) + self.lambda(wi))
+    }
+
+    /// Samples a microfacet normal from the distribution of normals visible from `wo`.
+    ///
+    /// See Heitz, "Sampling the GGX Distribution of Visible Normals" (JCGT 2018).
+    pub fn sample_visible_normal(&self, wo: &Vec3, rng: &Random) -> Vec3 {
+        // Transform the view direction to the hemisphere configuration.
+        let vh = Vec3::new(self.alpha * wo.x(), self.alpha * wo.y(), wo.z()).as_unit_vector();
+
+        // Build an orthonormal basis around it.
+        let len_sq = vh.x() * vh.x() + vh.y() * vh.y();
+        let t1 = if len_sq > 0. {
+            Vec3::new(-vh.y(), vh.x(), 0.) / len_sq.sqrt()
+        } else {
+            Vec3::new(1., 0., 0.)
+        };
+        let t2 = vh.cross(&t1);
+
+        // Sample the projected area of the visible hemisphere.
+        let r = rng.sample().sqrt();
+        let phi = 2. * PI * rng.sample();
+        let p1 = r * phi.cos();
+        let p2 = r * phi.sin();
+        let s = 0.5 * (1. + vh.z());
+        let p2 = (1. - s) * (1. - p1 * p1).max(0.).sqrt() + s * p2;
+
+        // Reproject onto the hemisphere and transform back to the ellipsoid configuration.
+        let nh = p1 * t1 + p2 * t2 + (1. - p1 * p1 - p2 * p2).max(0.).sqrt() * vh;
+        Vec3::new(self.alpha * nh.x(), self.alpha * nh.y(), nh.z().max(0.)).as_unit_vector()
+    }
+}
+
+/// Unpolarized Fresnel reflectance at a dielectric interface.
+///
+/// # Arguments
+/// * `cos_i`: The cosine between the incident direction and the (micro) normal.
+/// * `eta`: The relative index of refraction, i.e. transmitted over incident side.
+pub fn fresnel_dielectric(cos_i: f32, eta: f32) -> f32 {
+    let cos_i = cos_i.clamp(0., 1.);
+    let sin2_t = (1. - cos_i * cos_i) / (eta * eta);
+    if sin2_t >= 1. {
+        // Total internal reflection.
+        return 1.;
+    }
+
+    let cos_t = (1. - sin2_t).sqrt();
+    let r_parallel = (eta * cos_i - cos_t) / (eta * cos_i + cos_t);
+    let r_perpendicular = (cos_i - eta * cos_t) / (cos_i + eta * cos_t);
+    0.5 * (r_parallel * r_parallel + r_perpendicular * r_perpendicular)
+}
+
+/// Unpolarized Fresnel reflectance at a conductor interface with the
+/// complex index of refraction `eta + i k`, evaluated per color channel.
+pub fn fresnel_conductor(cos_i: f32, eta: &Color, k: &Color) -> Color {
+    let cos_i = cos_i.clamp(0., 1.);
+    let mut reflectance = Color::default();
+    for c in 0..3 {
+        let cos2 = cos_i * cos_i;
+        let sin2 = 1. - cos2;
+        let eta2 = eta.e[c] * eta.e[c];
+        let k2 = k.e[c] * k.e[c];
+
+        let t0 = eta2 - k2 - sin2;
+        let a2_plus_b2 = (t0 * t0 + 4. * eta2 * k2).sqrt();
+        let t1 = a2_plus_b2 + cos2;
+        let a = (0.5 * (a2_plus_b2 + t0)).max(0.).sqrt();
+        let t2 = 2. * cos_i * a;
+        let rs = (t1 - t2) / (t1 + t2);
+
+        let t3 = cos2 * a2_plus_b2 + sin2 * sin2;
+        let t4 = t2 * sin2;
+        let rp = rs * (t3 - t4) / (t3 + t4);
+
+        reflectance.e[c] = 0.5 * (rp + rs);
+    }
+    reflectance
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    pub fn dielectric_fresnel_at_normal_incidence() {
+        // ((1 - 1.5) / (1 + 1.5))^2 = 0.04
+        assert!((fresnel_dielectric(1., 1.5) - 0.04).abs() < 1e-5);
+    }
+
+    #[test]
+    pub fn dielectric_fresnel_total_internal_reflection() {
+        assert_eq!(fresnel_dielectric(0.1, 1. / 1.5), 1.);
+    }
+
+    #[test]
+    pub fn conductor_fresnel_matches_dielectric_without_absorption() {
+        let eta = Color::new(1.5, 1.5, 1.5);
+        let k = Color::default();
+        for &cos_i in &[1., 0.7, 0.3] {
+            let expected = fresnel_dielectric(cos_i, 1.5);
+            let actual = fresnel_conductor(cos_i, &eta, &k);
+            assert!((actual.x() - expected).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    pub fn visible_normals_are_in_upper_hemisphere() {
+        let rng = Random::default();
+        let distribution = TrowbridgeReitz::from_roughness(0.7);
+        let wo = Vec3::new(0.6, 0., 0.8);
+        for _ in 0..1000 {
+            let m = distribution.sample_visible_normal(&wo, &rng);
+            assert!(m.z() >= 0.);
+            assert!((m.len() - 1.).abs() < 1e-4);
+            assert!(m.dot(&wo) >= -1e-4);
+        }
+    }
+
+    #[test]
+    pub fn smith_masking_is_one_at_normal_incidence() {
+        let distribution = TrowbridgeReitz::from_roughness(0.5);
+        assert!((distribution.g1(&Vec3::new(0., 0., 1.)) - 1.).abs() < 1e-6);
+    }
+}
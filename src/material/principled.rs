@@ -0,0 +1,370 @@
+use crate::material::microfacet::{fresnel_dielectric, TrowbridgeReitz};
+use crate::material::{MaterialKind, ScatteredRay};
+use crate::primitives::Onb;
+use crate::texture::{SolidColor, TexturePtr};
+use crate::{Color, HitRecord, Material, Point3, Random, Ray, Vec3};
+use num_traits::Pow;
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+/// A scalar material parameter, either constant or read from one channel of a texture.
+#[derive(Clone)]
+pub enum Parameter {
+    Constant(f32),
+    Texture {
+        texture: TexturePtr,
+        /// The color channel to read, i.e. `0` for red, `1` for green and `2` for blue.
+        channel: usize,
+        /// The factor the texture value is multiplied with.
+        factor: f32,
+    },
+}
+
+impl Parameter {
+    pub fn value(&self, u: f32, v: f32, p: &Point3) -> f32 {
+        match self {
+            Parameter::Constant(value) => *value,
+            Parameter::Texture {
+                texture,
+                channel,
+                factor,
+            } => factor * texture.value(u, v, p).e[*channel],
+        }
+    }
+}
+
+impl From<f32> for Parameter {
+    fn from(value: f32) -> Self {
+        Parameter::Constant(value)
+    }
+}
+
+/// A layered "uber" material loosely following the Disney principled BSDF.
+///
+/// The layers are, from top to bottom: an optional clear coat, a metallic or
+/// dielectric specular GGX lobe and, below the dielectric specular, either
+/// a (rough) transmission or a diffuse lobe with sheen. A lobe is chosen
+/// stochastically per scattering event.
+pub struct Principled {
+    base_color: TexturePtr,
+    metallic: Parameter,
+    roughness: Parameter,
+    /// Scales the dielectric reflectance derived from the index of refraction,
+    /// as glTF's specular factor does; the default `0.5` leaves it unscaled.
+    specular: Parameter,
+    clearcoat: Parameter,
+    clearcoat_roughness: f32,
+    sheen: Parameter,
+    sheen_tint: Parameter,
+    transmission: Parameter,
+    /// The index of refraction of the dielectric specular layer and the transmission.
+    ior: f32,
+    emission: TexturePtr,
+    emission_strength: f32,
+}
+
+impl Principled {
+    /// Creates a white, fully rough dielectric material with default parameters.
+    pub fn new(base_color: TexturePtr) -> Self {
+        Self {
+            base_color,
+            metallic: 0.0.into(),
+            roughness: 0.5.into(),
+            specular: 0.5.into(),
+            clearcoat: 0.0.into(),
+            clearcoat_roughness: 0.03,
+            sheen: 0.0.into(),
+            sheen_tint: 0.5.into(),
+            transmission: 0.0.into(),
+            ior: 1.5,
+            emission: Arc::new(Box::new(SolidColor::new(Color::default()))),
+            emission_strength: 0.,
+        }
+    }
+
+    /// Creates the material from the parameters of glTF's metallic-roughness model.
+    ///
+    /// As in glTF, the metalness is read from the blue and the roughness
+    /// from the green channel of the metallic-roughness texture, and both are
+    /// multiplied with their respective factors.
+    pub fn from_metallic_roughness(
+        base_color: TexturePtr,
+        metallic_factor: f32,
+        roughness_factor: f32,
+        metallic_roughness_texture: Option<TexturePtr>,
+    ) -> Self {
+        let (metallic, roughness) = match metallic_roughness_texture {
+            Some(texture) => (
+                Parameter::Texture {
+                    texture: texture.clone(),
+                    channel: 2,
+                    factor: metallic_factor,
+                },
+                Parameter::Texture {
+                    texture,
+                    channel: 1,
+                    factor: roughness_factor,
+                },
+            ),
+            None => (metallic_factor.into(), roughness_factor.into()),
+        };
+
+        Self::new(base_color)
+            .with_metallic(metallic)
+            .with_roughness(roughness)
+    }
+
+    pub fn with_metallic(mut self, metallic: impl Into<Parameter>) -> Self {
+        self.metallic = metallic.into();
+        self
+    }
+
+    pub fn with_roughness(mut self, roughness: impl Into<Parameter>) -> Self {
+        self.roughness = roughness.into();
+        self
+    }
+
+    pub fn with_specular(mut self, specular: impl Into<Parameter>) -> Self {
+        self.specular = specular.into();
+        self
+    }
+
+    pub fn with_clearcoat(mut self, clearcoat: impl Into<Parameter>, roughness: f32) -> Self {
+        self.clearcoat = clearcoat.into();
+        self.clearcoat_roughness = roughness;
+        self
+    }
+
+    pub fn with_sheen(mut self, sheen: impl Into<Parameter>, tint: impl Into<Parameter>) -> Self {
+        self.sheen = sheen.into();
+        self.sheen_tint = tint.into();
+        self
+    }
+
+    pub fn with_transmission(mut self, transmission: impl Into<Parameter>, ior: f32) -> Self {
+        self.transmission = transmission.into();
+        self.ior = ior;
+        self
+    }
+
+    pub fn with_emission(mut self, emission: TexturePtr, strength: f32) -> Self {
+        self.emission = emission;
+        self.emission_strength = strength;
+        self
+    }
+
+    /// Samples a GGX reflection about a visible microfacet normal.
+    ///
+    /// Returns the local incident direction, the microfacet normal and the
+    /// shadowing-masking weight, or `None` if the reflection points below the surface.
+    fn sample_reflection(
+        distribution: &TrowbridgeReitz,
+        wo: &Vec3,
+        rng: &Random,
+    ) -> Option<(Vec3, Vec3, f32)> {
+        let m = distribution.sample_visible_normal(wo, rng);
+        let wi = (-*wo).reflect(&m);
+        if wi.z() <= 0. {
+            return None;
+        }
+        Some((wi, m, distribution.g2(wo, &wi) / distribution.g1(wo)))
+    }
+}
+
+/// Schlick's approximation of the Fresnel reflectance for a colored reflectance at normal incidence.
+fn schlick(f0: Color, cos_i: f32) -> Color {
+    let weight = (1. - cos_i.clamp(0., 1.)).pow(5.);
+    f0 + (Color::new(1., 1., 1.) - f0) * weight
+}
+
+impl Material for Principled {
+    fn scatter(&self, ray: &Ray, hit: &HitRecord, rng: &Random) -> Option<ScatteredRay> {
+        let (u, v, p) = (hit.u, hit.v, &hit.point);
+        let base_color = self.base_color.value(u, v, p);
+        let roughness = self.roughness.value(u, v, p);
+
+        let frame = Onb::from_w(&hit.normal);
+        let wo = frame.world_to_local(&-ray.direction);
+        if wo.z() <= 0. {
+            return None;
+        }
+
+        let scattered = |wi: Vec3, attenuation: Color| {
+            Some(ScatteredRay {
+                ray: ray.spawn(hit.point, frame.local_to_world(&wi)),
+                attenuation,
+            })
+        };
+
+        // Leaving a transmissive object, only the dielectric interface matters.
+        let transmission = self.transmission.value(u, v, p).clamp(0., 1.);
+        if !hit.is_front_facing && transmission > 0. {
+            let distribution = TrowbridgeReitz::from_roughness(roughness);
+            let m = distribution.sample_visible_normal(&wo, rng);
+            let eta = 1. / self.ior;
+            let wi = if rng.sample() < fresnel_dielectric(wo.dot(&m), eta) {
+                (-wo).reflect(&m)
+            } else {
+                (-wo).refract(&m, self.ior)
+            };
+            let weight = distribution.g2(&wo, &wi) / distribution.g1(&wo);
+            return scattered(wi, Color::new(weight, weight, weight));
+        }
+
+        // Clear coat: a colorless dielectric layer on top of everything else.
+        let clearcoat = self.clearcoat.value(u, v, p).clamp(0., 1.);
+        if clearcoat > 0. && rng.sample() < clearcoat * fresnel_dielectric(wo.z(), 1.5) {
+            let distribution = TrowbridgeReitz::from_roughness(self.clearcoat_roughness);
+            let (wi, _, weight) = Self::sample_reflection(&distribution, &wo, rng)?;
+            return scattered(wi, Color::new(weight, weight, weight));
+        }
+
+        let distribution = TrowbridgeReitz::from_roughness(roughness);
+
+        // Metal: specular reflection tinted by the base color.
+        let metallic = self.metallic.value(u, v, p).clamp(0., 1.);
+        if rng.sample() < metallic {
+            let (wi, m, weight) = Self::sample_reflection(&distribution, &wo, rng)?;
+            return scattered(wi, schlick(base_color, wo.dot(&m)) * weight);
+        }
+
+        // Dielectric: white specular reflection with the reflectance of the same interface
+        // that is refracted through, scaled by `specular`.
+        let specular = 2. * self.specular.value(u, v, p).max(0.);
+        let m = distribution.sample_visible_normal(&wo, rng);
+        if rng.sample() < specular * fresnel_dielectric(wo.dot(&m), self.ior) {
+            let wi = (-wo).reflect(&m);
+            if wi.z() <= 0. {
+                return None;
+            }
+            let weight = distribution.g2(&wo, &wi) / distribution.g1(&wo);
+            return scattered(wi, Color::new(weight, weight, weight));
+        }
+
+        // Transmission: tinted rough glass.
+        if rng.sample() < transmission {
+            let wi = (-wo).refract(&m, 1. / self.ior);
+            if wi.z() >= 0. {
+                return None;
+            }
+            let weight = distribution.g2(&wo, &wi) / distribution.g1(&wo);
+            return scattered(wi, base_color * weight);
+        }
+
+        // Diffuse with a retro-reflective sheen at grazing angles.
+        let mut wi = Vec3::new(0., 0., 1.) + Vec3::random_unit(rng);
+        if wi.near_zero() {
+            wi = Vec3::new(0., 0., 1.);
+        }
+        let wi = wi.as_unit_vector();
+
+        let mut attenuation = base_color;
+        let sheen = self.sheen.value(u, v, p);
+        if sheen > 0. {
+            let half = (wi + wo).as_unit_vector();
+            let tint = self.sheen_tint.value(u, v, p).clamp(0., 1.);
+            let luminance =
+                0.2126 * base_color.x() + 0.7152 * base_color.y() + 0.0722 * base_color.z();
+            let tint_color = if luminance > 0. {
+                base_color / luminance
+            } else {
+                Color::new(1., 1., 1.)
+            };
+            let sheen_color = Color::new(1., 1., 1.) * (1. - tint) + tint_color * tint;
+            attenuation += sheen_color * (PI * sheen * (1. - wi.dot(&half)).pow(5.));
+        }
+
+        scattered(wi, attenuation)
+    }
+
+    fn emitted(&self, u: f32, v: f32, p: &Point3) -> Color {
+        if self.emission_strength <= 0. {
+            return Color::default();
+        }
+        self.emission.value(u, v, p) * self.emission_strength
+    }
+
+    fn kind(&self) -> MaterialKind {
+        MaterialKind::Principled
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use crate::objects::HitRecord;
+
+    /// Scatters a ray arriving from `wo` at the origin of a surface facing up.
+    fn scatter(material: Principled, wo: Vec3, count: usize) -> Vec<ScatteredRay> {
+        let material: Arc<Box<dyn Material>> = Arc::new(Box::new(material));
+        let ray = Ray::new(wo, -wo);
+        let hit = HitRecord::new_from_ray(
+            &ray,
+            wo.len(),
+            Point3::default(),
+            Vec3::new(0., 0., 1.),
+            material.clone(),
+        );
+        let rng = Random::default();
+        (0..count)
+            .filter_map(|_| material.scatter(&ray, &hit, &rng))
+            .collect()
+    }
+
+    fn solid(color: Color) -> TexturePtr {
+        Arc::new(Box::new(SolidColor::new(color)))
+    }
+
+    #[test]
+    pub fn metals_are_tinted_by_the_base_color() {
+        let base_color = Color::new(0.9, 0.5, 0.1);
+        let metal = Principled::new(solid(base_color))
+            .with_metallic(1.)
+            .with_roughness(0.2);
+        let rays = scatter(metal, Vec3::new(0., 0., 1.), 100);
+        assert!(!rays.is_empty());
+        for ray in rays {
+            assert!(ray.ray.direction.z() > 0.);
+            let color = ray.attenuation / ray.attenuation.x();
+            assert!((color - base_color / base_color.x()).len() < 0.05);
+        }
+    }
+
+    #[test]
+    pub fn transmission_refracts_into_the_surface() {
+        let glass = Principled::new(solid(Color::new(1., 1., 1.)))
+            .with_roughness(0.1)
+            .with_specular(0.)
+            .with_transmission(1., 1.5);
+        let rays = scatter(glass, Vec3::new(0.3, 0., 1.), 100);
+        assert!(rays.len() > 90);
+        assert!(rays.iter().all(|ray| ray.ray.direction.z() < 0.));
+    }
+
+    #[test]
+    pub fn reflectance_follows_the_index_of_refraction() {
+        // ((2 - 1) / (2 + 1))^2 = 1/9 of the light is reflected at normal incidence.
+        let glass = Principled::new(solid(Color::new(1., 1., 1.)))
+            .with_roughness(0.)
+            .with_transmission(1., 2.);
+        let rays = scatter(glass, Vec3::new(0., 0., 1.), 10_000);
+        let reflected = rays.iter().filter(|ray| ray.ray.direction.z() > 0.).count();
+        assert!((reflected as f32 / rays.len() as f32 - 1. / 9.).abs() < 0.02);
+    }
+
+    #[test]
+    pub fn grazing_rays_stay_finite() {
+        let materials = [
+            Principled::new(solid(Color::new(0.8, 0.8, 0.8))).with_sheen(1., 0.5),
+            Principled::new(solid(Color::new(0.8, 0.8, 0.8))).with_metallic(1.),
+            Principled::new(solid(Color::new(0.8, 0.8, 0.8))).with_clearcoat(1., 0.1),
+            Principled::new(solid(Color::new(0.8, 0.8, 0.8))).with_transmission(1., 1.5),
+        ];
+        for material in materials {
+            for ray in scatter(material, Vec3::new(1., 0., 1e-4), 100) {
+                assert!(ray.attenuation.e.iter().all(|c| c.is_finite()));
+                assert!(ray.ray.direction.e.iter().all(|c| c.is_finite()));
+            }
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use crate::material::microfacet::{fresnel_dielectric, TrowbridgeReitz};
+use crate::material::{MaterialKind, ScatteredRay};
+use crate::primitives::Onb;
+use crate::{Color, HitRecord, Material, Random, Ray};
+
+/// A frosted dielectric using a GGX microfacet distribution for both
+/// reflection and transmission (Walter et al., "Microfacet Models for
+/// Refraction through Rough Surfaces", 2007).
+pub struct RoughDielectric {
+    /// The index of refraction.
+    ir: f32,
+    distribution: TrowbridgeReitz,
+}
+
+impl RoughDielectric {
+    pub fn new(ir: f32, roughness: f32) -> Self {
+        Self {
+            ir,
+            distribution: TrowbridgeReitz::from_roughness(roughness),
+        }
+    }
+}
+
+impl Material for RoughDielectric {
+    fn scatter(&self, ray: &Ray, hit: &HitRecord, rng: &Random) -> Option<ScatteredRay> {
+        // The ratio of the transmitted over the incident side's index of refraction.
+        let eta = if hit.is_front_facing {
+            self.ir
+        } else {
+            1. / self.ir
+        };
+
+        let frame = Onb::from_w(&hit.normal);
+        let wo = frame.world_to_local(&-ray.direction);
+        if wo.z() <= 0. {
+            return None;
+        }
+
+        let m = self.distribution.sample_visible_normal(&wo, rng);
+        let cos_i = wo.dot(&m);
+
+        // Choose between reflection and refraction proportional to the Fresnel term,
+        // which then cancels out of the sample weight.
+        let wi = if rng.sample() < fresnel_dielectric(cos_i, eta) {
+            let wi = (-wo).reflect(&m);
+            if wi.z() <= 0. {
+                return None;
+            }
+            wi
+        } else {
+            let wi = (-wo).refract(&m, 1. / eta);
+            if wi.z() >= 0. {
+                return None;
+            }
+            wi
+        };
+
+        let masking = self.distribution.g2(&wo, &wi) / self.distribution.g1(&wo);
+
+        Some(ScatteredRay {
+            ray: ray.spawn(hit.point, frame.local_to_world(&wi)),
+            attenuation: Color::new(masking, masking, masking),
+        })
+    }
+
+    fn kind(&self) -> MaterialKind {
+        MaterialKind::RoughDielectric
+    }
+}
@@ -0,0 +1,68 @@
+//! Procedural noise functions.
+
+use crate::Point3;
+
+/// Hashes integer lattice coordinates into a value in range `0.0..1.0`.
+fn hash(x: i32, y: i32, z: i32, seed: u32) -> f32 {
+    let mut h = seed
+        .wrapping_add((x as u32).wrapping_mul(0x8da6_b343))
+        .wrapping_add((y as u32).wrapping_mul(0xd816_3841))
+        .wrapping_add((z as u32).wrapping_mul(0xcb1a_b31f));
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2c1b_3c6d);
+    h ^= h >> 12;
+    h = h.wrapping_mul(0x297a_2d39);
+    h ^= h >> 15;
+    (h >> 8) as f32 / (1u32 << 24) as f32
+}
+
+/// Smoothly interpolated lattice noise.
+#[derive(Debug, Copy, Clone)]
+pub struct ValueNoise {
+    seed: u32,
+}
+
+impl ValueNoise {
+    pub fn new(seed: u32) -> Self {
+        Self { seed }
+    }
+
+    /// Evaluates the noise at the specified point; values are in range `0.0..1.0`.
+    pub fn value(&self, p: &Point3) -> f32 {
+        let fx = p.x().floor();
+        let fy = p.y().floor();
+        let fz = p.z().floor();
+        let (ix, iy, iz) = (fx as i32, fy as i32, fz as i32);
+
+        // Quintic fade curve for C2 continuity.
+        let fade = |t: f32| t * t * t * (t * (t * 6. - 15.) + 10.);
+        let u = fade(p.x() - fx);
+        let v = fade(p.y() - fy);
+        let w = fade(p.z() - fz);
+
+        let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+        let corner = |dx: i32, dy: i32, dz: i32| hash(ix + dx, iy + dy, iz + dz, self.seed);
+
+        let x00 = lerp(corner(0, 0, 0), corner(1, 0, 0), u);
+        let x10 = lerp(corner(0, 1, 0), corner(1, 1, 0), u);
+        let x01 = lerp(corner(0, 0, 1), corner(1, 0, 1), u);
+        let x11 = lerp(corner(0, 1, 1), corner(1, 1, 1), u);
+        lerp(lerp(x00, x10, v), lerp(x01, x11, v), w)
+    }
+
+    /// Fractal Brownian motion, i.e. the sum of `octaves` noise layers
+    /// of doubling frequency and halving amplitude, normalized to `0.0..1.0`.
+    pub fn fbm(&self, p: &Point3, octaves: usize) -> f32 {
+        let mut sum = 0.;
+        let mut amplitude = 1.;
+        let mut total = 0.;
+        let mut p = *p;
+        for _ in 0..octaves {
+            sum += amplitude * self.value(&p);
+            total += amplitude;
+            amplitude *= 0.5;
+            p *= 2.;
+        }
+        sum / total
+    }
+}
@@ -1,5 +1,37 @@
+mod cone;
+mod constant_medium;
+mod csg;
+mod cuboid;
+mod cylinder;
+mod disk;
+mod heightfield;
+mod heterogeneous_medium;
 mod hittable;
+mod instance;
+mod moving_sphere;
+mod plane;
+mod quad;
+mod sdf_object;
+mod solid;
 mod sphere;
+mod torus;
+mod voxel_grid;
 
-pub use hittable::{HitRecord, Hittable, HittableList};
+pub use cone::Cone;
+pub use constant_medium::ConstantMedium;
+pub use csg::{Csg, Operation};
+pub use cuboid::Cuboid;
+pub use cylinder::Cylinder;
+pub use disk::Disk;
+pub use heightfield::Heightfield;
+pub use heterogeneous_medium::HeterogeneousMedium;
+pub use hittable::{HitRecord, Hittable, HittableList, HittablePtr};
+pub use instance::Instance;
+pub use moving_sphere::MovingSphere;
+pub use plane::Plane;
+pub use quad::Quad;
+pub use sdf_object::SdfObject;
+pub use solid::{Solid, Span};
 pub use sphere::Sphere;
+pub use torus::Torus;
+pub use voxel_grid::VoxelGrid;
@@ -0,0 +1,218 @@
+use crate::material::MaterialPtr;
+use crate::objects::{Disk, Solid, Span};
+use crate::primitives::{BoundingBox, Onb};
+use crate::{HitRecord, Hittable, Point3, Ray, Vec3};
+use std::f32::consts::PI;
+
+/// A capped cone or, with a nonzero top radius, a conical frustum.
+///
+/// On the mantle, `u` is the angle around the axis and `v` the height
+/// along it; the caps use the same coordinates as a [`Disk`].
+pub struct Cone {
+    base: Point3,
+    frame: Onb,
+    height: f32,
+    base_radius: f32,
+    top_radius: f32,
+    material: MaterialPtr,
+}
+
+/// The part of the surface that was hit.
+enum Surface {
+    Mantle,
+    Base,
+    Top,
+}
+
+impl Cone {
+    /// Creates a cone standing on the center of its base, pointing along `axis`.
+    pub fn new(
+        base: Point3,
+        axis: Vec3,
+        height: f32,
+        base_radius: f32,
+        top_radius: f32,
+        material: MaterialPtr,
+    ) -> Self {
+        Self {
+            base,
+            frame: Onb::from_w(&axis.as_unit_vector()),
+            height,
+            base_radius,
+            top_radius,
+            material,
+        }
+    }
+
+    /// The change of the radius per unit height.
+    fn slope(&self) -> f32 {
+        (self.top_radius - self.base_radius) / self.height
+    }
+
+    /// Determines all intersections of the ray's line with the cone, ordered by distance.
+    fn boundaries(&self, r: &Ray) -> Vec<(f32, Surface)> {
+        let origin = self.frame.world_to_local(&(r.origin - self.base));
+        let direction = self.frame.world_to_local(&r.direction);
+
+        let mut boundaries = self.hit_mantle(&origin, &direction);
+        boundaries.extend(
+            Self::hit_cap(&origin, &direction, 0., self.base_radius).map(|t| (t, Surface::Base)),
+        );
+        boundaries.extend(
+            Self::hit_cap(&origin, &direction, self.height, self.top_radius)
+                .map(|t| (t, Surface::Top)),
+        );
+        boundaries.sort_by(|a, b| a.0.total_cmp(&b.0));
+        boundaries
+    }
+
+    /// Determines the intersections with the mantle in the local frame.
+    fn hit_mantle(&self, origin: &Point3, direction: &Vec3) -> Vec<(f32, Surface)> {
+        let k = self.slope();
+        let radius = self.base_radius + k * origin.z();
+
+        let a = direction.x() * direction.x() + direction.y() * direction.y()
+            - k * k * direction.z() * direction.z();
+        let half_b =
+            origin.x() * direction.x() + origin.y() * direction.y() - k * radius * direction.z();
+        let c = origin.x() * origin.x() + origin.y() * origin.y() - radius * radius;
+
+        let roots = if a.abs() < 1e-8 {
+            // The ray is parallel to the mantle and crosses it at most once.
+            if half_b.abs() < 1e-8 {
+                return vec![];
+            }
+            vec![-c / (2. * half_b)]
+        } else {
+            let discriminant = half_b * half_b - a * c;
+            if discriminant < 0. {
+                return vec![];
+            }
+            let sqrt_d = discriminant.sqrt();
+            vec![(-half_b - sqrt_d) / a, (-half_b + sqrt_d) / a]
+        };
+
+        // Restricting the height also rejects the mirrored nappe of the cone.
+        roots
+            .into_iter()
+            .filter(|&t| (0. ..=self.height).contains(&(origin.z() + t * direction.z())))
+            .map(|t| (t, Surface::Mantle))
+            .collect()
+    }
+
+    /// Determines the intersection with a cap at height `z` in the local frame.
+    fn hit_cap(origin: &Point3, direction: &Vec3, z: f32, radius: f32) -> Option<f32> {
+        if radius <= 0. || direction.z().abs() < 1e-8 {
+            return None;
+        }
+        let t = (z - origin.z()) / direction.z();
+        let x = origin.x() + t * direction.x();
+        let y = origin.y() + t * direction.y();
+        if x * x + y * y > radius * radius {
+            return None;
+        }
+        Some(t)
+    }
+
+    fn record(&self, r: &Ray, t: f32, surface: &Surface) -> HitRecord {
+        let hit_point = r.at(t);
+        let local = self.frame.world_to_local(&(hit_point - self.base));
+        let (local_normal, (u, v)) = match surface {
+            Surface::Mantle => {
+                let radius = self.base_radius + self.slope() * local.z();
+                let phi = local.y().atan2(local.x()) + PI;
+                (
+                    Vec3::new(local.x(), local.y(), -self.slope() * radius),
+                    (phi / (2. * PI), local.z() / self.height),
+                )
+            }
+            Surface::Base => (Vec3::new(0., 0., -1.), Disk::uv(&local, self.base_radius)),
+            Surface::Top => (Vec3::new(0., 0., 1.), Disk::uv(&local, self.top_radius)),
+        };
+
+        let outward_normal = self.frame.local_to_world(&local_normal).as_unit_vector();
+        HitRecord::new_from_ray(r, t, hit_point, outward_normal, self.material.clone())
+            .with_uv(u, v)
+    }
+}
+
+impl Hittable for Cone {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        self.boundaries(r)
+            .iter()
+            .find(|(t, _)| *t >= t_min && *t <= t_max)
+            .map(|(t, surface)| self.record(r, *t, surface))
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        let top = self.base + self.frame.w * self.height;
+        let base_extent = Disk::extent(&self.frame.w, self.base_radius);
+        let top_extent = Disk::extent(&self.frame.w, self.top_radius);
+        BoundingBox::new(self.base - base_extent, self.base + base_extent)
+            .union(&BoundingBox::new(top - top_extent, top + top_extent))
+    }
+}
+
+impl Solid for Cone {
+    fn spans(&self, r: &Ray) -> Vec<Span> {
+        // The cone is convex, so the ray enters at the first and leaves at the last
+        // boundary; rays passing through a rim may report the same point twice.
+        let boundaries = self.boundaries(r);
+        match (boundaries.first(), boundaries.last()) {
+            (Some((t0, enter)), Some((t1, exit))) if boundaries.len() > 1 => vec![Span {
+                enter: self.record(r, *t0, enter),
+                exit: self.record(r, *t1, exit),
+            }],
+            _ => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use crate::material::Lambertian;
+    use std::sync::Arc;
+
+    fn unit_cone() -> Cone {
+        let material: MaterialPtr = Arc::new(Box::new(Lambertian::new(Vec3::default(), 1.)));
+        Cone::new(
+            Point3::default(),
+            Vec3::new(0., 0., 1.),
+            1.,
+            1.,
+            0.,
+            material,
+        )
+    }
+
+    #[test]
+    pub fn rays_parallel_to_the_mantle_cross_it_once() {
+        // The direction is parallel to the mantle line from (-1, 0, 0) to the apex.
+        let cone = unit_cone();
+        let ray = Ray::new(Point3::new(-1., 0., -1.), Vec3::new(1., 0., 1.));
+        let diagonal = std::f32::consts::SQRT_2;
+
+        let base = cone.hit(&ray, 0.001, f32::INFINITY).expect("hit expected");
+        assert!((base.t - diagonal).abs() < 1e-5);
+        assert!((base.normal - Vec3::new(0., 0., -1.)).len() < 1e-5);
+
+        let mantle = cone.hit(&ray, 2., f32::INFINITY).expect("hit expected");
+        assert!((mantle.t - 1.5 * diagonal).abs() < 1e-4);
+        assert!((mantle.point - Point3::new(0.5, 0., 0.5)).len() < 1e-4);
+        // The ray leaves through the mantle, so the normal is flipped to face it.
+        let outward = Vec3::new(1., 0., 1.).as_unit_vector();
+        assert!(!mantle.is_front_facing);
+        assert!((mantle.normal + outward).len() < 1e-4);
+        assert!((mantle.v - 0.5).abs() < 1e-4);
+
+        assert_eq!(cone.spans(&ray).len(), 1);
+    }
+
+    #[test]
+    pub fn cone_bounds_work() {
+        let bounds = unit_cone().bounding_box();
+        assert!((bounds.min - Point3::new(-1., -1., 0.)).len() < 1e-5);
+        assert!((bounds.max - Point3::new(1., 1., 1.)).len() < 1e-5);
+    }
+}
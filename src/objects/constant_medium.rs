@@ -0,0 +1,121 @@
+use crate::primitives::BoundingBox;
+use crate::{HitRecord, Hittable, Material, Random, Ray};
+use std::sync::Arc;
+
+/// A participating medium of constant density, e.g. smoke or fog, filling a boundary.
+///
+/// The boundary needs to be convex: once a ray leaves it, it is assumed not to enter again.
+pub struct ConstantMedium {
+    boundary: Box<dyn Hittable>,
+    neg_inv_density: f32,
+    /// The phase function, e.g. [`Isotropic`](crate::material::Isotropic).
+    phase_function: Arc<Box<dyn Material>>,
+}
+
+impl ConstantMedium {
+    pub fn new(
+        boundary: Box<dyn Hittable>,
+        density: f32,
+        phase_function: Arc<Box<dyn Material>>,
+    ) -> Self {
+        Self {
+            boundary,
+            neg_inv_density: -1. / density,
+            phase_function,
+        }
+    }
+}
+
+impl Hittable for ConstantMedium {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        self.sample_hit(r, t_min, t_max, &Random::default())
+    }
+
+    fn sample_hit(&self, r: &Ray, t_min: f32, t_max: f32, rng: &Random) -> Option<HitRecord> {
+        // Find where the ray enters and leaves the boundary, irrespective of the ray's extent.
+        let entry = self.boundary.hit(r, f32::NEG_INFINITY, f32::INFINITY)?;
+        let exit = self.boundary.hit(r, entry.t + 0.0001, f32::INFINITY)?;
+
+        let t_enter = entry.t.max(t_min).max(0.);
+        let t_exit = exit.t.min(t_max);
+        if t_enter >= t_exit {
+            return None;
+        }
+
+        // Sample the free-flight distance; since the ray direction is normalized,
+        // the ray parameter is the distance travelled.
+        let distance_inside_boundary = t_exit - t_enter;
+        let hit_distance = self.neg_inv_density * (1. - rng.sample()).ln();
+        if hit_distance > distance_inside_boundary {
+            return None;
+        }
+
+        let t = t_enter + hit_distance;
+        Some(HitRecord::new_from_ray(
+            r,
+            t,
+            r.at(t),
+            // The normal is arbitrary, phase functions don't use it.
+            -r.direction,
+            self.phase_function.clone(),
+        ))
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        self.boundary.bounding_box()
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use crate::material::Isotropic;
+    use crate::objects::Sphere;
+    use crate::sampler::Halton;
+    use crate::{Color, Point3, Vec3};
+
+    fn fog(density: f32) -> ConstantMedium {
+        let phase_function: Arc<Box<dyn Material>> =
+            Arc::new(Box::new(Isotropic::new(Color::new(1., 1., 1.))));
+        ConstantMedium::new(
+            Box::new(Sphere::new(
+                Point3::new(0., 0., 0.),
+                1.,
+                phase_function.clone(),
+            )),
+            density,
+            phase_function,
+        )
+    }
+
+    #[test]
+    pub fn free_flights_follow_the_sampler() {
+        let medium = fog(2.);
+        let ray = Ray::new(Point3::new(-2., 0., 0.), Vec3::new(1., 0., 0.));
+        let distance = || {
+            let rng = Random::for_sample(Arc::new(Halton), (3, 5), 7);
+            medium
+                .sample_hit(&ray, 0., f32::INFINITY, &rng)
+                .map(|hit| hit.t)
+        };
+        assert!(distance().is_some());
+        assert_eq!(distance(), distance());
+    }
+
+    #[test]
+    pub fn free_flights_follow_beer_lambert() {
+        let medium = fog(0.5);
+        let ray = Ray::new(Point3::new(-2., 0., 0.), Vec3::new(1., 0., 0.));
+        let passing = |t_max: f32| {
+            let n = 20_000;
+            (0..n)
+                .filter(|_| medium.hit(&ray, 0., t_max).is_none())
+                .count() as f32
+                / n as f32
+        };
+
+        // The ray crosses 2 units of the unit sphere, or 1 unit when stopped at its center.
+        assert!((passing(f32::INFINITY) - (-0.5f32 * 2.).exp()).abs() < 0.02);
+        assert!((passing(2.) - (-0.5f32).exp()).abs() < 0.02);
+    }
+}
@@ -0,0 +1,217 @@
+use crate::objects::{Solid, Span};
+use crate::primitives::BoundingBox;
+use crate::{HitRecord, Hittable, Point3, Ray};
+
+/// The boolean operation combining the two solids of a [`Csg`] node.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Operation {
+    /// Everything inside either solid.
+    Union,
+    /// Everything inside both solids.
+    Intersection,
+    /// Everything inside the first solid but not the second.
+    Difference,
+}
+
+impl Operation {
+    fn contains(&self, inside_left: bool, inside_right: bool) -> bool {
+        match self {
+            Operation::Union => inside_left || inside_right,
+            Operation::Intersection => inside_left && inside_right,
+            Operation::Difference => inside_left && !inside_right,
+        }
+    }
+}
+
+/// Combines two solids using constructive solid geometry.
+///
+/// Since the result is a solid again, nodes can be nested to build more complex shapes.
+pub struct Csg {
+    left: Box<dyn Solid>,
+    right: Box<dyn Solid>,
+    operation: Operation,
+    bounds: BoundingBox,
+}
+
+impl Csg {
+    pub fn new(left: Box<dyn Solid>, operation: Operation, right: Box<dyn Solid>) -> Self {
+        let (a, b) = (left.bounding_box(), right.bounding_box());
+        let bounds = match operation {
+            Operation::Union => a.union(&b),
+            Operation::Intersection => BoundingBox::new(
+                Point3::new(
+                    a.min.x().max(b.min.x()),
+                    a.min.y().max(b.min.y()),
+                    a.min.z().max(b.min.z()),
+                ),
+                Point3::new(
+                    a.max.x().min(b.max.x()),
+                    a.max.y().min(b.max.y()),
+                    a.max.z().min(b.max.z()),
+                ),
+            ),
+            Operation::Difference => a,
+        };
+        Self {
+            left,
+            right,
+            operation,
+            bounds,
+        }
+    }
+
+    pub fn union(left: Box<dyn Solid>, right: Box<dyn Solid>) -> Self {
+        Self::new(left, Operation::Union, right)
+    }
+
+    pub fn intersection(left: Box<dyn Solid>, right: Box<dyn Solid>) -> Self {
+        Self::new(left, Operation::Intersection, right)
+    }
+
+    pub fn difference(left: Box<dyn Solid>, right: Box<dyn Solid>) -> Self {
+        Self::new(left, Operation::Difference, right)
+    }
+}
+
+/// A surface along the ray at which one of the operands is entered or left.
+struct Boundary {
+    record: HitRecord,
+    is_left: bool,
+    is_entry: bool,
+}
+
+impl Hittable for Csg {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        self.bounds.hit(r, t_min, t_max)?;
+        self.spans(r)
+            .into_iter()
+            .flat_map(|span| [span.enter, span.exit])
+            .find(|record| record.t >= t_min && record.t <= t_max)
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        self.bounds
+    }
+}
+
+impl Solid for Csg {
+    fn spans(&self, r: &Ray) -> Vec<Span> {
+        let boundaries = |spans: Vec<Span>, is_left: bool| {
+            spans.into_iter().flat_map(move |span| {
+                [
+                    Boundary {
+                        record: span.enter,
+                        is_left,
+                        is_entry: true,
+                    },
+                    Boundary {
+                        record: span.exit,
+                        is_left,
+                        is_entry: false,
+                    },
+                ]
+            })
+        };
+
+        let mut events: Vec<Boundary> = boundaries(self.left.spans(r), true)
+            .chain(boundaries(self.right.spans(r), false))
+            .collect();
+        if events.is_empty() {
+            return vec![];
+        }
+        events.sort_by(|a, b| a.record.t.total_cmp(&b.record.t));
+
+        // Sweep along the ray, tracking which operands contain the current point.
+        let (mut inside_left, mut inside_right) = (false, false);
+        let mut enter: Option<HitRecord> = None;
+        let mut spans = Vec::new();
+        for event in events {
+            let was_inside = self.operation.contains(inside_left, inside_right);
+            if event.is_left {
+                inside_left = event.is_entry;
+            } else {
+                inside_right = event.is_entry;
+            }
+            let is_inside = self.operation.contains(inside_left, inside_right);
+
+            let mut record = event.record;
+            if self.operation == Operation::Difference && !event.is_left {
+                // The surface of the subtracted solid faces the other way.
+                // The normal stays pointing against the ray; only the side flips.
+                record.is_front_facing = !record.is_front_facing;
+            }
+
+            match (was_inside, is_inside) {
+                (false, true) => enter = Some(record),
+                (true, false) => {
+                    if let Some(enter) = enter.take() {
+                        spans.push(Span {
+                            enter,
+                            exit: record,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+        spans
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use crate::material::{Lambertian, MaterialPtr};
+    use crate::objects::Sphere;
+    use crate::Vec3;
+    use std::sync::Arc;
+
+    fn sphere(x: f32, radius: f32) -> Box<dyn Solid> {
+        let material: MaterialPtr = Arc::new(Box::new(Lambertian::new(Vec3::default(), 1.)));
+        Box::new(Sphere::new(Point3::new(x, 0., 0.), radius, material))
+    }
+
+    fn ray() -> Ray {
+        Ray::new(Point3::new(-10., 0., 0.), Vec3::new(1., 0., 0.))
+    }
+
+    fn distances(csg: &Csg) -> Vec<(f32, f32)> {
+        csg.spans(&ray())
+            .iter()
+            .map(|span| (span.enter.t, span.exit.t))
+            .collect()
+    }
+
+    #[test]
+    pub fn union_works() {
+        let csg = Csg::union(sphere(-1., 1.5), sphere(1., 1.5));
+        assert_eq!(distances(&csg), vec![(7.5, 12.5)]);
+
+        let csg = Csg::union(sphere(-2., 1.), sphere(2., 1.));
+        assert_eq!(distances(&csg), vec![(7., 9.), (11., 13.)]);
+    }
+
+    #[test]
+    pub fn intersection_works() {
+        let csg = Csg::intersection(sphere(-1., 1.5), sphere(1., 1.5));
+        assert_eq!(distances(&csg), vec![(9.5, 10.5)]);
+    }
+
+    #[test]
+    pub fn difference_works() {
+        let csg = Csg::difference(sphere(0., 2.), sphere(0., 1.));
+        assert_eq!(distances(&csg), vec![(8., 9.), (11., 12.)]);
+
+        // Leaving the hole, the ray enters the remaining shell from its inner surface.
+        let spans = csg.spans(&ray());
+        assert!(spans[1].enter.is_front_facing);
+        assert_eq!(spans[1].enter.normal, Vec3::new(-1., 0., 0.));
+    }
+
+    #[test]
+    pub fn hit_respects_range() {
+        let csg = Csg::difference(sphere(0., 2.), sphere(0., 1.));
+        let hit = csg.hit(&ray(), 9.5, f32::INFINITY).unwrap();
+        assert_eq!(hit.t, 11.);
+    }
+}
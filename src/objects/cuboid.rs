@@ -0,0 +1,197 @@
+use crate::material::MaterialPtr;
+use crate::objects::{Solid, Span};
+use crate::primitives::{BoundingBox, Quaternion};
+use crate::{HitRecord, Hittable, Point3, Ray, Vec3};
+
+/// A box, optionally rotated about its center.
+///
+/// The surface coordinates of each face run from `0.0` to `1.0`
+/// along the two box axes spanning it.
+pub struct Cuboid {
+    center: Point3,
+    half_extents: Vec3,
+    /// The (unit length) box axes in world space.
+    axes: [Vec3; 3],
+    material: MaterialPtr,
+}
+
+impl Cuboid {
+    /// Creates an axis-aligned box between two opposite corners.
+    pub fn new(min: Point3, max: Point3, material: MaterialPtr) -> Self {
+        Self::oriented(
+            (min + max).half(),
+            (max - min).half(),
+            Quaternion::IDENTITY,
+            material,
+        )
+    }
+
+    /// Creates a box around a center, rotated by the specified rotation.
+    pub fn oriented(
+        center: Point3,
+        half_extents: Vec3,
+        rotation: Quaternion,
+        material: MaterialPtr,
+    ) -> Self {
+        let matrix = rotation.to_matrix();
+        Self {
+            center,
+            half_extents,
+            axes: [
+                matrix.transform_vector(&Vec3::new(1., 0., 0.)),
+                matrix.transform_vector(&Vec3::new(0., 1., 0.)),
+                matrix.transform_vector(&Vec3::new(0., 0., 1.)),
+            ],
+            material,
+        }
+    }
+
+    fn to_local(&self, v: &Vec3) -> Vec3 {
+        Vec3::new(
+            v.dot(&self.axes[0]),
+            v.dot(&self.axes[1]),
+            v.dot(&self.axes[2]),
+        )
+    }
+}
+
+impl Cuboid {
+    /// Intersects the ray's line with the box using the slab method.
+    ///
+    /// Returns the entry and exit distances together with the axes of the faces hit.
+    fn slabs(&self, r: &Ray) -> Option<((f32, usize), (f32, usize))> {
+        let origin = self.to_local(&(r.origin - self.center));
+        let direction = self.to_local(&r.direction);
+
+        let (mut t_near, mut near_axis) = (f32::NEG_INFINITY, 0);
+        let (mut t_far, mut far_axis) = (f32::INFINITY, 0);
+        for axis in 0..3 {
+            let inv_d = 1. / direction.e[axis];
+            let h = self.half_extents.e[axis];
+            let mut t0 = (-h - origin.e[axis]) * inv_d;
+            let mut t1 = (h - origin.e[axis]) * inv_d;
+            if inv_d < 0. {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            if t0 > t_near {
+                t_near = t0;
+                near_axis = axis;
+            }
+            if t1 < t_far {
+                t_far = t1;
+                far_axis = axis;
+            }
+        }
+
+        if t_near > t_far {
+            return None;
+        }
+        Some(((t_near, near_axis), (t_far, far_axis)))
+    }
+
+    fn record(&self, r: &Ray, t: f32, axis: usize) -> HitRecord {
+        let hit_point = r.at(t);
+        let local = self.to_local(&(hit_point - self.center));
+        let sign = 1f32.copysign(local.e[axis]);
+        let outward_normal = self.axes[axis] * sign;
+
+        let (a, b) = ((axis + 1) % 3, (axis + 2) % 3);
+        let u = 0.5 * (local.e[a] / self.half_extents.e[a] + 1.);
+        let v = 0.5 * (local.e[b] / self.half_extents.e[b] + 1.);
+
+        HitRecord::new_from_ray(r, t, hit_point, outward_normal, self.material.clone())
+            .with_uv(u, v)
+    }
+}
+
+impl Hittable for Cuboid {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        let ((t_near, near_axis), (t_far, far_axis)) = self.slabs(r)?;
+        if t_near >= t_min && t_near <= t_max {
+            Some(self.record(r, t_near, near_axis))
+        } else if t_far >= t_min && t_far <= t_max {
+            Some(self.record(r, t_far, far_axis))
+        } else {
+            None
+        }
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        let mut extent = Vec3::default();
+        for (axis, h) in self.axes.iter().zip(self.half_extents.e.iter()) {
+            extent += Vec3::new(axis.x().abs(), axis.y().abs(), axis.z().abs()) * *h;
+        }
+        BoundingBox::new(self.center - extent, self.center + extent)
+    }
+}
+
+impl Solid for Cuboid {
+    fn spans(&self, r: &Ray) -> Vec<Span> {
+        match self.slabs(r) {
+            Some(((t_near, near_axis), (t_far, far_axis))) => vec![Span {
+                enter: self.record(r, t_near, near_axis),
+                exit: self.record(r, t_far, far_axis),
+            }],
+            None => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::Degrees;
+    use std::sync::Arc;
+
+    fn material() -> MaterialPtr {
+        Arc::new(Box::new(Lambertian::new(Vec3::default(), 1.)))
+    }
+
+    #[test]
+    pub fn face_normals_point_outwards() {
+        let cuboid = Cuboid::new(
+            Point3::new(-1., -2., -3.),
+            Point3::new(1., 2., 3.),
+            material(),
+        );
+        for axis in 0..3 {
+            for sign in [-1f32, 1.] {
+                let mut direction = Vec3::default();
+                direction.e[axis] = -sign;
+                let ray = Ray::new(direction * -10., direction);
+                let hit = cuboid
+                    .hit(&ray, 0.001, f32::INFINITY)
+                    .expect("hit expected");
+                assert!((hit.normal + direction).len() < 1e-5, "{:?}", hit.normal);
+                assert!(hit.is_front_facing);
+                assert!((hit.u - 0.5).abs() < 1e-5 && (hit.v - 0.5).abs() < 1e-5);
+            }
+        }
+
+        // From the inside, the far face is hit and the normal faces the ray.
+        let ray = Ray::new(Point3::default(), Vec3::new(0., 0., 1.));
+        let hit = cuboid.hit(&ray, 0.001, f32::INFINITY).unwrap();
+        assert!((hit.t - 3.).abs() < 1e-5);
+        assert!(!hit.is_front_facing);
+        assert!((hit.normal - Vec3::new(0., 0., -1.)).len() < 1e-5);
+    }
+
+    #[test]
+    pub fn oriented_bounds_work() {
+        let rotation = Quaternion::from_axis_angle(Vec3::new(0., 1., 0.), Degrees(45.));
+        let cuboid = Cuboid::oriented(
+            Point3::default(),
+            Vec3::new(1., 1., 1.),
+            rotation,
+            material(),
+        );
+        let bounds = cuboid.bounding_box();
+        let diagonal = std::f32::consts::SQRT_2;
+        assert!((bounds.max - Point3::new(diagonal, 1., diagonal)).len() < 1e-5);
+
+        let ray = Ray::new(Point3::new(-10., 0., 0.), Vec3::new(1., 0., 0.));
+        let hit = cuboid.hit(&ray, 0.001, f32::INFINITY).unwrap();
+        assert!((hit.t - (10. - diagonal)).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,70 @@
+use crate::material::MaterialPtr;
+use crate::objects::{Cone, Solid, Span};
+use crate::primitives::BoundingBox;
+use crate::{HitRecord, Hittable, Point3, Ray, Vec3};
+
+/// A capped cylinder, i.e. a cone with equal base and top radii.
+pub struct Cylinder {
+    cone: Cone,
+}
+
+impl Cylinder {
+    /// Creates a cylinder standing on the center of its base, pointing along `axis`.
+    pub fn new(base: Point3, axis: Vec3, height: f32, radius: f32, material: MaterialPtr) -> Self {
+        Self {
+            cone: Cone::new(base, axis, height, radius, radius, material),
+        }
+    }
+}
+
+impl Hittable for Cylinder {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        self.cone.hit(r, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        self.cone.bounding_box()
+    }
+}
+
+impl Solid for Cylinder {
+    fn spans(&self, r: &Ray) -> Vec<Span> {
+        self.cone.spans(r)
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use crate::material::Lambertian;
+    use std::sync::Arc;
+
+    #[test]
+    pub fn cylinder_hit_works() {
+        let material: MaterialPtr = Arc::new(Box::new(Lambertian::new(Vec3::default(), 1.)));
+        let cylinder = Cylinder::new(Point3::default(), Vec3::new(0., 1., 0.), 2., 1., material);
+
+        let side = Ray::new(Point3::new(-5., 1., 0.), Vec3::new(1., 0., 0.));
+        let hit = cylinder
+            .hit(&side, 0.001, f32::INFINITY)
+            .expect("hit expected");
+        assert!((hit.t - 4.).abs() < 1e-5);
+        assert!((hit.normal - Vec3::new(-1., 0., 0.)).len() < 1e-5);
+        assert!((hit.v - 0.5).abs() < 1e-5);
+
+        let top = Ray::new(Point3::new(0.5, 5., 0.), Vec3::new(0., -1., 0.));
+        let hit = cylinder
+            .hit(&top, 0.001, f32::INFINITY)
+            .expect("hit expected");
+        assert!((hit.t - 3.).abs() < 1e-5);
+        assert!((hit.normal - Vec3::new(0., 1., 0.)).len() < 1e-5);
+        assert!((hit.v - 0.5).abs() < 1e-5);
+
+        let above = Ray::new(Point3::new(-5., 2.5, 0.), Vec3::new(1., 0., 0.));
+        assert!(cylinder.hit(&above, 0.001, f32::INFINITY).is_none());
+
+        let bounds = cylinder.bounding_box();
+        assert!((bounds.min - Point3::new(-1., 0., -1.)).len() < 1e-5);
+        assert!((bounds.max - Point3::new(1., 2., 1.)).len() < 1e-5);
+    }
+}
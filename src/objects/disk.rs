@@ -0,0 +1,105 @@
+use crate::material::MaterialPtr;
+use crate::primitives::{BoundingBox, Onb};
+use crate::{HitRecord, Hittable, Point3, Ray, Vec3};
+use std::f32::consts::PI;
+
+/// A flat, circular disk.
+///
+/// `u` is the angle around the center and `v` the distance from it,
+/// both normalized to `0.0..=1.0`.
+pub struct Disk {
+    center: Point3,
+    radius: f32,
+    frame: Onb,
+    material: MaterialPtr,
+}
+
+impl Disk {
+    pub fn new(center: Point3, normal: Vec3, radius: f32, material: MaterialPtr) -> Self {
+        Self {
+            center,
+            radius,
+            frame: Onb::from_w(&normal.as_unit_vector()),
+            material,
+        }
+    }
+
+    /// Determines the half extents of a circle with the specified (unit) normal along each axis.
+    pub fn extent(normal: &Vec3, radius: f32) -> Vec3 {
+        let axis = |n: f32| radius * (1. - n * n).max(0.).sqrt();
+        Vec3::new(axis(normal.x()), axis(normal.y()), axis(normal.z()))
+    }
+
+    /// Determines the surface coordinates of a point in the disk's local frame.
+    pub fn uv(local: &Point3, radius: f32) -> (f32, f32) {
+        let phi = local.y().atan2(local.x()) + PI;
+        let distance = (local.x() * local.x() + local.y() * local.y()).sqrt();
+        (phi / (2. * PI), distance / radius)
+    }
+}
+
+impl Hittable for Disk {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        let denominator = self.frame.w.dot(&r.direction);
+        if denominator.abs() < 1e-8 {
+            return None;
+        }
+
+        let t = self.frame.w.dot(&(self.center - r.origin)) / denominator;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        let hit_point = r.at(t);
+        let local = self.frame.world_to_local(&(hit_point - self.center));
+        if local.len_squared() > self.radius * self.radius {
+            return None;
+        }
+
+        let (u, v) = Self::uv(&local, self.radius);
+        Some(
+            HitRecord::new_from_ray(r, t, hit_point, self.frame.w, self.material.clone())
+                .with_uv(u, v),
+        )
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        let padding = Vec3::new(1e-4, 1e-4, 1e-4);
+        let extent = Self::extent(&self.frame.w, self.radius) + padding;
+        BoundingBox::new(self.center - extent, self.center + extent)
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use crate::material::Lambertian;
+    use std::sync::Arc;
+
+    #[test]
+    pub fn disk_hit_works() {
+        let material: MaterialPtr = Arc::new(Box::new(Lambertian::new(Vec3::default(), 1.)));
+        let disk = Disk::new(Point3::default(), Vec3::new(0., 1., 0.), 2., material);
+
+        let ray = Ray::new(Point3::new(1., 5., 0.), Vec3::new(0., -1., 0.));
+        let hit = disk.hit(&ray, 0.001, f32::INFINITY).expect("hit expected");
+        assert!((hit.t - 5.).abs() < 1e-5);
+        assert!((hit.normal - Vec3::new(0., 1., 0.)).len() < 1e-5);
+        assert!((hit.v - 0.5).abs() < 1e-5);
+        assert!((0. ..=1.).contains(&hit.u));
+
+        let outside = Ray::new(Point3::new(1.5, 5., 1.5), Vec3::new(0., -1., 0.));
+        assert!(disk.hit(&outside, 0.001, f32::INFINITY).is_none());
+
+        let bounds = disk.bounding_box();
+        assert!((bounds.max - Point3::new(2., 0., 2.)).len() < 1e-3);
+    }
+
+    #[test]
+    pub fn tilted_extent_works() {
+        let normal = Vec3::new(1., 1., 0.).as_unit_vector();
+        let extent = Disk::extent(&normal, 2.);
+        let diagonal = 2. * std::f32::consts::FRAC_1_SQRT_2;
+        assert!((extent - Vec3::new(diagonal, diagonal, 2.)).len() < 1e-5);
+    }
+}
@@ -0,0 +1,339 @@
+use crate::material::MaterialPtr;
+use crate::pgm;
+use crate::primitives::BoundingBox;
+use crate::{HitRecord, Hittable, Point3, Ray, Vec3};
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+/// A terrain surface given by heights sampled on a regular grid.
+///
+/// Each grid cell is split into two triangles. Rays walk the cells they
+/// cross front to back, so the cost depends on the number of cells along
+/// the ray rather than on the total number of cells. Normals are
+/// interpolated from per-sample normals, and the surface coordinates span
+/// the whole field.
+pub struct Heightfield {
+    nx: usize,
+    nz: usize,
+    /// The world space heights of the samples, with X varying fastest.
+    heights: Vec<f32>,
+    normals: Vec<Vec3>,
+    /// The highest sample of each cell, used to skip cells the ray passes above.
+    cell_max: Vec<f32>,
+    bounds: BoundingBox,
+    material: MaterialPtr,
+}
+
+impl Heightfield {
+    /// Creates a heightfield from `nx` × `nz` samples in range `0.0..=1.0`, with X varying fastest.
+    ///
+    /// The samples are stretched to fill `bounds`, with `0.0` mapping to the
+    /// bottom and `1.0` to the top of the box.
+    pub fn new(
+        nx: usize,
+        nz: usize,
+        samples: Vec<f32>,
+        bounds: BoundingBox,
+        material: MaterialPtr,
+    ) -> Self {
+        assert!(nx >= 2 && nz >= 2, "at least 2×2 samples are required");
+        assert_eq!(samples.len(), nx * nz, "sample count mismatch");
+
+        let scale = bounds.max.y() - bounds.min.y();
+        let heights: Vec<f32> = samples.iter().map(|s| bounds.min.y() + s * scale).collect();
+
+        let mut field = Self {
+            nx,
+            nz,
+            heights,
+            normals: Vec::new(),
+            cell_max: Vec::new(),
+            bounds,
+            material,
+        };
+
+        field.normals = (0..nz)
+            .flat_map(|z| (0..nx).map(move |x| (x, z)))
+            .map(|(x, z)| field.sample_normal(x, z))
+            .collect();
+        field.cell_max = (0..nz - 1)
+            .flat_map(|z| (0..nx - 1).map(move |x| (x, z)))
+            .map(|(x, z)| {
+                field
+                    .height(x, z)
+                    .max(field.height(x + 1, z))
+                    .max(field.height(x, z + 1))
+                    .max(field.height(x + 1, z + 1))
+            })
+            .collect();
+        field
+    }
+
+    /// Generates a heightfield by evaluating `height` at normalized
+    /// coordinates `(x, z)` in range `0.0..=1.0`.
+    pub fn procedural(
+        nx: usize,
+        nz: usize,
+        bounds: BoundingBox,
+        material: MaterialPtr,
+        height: impl Fn(f32, f32) -> f32,
+    ) -> Self {
+        let mut samples = Vec::with_capacity(nx * nz);
+        for z in 0..nz {
+            for x in 0..nx {
+                samples.push(height(
+                    x as f32 / (nx - 1) as f32,
+                    z as f32 / (nz - 1) as f32,
+                ));
+            }
+        }
+        Self::new(nx, nz, samples, bounds, material)
+    }
+
+    /// Loads a heightfield from a grayscale PGM image.
+    ///
+    /// Image rows run along the X axis; the first row is at the minimum Z coordinate.
+    pub fn load_pgm(
+        path: impl AsRef<Path>,
+        bounds: BoundingBox,
+        material: MaterialPtr,
+    ) -> std::io::Result<Self> {
+        let image = pgm::load(path)?;
+        if image.width < 2 || image.height < 2 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "at least 2×2 samples are required",
+            ));
+        }
+        Ok(Self::new(
+            image.width,
+            image.height,
+            image.values,
+            bounds,
+            material,
+        ))
+    }
+
+    /// The size of a grid cell along the X and Z axes.
+    fn cell_size(&self) -> (f32, f32) {
+        (
+            (self.bounds.max.x() - self.bounds.min.x()) / (self.nx - 1) as f32,
+            (self.bounds.max.z() - self.bounds.min.z()) / (self.nz - 1) as f32,
+        )
+    }
+
+    fn height(&self, x: usize, z: usize) -> f32 {
+        self.heights[z * self.nx + x]
+    }
+
+    fn vertex(&self, x: usize, z: usize) -> Point3 {
+        let (dx, dz) = self.cell_size();
+        Point3::new(
+            self.bounds.min.x() + x as f32 * dx,
+            self.height(x, z),
+            self.bounds.min.z() + z as f32 * dz,
+        )
+    }
+
+    /// Estimates the normal at a sample using central differences.
+    fn sample_normal(&self, x: usize, z: usize) -> Vec3 {
+        let (dx, dz) = self.cell_size();
+        let (x0, x1) = (x.saturating_sub(1), (x + 1).min(self.nx - 1));
+        let (z0, z1) = (z.saturating_sub(1), (z + 1).min(self.nz - 1));
+        let slope_x = (self.height(x1, z) - self.height(x0, z)) / ((x1 - x0) as f32 * dx);
+        let slope_z = (self.height(x, z1) - self.height(x, z0)) / ((z1 - z0) as f32 * dz);
+        Vec3::new(-slope_x, 1., -slope_z).as_unit_vector()
+    }
+
+    /// Intersects the ray with the two triangles of a cell.
+    fn hit_cell(&self, r: &Ray, x: usize, z: usize, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        let corners = [(x, z), (x + 1, z), (x + 1, z + 1), (x, z + 1)];
+        let mut closest: Option<(f32, Vec3)> = None;
+        for triangle in [[0, 1, 2], [0, 2, 3]] {
+            let [a, b, c] = triangle.map(|i| corners[i]);
+            let t_limit = closest.map_or(t_max, |(t, _)| t);
+            let (p0, p1, p2) = (
+                self.vertex(a.0, a.1),
+                self.vertex(b.0, b.1),
+                self.vertex(c.0, c.1),
+            );
+            if let Some((t, b1, b2)) = hit_triangle(r, &p0, &p1, &p2, t_min, t_limit) {
+                let normal = self.normals[a.1 * self.nx + a.0] * (1. - b1 - b2)
+                    + self.normals[b.1 * self.nx + b.0] * b1
+                    + self.normals[c.1 * self.nx + c.0] * b2;
+                closest = Some((t, normal.as_unit_vector()));
+            }
+        }
+
+        let (t, normal) = closest?;
+        let hit_point = r.at(t);
+        let u = (hit_point.x() - self.bounds.min.x()) / (self.bounds.max.x() - self.bounds.min.x());
+        let v = (hit_point.z() - self.bounds.min.z()) / (self.bounds.max.z() - self.bounds.min.z());
+        Some(HitRecord::new_from_ray(r, t, hit_point, normal, self.material.clone()).with_uv(u, v))
+    }
+}
+
+/// Intersects the ray with a triangle using the Möller–Trumbore algorithm.
+///
+/// Returns the distance and the barycentric coordinates of the second and third vertex.
+fn hit_triangle(
+    r: &Ray,
+    p0: &Point3,
+    p1: &Point3,
+    p2: &Point3,
+    t_min: f32,
+    t_max: f32,
+) -> Option<(f32, f32, f32)> {
+    let edge1 = *p1 - *p0;
+    let edge2 = *p2 - *p0;
+    let p = r.direction.cross(&edge2);
+    let determinant = edge1.dot(&p);
+    if determinant.abs() < 1e-12 {
+        return None;
+    }
+
+    let inv_determinant = 1. / determinant;
+    let s = r.origin - *p0;
+    let b1 = s.dot(&p) * inv_determinant;
+    if !(0. ..=1.).contains(&b1) {
+        return None;
+    }
+
+    let q = s.cross(&edge1);
+    let b2 = r.direction.dot(&q) * inv_determinant;
+    if b2 < 0. || b1 + b2 > 1. {
+        return None;
+    }
+
+    let t = edge2.dot(&q) * inv_determinant;
+    if t < t_min || t > t_max {
+        return None;
+    }
+    Some((t, b1, b2))
+}
+
+impl Hittable for Heightfield {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        let (t_start, t_end) = self.bounds.hit(r, t_min, t_max)?;
+        let (dx, dz) = self.cell_size();
+
+        // Find the cell the ray starts in.
+        let start = r.at(t_start);
+        let cell = |p: f32, min: f32, size: f32, n: usize| {
+            (((p - min) / size).floor().max(0.) as usize).min(n - 2)
+        };
+        let mut x = cell(start.x(), self.bounds.min.x(), dx, self.nx);
+        let mut z = cell(start.z(), self.bounds.min.z(), dz, self.nz);
+
+        // Walk the cells using a 2D digital differential analyzer.
+        let axis = |origin: f32, direction: f32, min: f32, size: f32, index: usize| {
+            if direction.abs() < 1e-12 {
+                return (0, f32::INFINITY, f32::INFINITY);
+            }
+            let step = if direction > 0. { 1 } else { -1 };
+            let boundary = min + (index as f32 + if direction > 0. { 1. } else { 0. }) * size;
+            (
+                step,
+                (boundary - origin) / direction,
+                size / direction.abs(),
+            )
+        };
+        let (step_x, mut t_next_x, t_delta_x) =
+            axis(r.origin.x(), r.direction.x(), self.bounds.min.x(), dx, x);
+        let (step_z, mut t_next_z, t_delta_z) =
+            axis(r.origin.z(), r.direction.z(), self.bounds.min.z(), dz, z);
+
+        let mut t_enter = t_start;
+        loop {
+            let t_exit = t_next_x.min(t_next_z).min(t_end);
+
+            // Skip cells the ray passes entirely above.
+            let lowest = r.at(t_enter).y().min(r.at(t_exit).y());
+            if lowest <= self.cell_max[z * (self.nx - 1) + x] {
+                if let Some(hit) = self.hit_cell(r, x, z, t_min, t_max) {
+                    return Some(hit);
+                }
+            }
+
+            if t_exit >= t_end {
+                return None;
+            }
+
+            if t_next_x < t_next_z {
+                let next = x as isize + step_x;
+                if next < 0 || next as usize >= self.nx - 1 {
+                    return None;
+                }
+                x = next as usize;
+                t_next_x += t_delta_x;
+            } else {
+                let next = z as isize + step_z;
+                if next < 0 || next as usize >= self.nz - 1 {
+                    return None;
+                }
+                z = next as usize;
+                t_next_z += t_delta_z;
+            }
+            t_enter = t_exit;
+        }
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        self.bounds
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use crate::material::Lambertian;
+    use std::io::Write;
+    use std::sync::Arc;
+
+    fn material() -> MaterialPtr {
+        Arc::new(Box::new(Lambertian::new(Vec3::default(), 1.)))
+    }
+
+    fn bounds() -> BoundingBox {
+        BoundingBox::new(Point3::new(0., 0., 0.), Point3::new(4., 1., 4.))
+    }
+
+    #[test]
+    pub fn slope_hit_works() {
+        // A ramp rising along X from height 0 to 1.
+        let field = Heightfield::procedural(5, 5, bounds(), material(), |x, _| x);
+
+        let ray = Ray::new(Point3::new(3., 10., 1.5), Vec3::new(0., -1., 0.));
+        let hit = field.hit(&ray, 0.001, f32::INFINITY).unwrap();
+        assert!((hit.t - (10. - 0.75)).abs() < 1e-4);
+        assert!((hit.normal - Vec3::new(-0.25, 1., 0.).as_unit_vector()).len() < 1e-4);
+        assert!((hit.u - 0.75).abs() < 1e-4);
+        assert!((hit.v - 0.375).abs() < 1e-4);
+
+        // A ray travelling across the field hits the ramp where it reaches its height.
+        let ray = Ray::new(Point3::new(-1., 0.5, 2.2), Vec3::new(1., 0., 0.));
+        let hit = field.hit(&ray, 0.001, f32::INFINITY).unwrap();
+        assert!((hit.point.x() - 2.).abs() < 1e-4);
+
+        // Above the highest point, the ray misses.
+        let ray = Ray::new(Point3::new(-1., 1.5, 2.2), Vec3::new(1., 0., 0.));
+        assert!(field.hit(&ray, 0.001, f32::INFINITY).is_none());
+    }
+
+    #[test]
+    pub fn load_pgm_works() {
+        let path = std::env::temp_dir().join("raytracing-heightfield-test.pgm");
+        {
+            let mut file = std::fs::File::create(&path).unwrap();
+            write!(file, "P5\n# a comment\n2 2\n255\n").unwrap();
+            file.write_all(&[0, 255, 255, 0]).unwrap();
+        }
+
+        let field = Heightfield::load_pgm(&path, bounds(), material()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(field.height(0, 0), 0.);
+        assert_eq!(field.height(1, 0), 1.);
+        assert_eq!(field.height(0, 1), 1.);
+        assert_eq!(field.height(1, 1), 0.);
+    }
+}
@@ -1,7 +1,10 @@
-use crate::{Material, Point3, Ray, Vec3};
+use crate::primitives::BoundingBox;
+use crate::stats;
+use crate::{Material, Point3, Random, Ray, Vec3};
 use space_partitioning::quadtree::{QuadRect, QuadTreeElement, AABB};
 use space_partitioning::QuadTree;
 use std::sync::Arc;
+use std::time::Instant;
 
 pub struct HitRecord {
     /// The distance from the ray's origin at which the hit occurred.
@@ -23,6 +26,12 @@ pub struct HitRecord {
 
     /// The material that was hit.
     pub material: Arc<Box<dyn Material>>,
+
+    /// The horizontal surface (texture) coordinate of the hit point.
+    pub u: f32,
+
+    /// The vertical surface (texture) coordinate of the hit point.
+    pub v: f32,
 }
 
 impl HitRecord {
@@ -44,19 +53,45 @@ impl HitRecord {
                 -outward_normal
             },
             material,
+            u: 0.,
+            v: 0.,
         }
     }
+
+    /// Sets the surface coordinates of the hit point.
+    pub fn with_uv(mut self, u: f32, v: f32) -> Self {
+        self.u = u;
+        self.v = v;
+        self
+    }
 }
 
 pub trait Hittable: Send + Sync {
     fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord>;
-    fn to_aabb(&self) -> AABB;
+
+    /// Intersects the ray like [`Hittable::hit`], drawing random decisions, e.g. the
+    /// free-flight distances in media, from the path's `rng` instead of white noise.
+    fn sample_hit(&self, r: &Ray, t_min: f32, t_max: f32, _rng: &Random) -> Option<HitRecord> {
+        self.hit(r, t_min, t_max)
+    }
+
+    /// The world space bounds of the object.
+    fn bounding_box(&self) -> BoundingBox;
+
+    /// The bounds of the object in the acceleration structure.
+    fn to_aabb(&self) -> AABB {
+        self.bounding_box().to_aabb()
+    }
 }
 
+pub type HittablePtr = Arc<Box<dyn Hittable>>;
+
 pub struct HittableList {
-    objects: Vec<Arc<Box<dyn Hittable>>>,
+    objects: Vec<HittablePtr>,
     tree_objects: QuadTree<u32>,
-    extents: AABB,
+    /// Objects without finite bounds, e.g. planes, which are tested for every ray.
+    unbounded_objects: Vec<u32>,
+    extents: BoundingBox,
 }
 
 impl HittableList {
@@ -64,44 +99,74 @@ impl HittableList {
         Self {
             objects: Vec::default(),
             tree_objects: QuadTree::new(bounds, 3, 4, 1),
-            extents: AABB::default(),
+            unbounded_objects: Vec::default(),
+            extents: BoundingBox::empty(),
         }
     }
 
     #[allow(dead_code)]
     pub fn clear(&mut self) {
         self.objects.clear();
+        self.unbounded_objects.clear();
     }
 
     pub fn add(&mut self, object: Box<dyn Hittable>) {
         let id = self.objects.len() as u32;
-        let aabb = object.to_aabb();
-        self.extents = self.extents + aabb;
-        self.tree_objects
-            .insert(QuadTreeElement::new(id, aabb))
-            .expect("insert failed");
+        let bounds = object.bounding_box();
+        self.extents = self.extents.union(&bounds);
+        if bounds.is_finite() {
+            let start = Instant::now();
+            self.tree_objects
+                .insert(QuadTreeElement::new(id, object.to_aabb()))
+                .expect("insert failed");
+            stats::add_acceleration_build_time(start.elapsed());
+        } else {
+            self.unbounded_objects.push(id);
+        }
 
         self.objects.push(Arc::new(object));
     }
-}
 
-impl Hittable for HittableList {
-    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+    /// Finds the closest hit, intersecting each candidate object using `hit`.
+    fn closest_hit(
+        &self,
+        r: &Ray,
+        t_max: f32,
+        hit: impl Fn(&dyn Hittable, f32) -> Option<HitRecord>,
+    ) -> Option<HitRecord> {
         let mut best_hit = None;
         let mut closest_so_far = t_max;
 
-        self.tree_objects.intersect_generic_fn(r, |id| {
+        let mut test = |id: u32| {
             let object = &self.objects[id as usize];
-            if let Some(hit) = object.hit(r, t_min, closest_so_far) {
+            stats::count_intersection_test();
+            if let Some(hit) = hit(object.as_ref().as_ref(), closest_so_far) {
                 closest_so_far = hit.t;
                 best_hit = Some(hit);
             }
-        });
+        };
+
+        for &id in &self.unbounded_objects {
+            test(id);
+        }
+        self.tree_objects.intersect_generic_fn(r, &mut test);
 
         best_hit
     }
+}
 
-    fn to_aabb(&self) -> AABB {
+impl Hittable for HittableList {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        self.closest_hit(r, t_max, |object, t_max| object.hit(r, t_min, t_max))
+    }
+
+    fn sample_hit(&self, r: &Ray, t_min: f32, t_max: f32, rng: &Random) -> Option<HitRecord> {
+        self.closest_hit(r, t_max, |object, t_max| {
+            object.sample_hit(r, t_min, t_max, rng)
+        })
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
         self.extents
     }
 }
@@ -0,0 +1,106 @@
+use crate::objects::HittablePtr;
+use crate::primitives::{AnimatedTransform, BoundingBox, Transform};
+use crate::{HitRecord, Hittable, Random, Ray};
+
+/// The placement of an instance, either fixed or changing over time.
+enum Placement {
+    Static(Transform),
+    Animated(AnimatedTransform),
+}
+
+/// Places a shared object into the world using an affine transform.
+///
+/// Rays are transformed into the object's space and the resulting hits back
+/// into world space, so the same object can be placed many times while its
+/// geometry is stored only once.
+pub struct Instance {
+    object: HittablePtr,
+    placement: Placement,
+    bounds: BoundingBox,
+}
+
+impl Instance {
+    pub fn new(object: HittablePtr, transform: Transform) -> Self {
+        let bounds = transform.bounding_box(&object.bounding_box());
+        Self {
+            object,
+            placement: Placement::Static(transform),
+            bounds,
+        }
+    }
+
+    /// Creates an instance moving over time; the bounds cover the whole motion.
+    pub fn animated(object: HittablePtr, transform: AnimatedTransform) -> Self {
+        let bounds = transform.bounding_box(&object.bounding_box());
+        Self {
+            object,
+            placement: Placement::Animated(transform),
+            bounds,
+        }
+    }
+}
+
+impl Instance {
+    /// Intersects the ray in object space using `hit`, transforming the hit back.
+    fn transformed_hit(
+        &self,
+        r: &Ray,
+        t_min: f32,
+        t_max: f32,
+        hit: impl FnOnce(&Ray, f32, f32) -> Option<HitRecord>,
+    ) -> Option<HitRecord> {
+        let animated;
+        let transform = match &self.placement {
+            Placement::Static(transform) => transform,
+            Placement::Animated(transform) => {
+                animated = transform.at(r.time);
+                &animated
+            }
+        };
+
+        let origin = transform.inverse_point(&r.origin);
+        let direction = transform.inverse_vector(&r.direction);
+
+        // Object space rays are normalized again, so distances scale by the
+        // length of the transformed (unit) direction.
+        let scale = direction.len();
+        let local_ray = r.spawn(origin, direction);
+        let hit = hit(&local_ray, t_min * scale, t_max * scale)?;
+
+        let local_outward_normal = if hit.is_front_facing {
+            hit.normal
+        } else {
+            -hit.normal
+        };
+        let outward_normal = transform.normal(&local_outward_normal).as_unit_vector();
+
+        Some(
+            HitRecord::new_from_ray(
+                r,
+                hit.t / scale,
+                transform.point(&hit.point),
+                outward_normal,
+                hit.material,
+            )
+            .with_uv(hit.u, hit.v),
+        )
+    }
+}
+
+impl Hittable for Instance {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        self.transformed_hit(r, t_min, t_max, |local_ray, t_min, t_max| {
+            self.object.hit(local_ray, t_min, t_max)
+        })
+    }
+
+    fn sample_hit(&self, r: &Ray, t_min: f32, t_max: f32, rng: &Random) -> Option<HitRecord> {
+        self.transformed_hit(r, t_min, t_max, |local_ray, t_min, t_max| {
+            self.object.sample_hit(local_ray, t_min, t_max, rng)
+        })
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        self.bounds
+    }
+}
@@ -0,0 +1,55 @@
+use crate::objects::Sphere;
+use crate::primitives::BoundingBox;
+use crate::{HitRecord, Hittable, Material, Point3, Ray};
+use std::sync::Arc;
+
+/// A sphere moving linearly from one center to another over time.
+pub struct MovingSphere {
+    sphere: Sphere,
+    center1: Point3,
+    time0: f32,
+    time1: f32,
+}
+
+impl MovingSphere {
+    pub fn new(
+        center0: Point3,
+        time0: f32,
+        center1: Point3,
+        time1: f32,
+        radius: f32,
+        material: Arc<Box<dyn Material>>,
+    ) -> Self {
+        Self {
+            sphere: Sphere::new(center0, radius, material),
+            center1,
+            time0,
+            time1,
+        }
+    }
+
+    /// Determines the center at the specified time, clamped to the start and end positions.
+    pub fn center(&self, time: f32) -> Point3 {
+        let center0 = self.sphere.center;
+        if self.time1 <= self.time0 {
+            return center0;
+        }
+        let t = ((time - self.time0) / (self.time1 - self.time0)).clamp(0., 1.);
+        center0 + (self.center1 - center0) * t
+    }
+}
+
+impl Hittable for MovingSphere {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        self.sphere.hit_at(self.center(r.time), r, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        let end = Sphere::new(
+            self.center1,
+            self.sphere.radius,
+            self.sphere.material.clone(),
+        );
+        self.sphere.bounding_box().union(&end.bounding_box())
+    }
+}
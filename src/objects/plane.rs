@@ -0,0 +1,92 @@
+use crate::material::MaterialPtr;
+use crate::primitives::{BoundingBox, Onb};
+use crate::{HitRecord, Hittable, Point3, Ray, Vec3};
+
+/// An infinite plane through a point.
+///
+/// The surface coordinates repeat every unit along two axes spanning the plane.
+pub struct Plane {
+    point: Point3,
+    frame: Onb,
+    material: MaterialPtr,
+}
+
+impl Plane {
+    pub fn new(point: Point3, normal: Vec3, material: MaterialPtr) -> Self {
+        Self {
+            point,
+            frame: Onb::from_w(&normal.as_unit_vector()),
+            material,
+        }
+    }
+}
+
+impl Hittable for Plane {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        let denominator = self.frame.w.dot(&r.direction);
+        if denominator.abs() < 1e-8 {
+            return None;
+        }
+
+        let t = self.frame.w.dot(&(self.point - r.origin)) / denominator;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        let hit_point = r.at(t);
+        let local = self.frame.world_to_local(&(hit_point - self.point));
+        Some(
+            HitRecord::new_from_ray(r, t, hit_point, self.frame.w, self.material.clone())
+                .with_uv(local.x().rem_euclid(1.), local.y().rem_euclid(1.)),
+        )
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        BoundingBox::infinite()
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::objects::HittableList;
+    use space_partitioning::quadtree::QuadRect;
+    use std::sync::Arc;
+
+    fn ground() -> Plane {
+        let material: MaterialPtr = Arc::new(Box::new(Lambertian::new(Vec3::default(), 1.)));
+        Plane::new(Point3::new(0., 1., 0.), Vec3::new(0., 2., 0.), material)
+    }
+
+    #[test]
+    pub fn plane_hit_works() {
+        let plane = ground();
+        let ray = Ray::new(Point3::new(0.25, 5., 3.5), Vec3::new(0., -1., 0.));
+        let hit = plane.hit(&ray, 0.001, f32::INFINITY).expect("hit expected");
+        assert!((hit.t - 4.).abs() < 1e-5);
+        assert!((hit.normal - Vec3::new(0., 1., 0.)).len() < 1e-5);
+        assert!((0. ..1.).contains(&hit.u) && (0. ..1.).contains(&hit.v));
+
+        // The surface coordinates repeat every unit.
+        let shifted = Ray::new(Point3::new(1.25, 5., 5.5), Vec3::new(0., -1., 0.));
+        let shifted_hit = plane.hit(&shifted, 0.001, f32::INFINITY).unwrap();
+        assert!((shifted_hit.u - hit.u).abs() < 1e-4 && (shifted_hit.v - hit.v).abs() < 1e-4);
+
+        let parallel = Ray::new(Point3::new(0., 5., 0.), Vec3::new(1., 0., 0.));
+        assert!(plane.hit(&parallel, 0.001, f32::INFINITY).is_none());
+    }
+
+    #[test]
+    pub fn planes_are_hit_outside_of_the_quadtree() {
+        assert!(!ground().bounding_box().is_finite());
+
+        let mut world = HittableList::new(QuadRect::new(-10, -10, 20, 20));
+        world.add(Box::new(ground()));
+        let far_away = Ray::new(Point3::new(1000., 5., -1000.), Vec3::new(0., -1., 0.));
+        let hit = world
+            .hit(&far_away, 0.001, f32::INFINITY)
+            .expect("hit expected");
+        assert!((hit.t - 4.).abs() < 1e-3);
+    }
+}
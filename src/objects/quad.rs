@@ -0,0 +1,103 @@
+use crate::material::MaterialPtr;
+use crate::primitives::BoundingBox;
+use crate::{HitRecord, Hittable, Point3, Ray, Vec3};
+
+/// A parallelogram spanned by two edges starting at a corner.
+///
+/// The surface coordinates run from `0.0` to `1.0` along each edge.
+pub struct Quad {
+    corner: Point3,
+    u: Vec3,
+    v: Vec3,
+    normal: Vec3,
+    /// The plane normal scaled such that its dot product with the cross
+    /// products of the edges yields the surface coordinates.
+    w: Vec3,
+    material: MaterialPtr,
+}
+
+impl Quad {
+    pub fn new(corner: Point3, u: Vec3, v: Vec3, material: MaterialPtr) -> Self {
+        let n = u.cross(&v);
+        Self {
+            corner,
+            u,
+            v,
+            normal: n.as_unit_vector(),
+            w: n / n.dot(&n),
+            material,
+        }
+    }
+}
+
+impl Hittable for Quad {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        let denominator = self.normal.dot(&r.direction);
+        if denominator.abs() < 1e-8 {
+            return None;
+        }
+
+        let t = self.normal.dot(&(self.corner - r.origin)) / denominator;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        let hit_point = r.at(t);
+        let planar = hit_point - self.corner;
+        let alpha = self.w.dot(&planar.cross(&self.v));
+        let beta = self.w.dot(&self.u.cross(&planar));
+        if !(0. ..=1.).contains(&alpha) || !(0. ..=1.).contains(&beta) {
+            return None;
+        }
+
+        Some(
+            HitRecord::new_from_ray(r, t, hit_point, self.normal, self.material.clone())
+                .with_uv(alpha, beta),
+        )
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        let bounds = [self.u, self.v, self.u + self.v]
+            .iter()
+            .map(|&edge| self.corner + edge)
+            .fold(BoundingBox::new(self.corner, self.corner), |bounds, p| {
+                bounds.union(&BoundingBox::new(p, p))
+            });
+
+        // Pad the box so that axis-aligned quads do not produce a degenerate one.
+        let padding = Vec3::new(1e-4, 1e-4, 1e-4);
+        BoundingBox::new(bounds.min - padding, bounds.max + padding)
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use crate::material::Lambertian;
+    use std::sync::Arc;
+
+    #[test]
+    pub fn quad_hit_works() {
+        let material: MaterialPtr = Arc::new(Box::new(Lambertian::new(Vec3::default(), 1.)));
+        let quad = Quad::new(
+            Point3::new(0., 0., 0.),
+            Vec3::new(2., 0., 0.),
+            Vec3::new(0., 3., 0.),
+            material,
+        );
+
+        let ray = Ray::new(Point3::new(1.5, 0.75, 5.), Vec3::new(0., 0., -1.));
+        let hit = quad.hit(&ray, 0.001, f32::INFINITY).expect("hit expected");
+        assert!((hit.t - 5.).abs() < 1e-5);
+        assert!((hit.normal - Vec3::new(0., 0., 1.)).len() < 1e-5);
+        assert!((hit.u - 0.75).abs() < 1e-5 && (hit.v - 0.25).abs() < 1e-5);
+
+        let outside = Ray::new(Point3::new(2.5, 1., 5.), Vec3::new(0., 0., -1.));
+        assert!(quad.hit(&outside, 0.001, f32::INFINITY).is_none());
+
+        // The box around the flat quad is padded to a nonzero thickness.
+        let bounds = quad.bounding_box();
+        assert!((bounds.max - Point3::new(2., 3., 0.)).len() < 1e-3);
+        assert!(bounds.max.z() > bounds.min.z());
+    }
+}
@@ -0,0 +1,137 @@
+use crate::material::MaterialPtr;
+use crate::objects::Sphere;
+use crate::primitives::BoundingBox;
+use crate::sdf::DistanceFieldPtr;
+use crate::{HitRecord, Hittable, Point3, Ray, Vec3};
+
+/// Renders a signed distance field using sphere tracing.
+///
+/// The ray is marched within the field's bounds, each step advancing by the
+/// distance to the closest surface. Normals are estimated from the gradient
+/// of the field, and the surface coordinates are the spherical coordinates
+/// of the normal.
+pub struct SdfObject {
+    field: DistanceFieldPtr,
+    bounds: BoundingBox,
+    material: MaterialPtr,
+    max_steps: usize,
+}
+
+impl SdfObject {
+    /// The distance below which the surface counts as hit.
+    const HIT_DISTANCE: f32 = 1e-4;
+
+    /// The offset used for estimating the gradient.
+    const GRADIENT_STEP: f32 = 1e-4;
+
+    pub fn new(field: DistanceFieldPtr, material: MaterialPtr) -> Self {
+        // Pad the bounds so that surfaces touching them are not skipped.
+        let bounds = field.bounding_box();
+        let padding = Vec3::new(1e-3, 1e-3, 1e-3);
+        let bounds = BoundingBox::new(bounds.min - padding, bounds.max + padding);
+        Self {
+            field,
+            bounds,
+            material,
+            max_steps: 256,
+        }
+    }
+
+    /// Sets the maximum number of steps before a ray is considered to miss.
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Estimates the surface normal from four samples arranged as a tetrahedron.
+    fn normal(&self, p: &Point3) -> Vec3 {
+        let h = Self::GRADIENT_STEP;
+        [
+            Vec3::new(1., -1., -1.),
+            Vec3::new(-1., -1., 1.),
+            Vec3::new(-1., 1., -1.),
+            Vec3::new(1., 1., 1.),
+        ]
+        .iter()
+        .fold(Vec3::default(), |sum, &k| {
+            sum + k * self.field.distance(&(*p + k * h))
+        })
+        .as_unit_vector()
+    }
+}
+
+impl Hittable for SdfObject {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        let (t_start, t_end) = self.bounds.hit(r, t_min, t_max)?;
+
+        // Rays starting inside the surface, e.g. after refraction, march
+        // towards the surface from the inside.
+        let start = r.at(t_start);
+        let start_distance = self.field.distance(&start);
+        let side = start_distance.signum();
+
+        // Rays leaving the surface they were scattered from must get away
+        // from it before hits count, or they would hit it again immediately.
+        let mut leaving_surface = start_distance.abs() < Self::HIT_DISTANCE
+            && side * self.normal(&start).dot(&r.direction) > 0.;
+
+        let mut t = t_start;
+        for _ in 0..self.max_steps {
+            let distance = side * self.field.distance(&r.at(t));
+            if distance >= Self::HIT_DISTANCE {
+                leaving_surface = false;
+            } else if !leaving_surface {
+                let hit_point = r.at(t);
+                let outward_normal = self.normal(&hit_point);
+                let (u, v) = Sphere::uv(&outward_normal);
+                return Some(
+                    HitRecord::new_from_ray(r, t, hit_point, outward_normal, self.material.clone())
+                        .with_uv(u, v),
+                );
+            }
+
+            t += distance.max(Self::HIT_DISTANCE);
+            if t > t_end {
+                break;
+            }
+        }
+
+        None
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        self.bounds
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::sdf::SdfSphere;
+    use std::sync::Arc;
+
+    #[test]
+    pub fn sphere_tracing_works() {
+        let material: MaterialPtr = Arc::new(Box::new(Lambertian::new(Vec3::default(), 1.)));
+        let object = SdfObject::new(
+            Box::new(SdfSphere::new(Point3::new(0., 0., 0.), 1.)),
+            material,
+        );
+
+        let ray = Ray::new(Point3::new(-5., 0.3, 0.), Vec3::new(1., 0., 0.));
+        let hit = object.hit(&ray, 0.001, f32::INFINITY).unwrap();
+        let expected_t = 5. - (1f32 - 0.09).sqrt();
+        assert!((hit.t - expected_t).abs() < 1e-3);
+
+        let expected_normal = ray.at(expected_t);
+        assert!((hit.normal - expected_normal).len() < 1e-2);
+        assert!(hit.is_front_facing);
+
+        // From the inside, the ray leaves through the back.
+        let ray = Ray::new(Point3::new(0., 0., 0.), Vec3::new(1., 0., 0.));
+        let hit = object.hit(&ray, 0.001, f32::INFINITY).unwrap();
+        assert!((hit.t - 1.).abs() < 1e-3);
+        assert!(!hit.is_front_facing);
+    }
+}
@@ -0,0 +1,19 @@
+use crate::{HitRecord, Hittable, Ray};
+
+/// A range along a ray that lies inside a solid.
+pub struct Span {
+    /// The surface through which the ray enters the solid.
+    pub enter: HitRecord,
+    /// The surface through which the ray leaves the solid.
+    pub exit: HitRecord,
+}
+
+/// A closed object that can report every range along a ray lying inside of it.
+///
+/// Unlike [`Hittable::hit`], the spans are not restricted to a range of
+/// the ray parameter, i.e. they may start behind the ray's origin. This is
+/// what allows solids to be combined using constructive solid geometry.
+pub trait Solid: Hittable {
+    /// Determines the disjoint spans along the ray, ordered by distance.
+    fn spans(&self, r: &Ray) -> Vec<Span>;
+}
@@ -1,5 +1,7 @@
-use crate::{HitRecord, Hittable, Material, Point3, Ray, GRID_SCALE};
-use space_partitioning::quadtree::AABB;
+use crate::objects::{Solid, Span};
+use crate::primitives::BoundingBox;
+use crate::{HitRecord, Hittable, Material, Point3, Ray, Vec3};
+use std::f32::consts::PI;
 use std::sync::Arc;
 
 pub struct Sphere {
@@ -16,11 +18,20 @@ impl Sphere {
             material,
         }
     }
-}
 
-impl Hittable for Sphere {
-    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
-        let oc = r.origin - self.center;
+    /// Determines the surface coordinates of a point on the unit sphere.
+    ///
+    /// `u` is the angle around the Y axis starting at `-X`, `v` is the
+    /// angle from `-Y` to `+Y`, both normalized to `0.0..=1.0`.
+    pub fn uv(p: &Point3) -> (f32, f32) {
+        let theta = (-p.y()).clamp(-1., 1.).acos();
+        let phi = (-p.z()).atan2(p.x()) + PI;
+        (phi / (2. * PI), theta / PI)
+    }
+
+    /// Determines both intersections of the ray's line with the sphere located at `center`.
+    fn roots(&self, center: Point3, r: &Ray) -> Option<(f32, f32)> {
+        let oc = r.origin - center;
         let a = r.direction.len_squared();
         let half_b = oc.dot(&r.direction);
         let c = oc.len_squared() - self.radius * self.radius;
@@ -31,40 +42,56 @@ impl Hittable for Sphere {
         }
 
         let sqrt_d = discriminant.sqrt();
+        Some(((-half_b - sqrt_d) / a, (-half_b + sqrt_d) / a))
+    }
+
+    fn record(&self, center: Point3, r: &Ray, t: f32) -> HitRecord {
+        let hit_point = r.at(t);
+        let outward_normal = (hit_point - center) / self.radius;
+        let (u, v) = Self::uv(&outward_normal);
+        HitRecord::new_from_ray(r, t, hit_point, outward_normal, self.material.clone())
+            .with_uv(u, v)
+    }
+
+    /// Intersects the ray with the sphere as if it was located at `center`.
+    pub fn hit_at(&self, center: Point3, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        let (near, far) = self.roots(center, r)?;
 
         // Find the nearest root that lies in the acceptable range.
         // Since it's a quadratic equation there may be two solutions,
         // and we need to check both for validity.
-        let mut root = (-half_b - sqrt_d) / a;
+        let mut root = near;
         if root < t_min || root > t_max {
             // Check the second solution.
-            root = (-half_b + sqrt_d) / a;
+            root = far;
             if root < t_min || root > t_max {
                 return None;
             }
         }
 
-        let hit_point = r.at(root);
-        let outward_normal = (hit_point - self.center) / self.radius;
-        Some(HitRecord::new_from_ray(
-            r,
-            root,
-            hit_point,
-            outward_normal,
-            self.material.clone(),
-        ))
+        Some(self.record(center, r, root))
+    }
+}
+
+impl Hittable for Sphere {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        self.hit_at(self.center, r, t_min, t_max)
     }
 
-    fn to_aabb(&self) -> AABB {
-        let x = self.center.x() * GRID_SCALE;
-        let z = self.center.z() * GRID_SCALE;
-        let r = self.radius * GRID_SCALE;
+    fn bounding_box(&self) -> BoundingBox {
+        let r = Vec3::new(self.radius, self.radius, self.radius);
+        BoundingBox::new(self.center - r, self.center + r)
+    }
+}
 
-        AABB::new(
-            (x - r).floor() as i32,
-            (z - r).floor() as i32,
-            (x + r).ceil() as i32,
-            (z + r).ceil() as i32,
-        )
+impl Solid for Sphere {
+    fn spans(&self, r: &Ray) -> Vec<Span> {
+        match self.roots(self.center, r) {
+            Some((near, far)) => vec![Span {
+                enter: self.record(self.center, r, near),
+                exit: self.record(self.center, r, far),
+            }],
+            None => vec![],
+        }
     }
 }
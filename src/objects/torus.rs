@@ -0,0 +1,304 @@
+use crate::material::MaterialPtr;
+use crate::objects::{Disk, Solid, Span};
+use crate::primitives::{BoundingBox, Onb};
+use crate::{HitRecord, Hittable, Point3, Ray, Vec3};
+use std::f32::consts::PI;
+
+/// A torus around an axis through its center.
+///
+/// `u` is the angle around the axis and `v` the angle around the tube,
+/// both normalized to `0.0..=1.0`.
+pub struct Torus {
+    center: Point3,
+    frame: Onb,
+    /// The distance from the center to the center of the tube.
+    major_radius: f32,
+    /// The radius of the tube.
+    minor_radius: f32,
+    material: MaterialPtr,
+}
+
+impl Torus {
+    pub fn new(
+        center: Point3,
+        axis: Vec3,
+        major_radius: f32,
+        minor_radius: f32,
+        material: MaterialPtr,
+    ) -> Self {
+        Self {
+            center,
+            frame: Onb::from_w(&axis.as_unit_vector()),
+            major_radius,
+            minor_radius,
+            material,
+        }
+    }
+}
+
+impl Torus {
+    /// Determines all intersections of the ray's line with the torus, ordered by distance.
+    fn roots(&self, r: &Ray) -> Vec<f32> {
+        let origin = self.frame.world_to_local(&(r.origin - self.center));
+        let direction = self.frame.world_to_local(&r.direction);
+
+        // Start at the bounding sphere, both to reject misses early and to
+        // keep the quartic's coefficients small, which improves its precision.
+        let outer = self.major_radius + self.minor_radius;
+        let half_b = origin.dot(&direction);
+        let discriminant = half_b * half_b - (origin.len_squared() - outer * outer);
+        if discriminant < 0. {
+            return vec![];
+        }
+        let t_start = -half_b - discriminant.sqrt();
+
+        let o = [
+            (origin.x() + t_start * direction.x()) as f64,
+            (origin.y() + t_start * direction.y()) as f64,
+            (origin.z() + t_start * direction.z()) as f64,
+        ];
+        let d = [
+            direction.x() as f64,
+            direction.y() as f64,
+            direction.z() as f64,
+        ];
+        let r2 = (self.major_radius as f64).powi(2);
+        let f = o[0] * d[0] + o[1] * d[1] + o[2] * d[2];
+        let e = o[0] * o[0] + o[1] * o[1] + o[2] * o[2] - r2 - (self.minor_radius as f64).powi(2);
+
+        // (|p|² - R² - r²)² = 4R²(r² - z²) for p = o + t·d with a unit length d.
+        let coefficients = [
+            e * e + 4. * r2 * o[2] * o[2] - 4. * r2 * (self.minor_radius as f64).powi(2),
+            4. * f * e + 8. * r2 * o[2] * d[2],
+            4. * f * f + 2. * e + 4. * r2 * d[2] * d[2],
+            4. * f,
+            1.,
+        ];
+
+        let mut roots: Vec<f32> = solve_quartic(&coefficients)
+            .into_iter()
+            .map(|t| t_start + polish_root(&coefficients, t) as f32)
+            .collect();
+        roots.sort_by(|a, b| a.total_cmp(b));
+        roots
+    }
+
+    fn record(&self, r: &Ray, t: f32) -> HitRecord {
+        let hit_point = r.at(t);
+        let local = self.frame.world_to_local(&(hit_point - self.center));
+        let distance = (local.x() * local.x() + local.y() * local.y()).sqrt();
+        let ring = if distance > 0. {
+            Vec3::new(local.x(), local.y(), 0.) * (self.major_radius / distance)
+        } else {
+            Vec3::new(self.major_radius, 0., 0.)
+        };
+        let outward_normal = self.frame.local_to_world(&(local - ring)).as_unit_vector();
+
+        let u = (local.y().atan2(local.x()) + PI) / (2. * PI);
+        let v = (local.z().atan2(distance - self.major_radius) + PI) / (2. * PI);
+
+        HitRecord::new_from_ray(r, t, hit_point, outward_normal, self.material.clone())
+            .with_uv(u, v)
+    }
+}
+
+impl Hittable for Torus {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        self.roots(r)
+            .into_iter()
+            .find(|&t| t >= t_min && t <= t_max)
+            .map(|t| self.record(r, t))
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        let extent = Disk::extent(&self.frame.w, self.major_radius)
+            + Vec3::new(self.minor_radius, self.minor_radius, self.minor_radius);
+        BoundingBox::new(self.center - extent, self.center + extent)
+    }
+}
+
+impl Solid for Torus {
+    fn spans(&self, r: &Ray) -> Vec<Span> {
+        let roots = self.roots(r);
+        roots
+            .chunks_exact(2)
+            .map(|pair| Span {
+                enter: self.record(r, pair[0]),
+                exit: self.record(r, pair[1]),
+            })
+            .collect()
+    }
+}
+
+const EPSILON: f64 = 1e-9;
+
+/// Evaluates the polynomial `c[0] + c[1]·x + c[2]·x² + …`.
+fn evaluate(c: &[f64], x: f64) -> f64 {
+    c.iter().rev().fold(0., |sum, &c| sum * x + c)
+}
+
+/// Refines a root found analytically using a few Newton iterations.
+fn polish_root(c: &[f64; 5], mut x: f64) -> f64 {
+    let derivative = [c[1], 2. * c[2], 3. * c[3], 4. * c[4]];
+    for _ in 0..2 {
+        let slope = evaluate(&derivative, x);
+        if slope.abs() < EPSILON {
+            break;
+        }
+        x -= evaluate(c, x) / slope;
+    }
+    x
+}
+
+/// Solves `c[0] + c[1]·x + c[2]·x² = 0`.
+fn solve_quadratic(c: &[f64; 3]) -> Vec<f64> {
+    let p = c[1] / (2. * c[2]);
+    let q = c[0] / c[2];
+    let discriminant = p * p - q;
+
+    if discriminant.abs() < EPSILON {
+        vec![-p]
+    } else if discriminant < 0. {
+        vec![]
+    } else {
+        let sqrt_d = discriminant.sqrt();
+        vec![sqrt_d - p, -sqrt_d - p]
+    }
+}
+
+/// Solves `c[0] + c[1]·x + c[2]·x² + c[3]·x³ = 0` using Cardano's method.
+fn solve_cubic(c: &[f64; 4]) -> Vec<f64> {
+    // Normal form x³ + Ax² + Bx + C = 0, substituted with x = y - A/3
+    // to eliminate the quadratic term: y³ + 3py + 2q = 0.
+    let a = c[2] / c[3];
+    let b = c[1] / c[3];
+    let c = c[0] / c[3];
+
+    let a2 = a * a;
+    let p = (-a2 / 3. + b) / 3.;
+    let q = (2. / 27. * a * a2 - a * b / 3. + c) / 2.;
+    let p3 = p * p * p;
+    let discriminant = q * q + p3;
+
+    let roots = if discriminant.abs() < EPSILON {
+        if q.abs() < EPSILON {
+            vec![0.]
+        } else {
+            let u = (-q).cbrt();
+            vec![2. * u, -u]
+        }
+    } else if discriminant < 0. {
+        // Three real roots.
+        let phi = (-q / (-p3).sqrt()).clamp(-1., 1.).acos() / 3.;
+        let t = 2. * (-p).sqrt();
+        vec![
+            t * phi.cos(),
+            -t * (phi + std::f64::consts::PI / 3.).cos(),
+            -t * (phi - std::f64::consts::PI / 3.).cos(),
+        ]
+    } else {
+        let sqrt_d = discriminant.sqrt();
+        vec![(sqrt_d - q).cbrt() - (sqrt_d + q).cbrt()]
+    };
+
+    roots.into_iter().map(|y| y - a / 3.).collect()
+}
+
+/// Solves `c[0] + c[1]·x + … + c[4]·x⁴ = 0` using Ferrari's method.
+///
+/// Follows Schwarze, "Cubic and Quartic Roots", Graphics Gems (1990).
+fn solve_quartic(c: &[f64; 5]) -> Vec<f64> {
+    // Normal form x⁴ + Ax³ + Bx² + Cx + D = 0, substituted with x = y - A/4
+    // to eliminate the cubic term: y⁴ + py² + qy + r = 0.
+    let a = c[3] / c[4];
+    let b = c[2] / c[4];
+    let c_ = c[1] / c[4];
+    let d = c[0] / c[4];
+
+    let a2 = a * a;
+    let p = -3. / 8. * a2 + b;
+    let q = a2 * a / 8. - a * b / 2. + c_;
+    let r = -3. / 256. * a2 * a2 + a2 * b / 16. - a * c_ / 4. + d;
+
+    let roots = if r.abs() < EPSILON {
+        // No absolute term: y(y³ + py + q) = 0.
+        let mut roots = solve_cubic(&[q, p, 0., 1.]);
+        roots.push(0.);
+        roots
+    } else {
+        // Solve the resolvent cubic and use one of its roots to
+        // factor the quartic into two quadratic equations.
+        let z = solve_cubic(&[r * p / 2. - q * q / 8., -r, -p / 2., 1.])[0];
+
+        let u = z * z - r;
+        let v = 2. * z - p;
+        let u = if u.abs() < EPSILON {
+            0.
+        } else if u > 0. {
+            u.sqrt()
+        } else {
+            return vec![];
+        };
+        let v = if v.abs() < EPSILON {
+            0.
+        } else if v > 0. {
+            v.sqrt()
+        } else {
+            return vec![];
+        };
+
+        let v = if q < 0. { -v } else { v };
+        let mut roots = solve_quadratic(&[z - u, v, 1.]);
+        roots.extend(solve_quadratic(&[z + u, -v, 1.]));
+        roots
+    };
+
+    roots.into_iter().map(|y| y - a / 4.).collect()
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    fn assert_roots(mut actual: Vec<f64>, expected: &[f64]) {
+        actual.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        actual.dedup_by(|a, b| (*a - *b).abs() < 1e-6);
+        assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-6, "{} != {}", a, e);
+        }
+    }
+
+    #[test]
+    pub fn cubic_works() {
+        // (x - 1)(x - 2)(x + 3) = x³ - 7x + 6
+        assert_roots(solve_cubic(&[6., -7., 0., 1.]), &[-3., 1., 2.]);
+    }
+
+    #[test]
+    pub fn quartic_works() {
+        // (x - 1)(x + 1)(x - 2)(x + 3) = x⁴ + x³ - 7x² - x + 6
+        assert_roots(solve_quartic(&[6., -1., -7., 1., 1.]), &[-3., -1., 1., 2.]);
+        // x⁴ + 1 has no real roots.
+        assert_roots(solve_quartic(&[1., 0., 0., 0., 1.]), &[]);
+    }
+
+    #[test]
+    pub fn torus_hit_works() {
+        use crate::material::{Lambertian, MaterialPtr};
+        use std::sync::Arc;
+
+        let material: MaterialPtr = Arc::new(Box::new(Lambertian::new(Vec3::default(), 1.)));
+        let torus = Torus::new(Point3::default(), Vec3::new(0., 1., 0.), 2., 0.5, material);
+
+        // Along the X axis the ray enters the tube at x = -2.5.
+        let ray = Ray::new(Point3::new(-10., 0., 0.), Vec3::new(1., 0., 0.));
+        let hit = torus.hit(&ray, 0.001, f32::INFINITY).expect("hit expected");
+        assert!((hit.t - 7.5).abs() < 1e-4);
+        assert!((hit.normal - Vec3::new(-1., 0., 0.)).len() < 1e-4);
+
+        // The hole in the middle is empty.
+        let ray = Ray::new(Point3::new(0., 10., 0.), Vec3::new(0., -1., 0.));
+        assert!(torus.hit(&ray, 0.001, f32::INFINITY).is_none());
+    }
+}
@@ -0,0 +1,159 @@
+use crate::Point3;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Error, ErrorKind, Read};
+use std::path::Path;
+
+/// A dense 3D grid of density values.
+///
+/// Samples are located at the voxel centers and interpolated trilinearly.
+pub struct VoxelGrid {
+    nx: usize,
+    ny: usize,
+    nz: usize,
+    /// The densities, with X varying fastest and Z slowest.
+    data: Vec<f32>,
+    max_density: f32,
+}
+
+impl VoxelGrid {
+    pub fn new(nx: usize, ny: usize, nz: usize, data: Vec<f32>) -> Self {
+        assert_eq!(data.len(), nx * ny * nz, "grid size mismatch");
+        let max_density = data.iter().copied().fold(0., f32::max);
+        Self {
+            nx,
+            ny,
+            nz,
+            data,
+            max_density,
+        }
+    }
+
+    /// Generates a grid by evaluating `density` at every voxel center,
+    /// given in normalized coordinates in range `0.0..1.0`.
+    pub fn procedural(nx: usize, ny: usize, nz: usize, density: impl Fn(Point3) -> f32) -> Self {
+        let mut data = Vec::with_capacity(nx * ny * nz);
+        for z in 0..nz {
+            for y in 0..ny {
+                for x in 0..nx {
+                    data.push(density(Point3::new(
+                        (x as f32 + 0.5) / nx as f32,
+                        (y as f32 + 0.5) / ny as f32,
+                        (z as f32 + 0.5) / nz as f32,
+                    )));
+                }
+            }
+        }
+        Self::new(nx, ny, nz, data)
+    }
+
+    /// Loads a grid from a simple raw file.
+    ///
+    /// The file starts with the text line `VOXELS`, followed by a text line
+    /// holding the grid dimensions as `nx ny nz`. The densities follow as
+    /// little-endian 32-bit floats, with X varying fastest and Z slowest.
+    pub fn load_raw(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let invalid = |message: &str| Error::new(ErrorKind::InvalidData, message.to_string());
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        if line.trim() != "VOXELS" {
+            return Err(invalid("missing VOXELS header"));
+        }
+
+        line.clear();
+        reader.read_line(&mut line)?;
+        let dims = line
+            .split_whitespace()
+            .map(|n| n.parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| invalid("invalid grid dimensions"))?;
+        if dims.len() != 3 {
+            return Err(invalid("expected three grid dimensions"));
+        }
+        if dims.contains(&0) {
+            return Err(invalid("grid dimensions must not be zero"));
+        }
+
+        let size = dims[0]
+            .checked_mul(dims[1])
+            .and_then(|n| n.checked_mul(dims[2]))
+            .and_then(|n| n.checked_mul(4))
+            .ok_or_else(|| invalid("grid dimensions too large"))?;
+        let mut bytes = vec![0u8; size];
+        reader.read_exact(&mut bytes)?;
+        let data = bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+
+        Ok(Self::new(dims[0], dims[1], dims[2], data))
+    }
+
+    /// The largest density in the grid, used as the majorant for tracking.
+    pub fn max_density(&self) -> f32 {
+        self.max_density
+    }
+
+    fn voxel(&self, x: usize, y: usize, z: usize) -> f32 {
+        self.data[(z * self.ny + y) * self.nx + x]
+    }
+
+    /// Looks up the interpolated density at normalized coordinates in range `0.0..=1.0`.
+    pub fn density(&self, p: &Point3) -> f32 {
+        // Convert to continuous voxel coordinates relative to the voxel centers.
+        let gx = (p.x() * self.nx as f32 - 0.5).clamp(0., (self.nx - 1) as f32);
+        let gy = (p.y() * self.ny as f32 - 0.5).clamp(0., (self.ny - 1) as f32);
+        let gz = (p.z() * self.nz as f32 - 0.5).clamp(0., (self.nz - 1) as f32);
+
+        let (x0, y0, z0) = (gx as usize, gy as usize, gz as usize);
+        let x1 = (x0 + 1).min(self.nx - 1);
+        let y1 = (y0 + 1).min(self.ny - 1);
+        let z1 = (z0 + 1).min(self.nz - 1);
+        let (tx, ty, tz) = (gx - x0 as f32, gy - y0 as f32, gz - z0 as f32);
+
+        let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+        let c00 = lerp(self.voxel(x0, y0, z0), self.voxel(x1, y0, z0), tx);
+        let c10 = lerp(self.voxel(x0, y1, z0), self.voxel(x1, y1, z0), tx);
+        let c01 = lerp(self.voxel(x0, y0, z1), self.voxel(x1, y0, z1), tx);
+        let c11 = lerp(self.voxel(x0, y1, z1), self.voxel(x1, y1, z1), tx);
+        lerp(lerp(c00, c10, ty), lerp(c01, c11, ty), tz)
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    pub fn density_interpolates_between_voxel_centers() {
+        let grid = VoxelGrid::new(2, 1, 1, vec![0., 1.]);
+        assert_eq!(grid.density(&Point3::new(0.25, 0.5, 0.5)), 0.);
+        assert_eq!(grid.density(&Point3::new(0.5, 0.5, 0.5)), 0.5);
+        assert_eq!(grid.density(&Point3::new(1.0, 0.5, 0.5)), 1.);
+        assert_eq!(grid.max_density(), 1.);
+    }
+
+    #[test]
+    pub fn load_raw_works() {
+        let path = std::env::temp_dir().join("raytracing-voxel-grid-test.vox");
+        {
+            let mut file = File::create(&path).unwrap();
+            write!(file, "VOXELS\n2 1 1\n").unwrap();
+            file.write_all(&0.25f32.to_le_bytes()).unwrap();
+            file.write_all(&2f32.to_le_bytes()).unwrap();
+        }
+
+        let grid = VoxelGrid::load_raw(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(grid.max_density(), 2.);
+        assert_eq!(grid.density(&Point3::new(0., 0., 0.)), 0.25);
+
+        let huge = format!("VOXELS\n{} {} 2\n", usize::MAX / 2, usize::MAX / 2);
+        std::fs::write(&path, huge).unwrap();
+        let error = VoxelGrid::load_raw(&path).err().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,118 @@
+//! Loading of grayscale images in the portable graymap (PGM) format.
+
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+/// A grayscale image with values normalized to `0.0..=1.0`.
+pub struct Grayscale {
+    pub width: usize,
+    pub height: usize,
+    /// The pixel values row by row, starting at the top.
+    pub values: Vec<f32>,
+}
+
+/// Loads a PGM image, either binary (`P5`) or plain (`P2`).
+pub fn load(path: impl AsRef<Path>) -> std::io::Result<Grayscale> {
+    let invalid = |message: &str| Error::new(ErrorKind::InvalidData, message.to_string());
+    let bytes = std::fs::read(path)?;
+
+    // The header consists of the magic number, width, height and maximum
+    // value, separated by whitespace and possibly interleaved with comments.
+    let mut position = 0;
+    let mut header = Vec::new();
+    while header.len() < 4 {
+        while position < bytes.len() && bytes[position].is_ascii_whitespace() {
+            position += 1;
+        }
+        if position < bytes.len() && bytes[position] == b'#' {
+            while position < bytes.len() && bytes[position] != b'\n' {
+                position += 1;
+            }
+            continue;
+        }
+        let start = position;
+        while position < bytes.len() && !bytes[position].is_ascii_whitespace() {
+            position += 1;
+        }
+        if start == position {
+            return Err(invalid("truncated PGM header"));
+        }
+        header.push(String::from_utf8_lossy(&bytes[start..position]).to_string());
+    }
+
+    let number = |token: &str| {
+        token
+            .parse::<usize>()
+            .map_err(|_| invalid("invalid PGM header"))
+    };
+    let (width, height, max_value) = (
+        number(&header[1])?,
+        number(&header[2])?,
+        number(&header[3])?,
+    );
+    if max_value == 0 || max_value > 65535 {
+        return Err(invalid("unsupported PGM maximum value"));
+    }
+
+    let count = width * height;
+    let values: Vec<f32> = match header[0].as_str() {
+        "P5" => {
+            // A single whitespace character separates the header from the data.
+            let data = bytes.get(position + 1..).unwrap_or_default();
+            let size = if max_value < 256 { 1 } else { 2 };
+            if data.len() < count * size {
+                return Err(invalid("truncated PGM data"));
+            }
+            data.chunks_exact(size)
+                .take(count)
+                .map(|b| match b {
+                    [v] => *v as f32,
+                    _ => u16::from_be_bytes([b[0], b[1]]) as f32,
+                })
+                .collect()
+        }
+        "P2" => String::from_utf8_lossy(&bytes[position..])
+            .split_whitespace()
+            .take(count)
+            .map(|v| v.parse::<f32>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| invalid("invalid PGM data"))?,
+        _ => return Err(invalid("not a grayscale PGM image")),
+    };
+    if values.len() != count {
+        return Err(invalid("truncated PGM data"));
+    }
+
+    Ok(Grayscale {
+        width,
+        height,
+        values: values.iter().map(|v| v / max_value as f32).collect(),
+    })
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    pub fn load_works() {
+        let path = std::env::temp_dir().join("raytracing-pgm-test.pgm");
+        {
+            let mut file = std::fs::File::create(&path).unwrap();
+            write!(file, "P5\n# a comment\n2 2\n255\n").unwrap();
+            file.write_all(&[0, 255, 51, 0]).unwrap();
+        }
+
+        let image = load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!((image.width, image.height), (2, 2));
+        assert_eq!(image.values, vec![0., 1., 0.2, 0.]);
+
+        let path = std::env::temp_dir().join("raytracing-pgm-plain-test.pgm");
+        std::fs::write(&path, "P2 3 1 10\n0 5 10\n").unwrap();
+        let image = load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(image.values, vec![0., 0.5, 1.]);
+    }
+}
@@ -0,0 +1,305 @@
+//! Lens and film effects applied to the rendered high dynamic range image.
+
+use crate::film::Image;
+use crate::random::Random;
+use crate::{Color, Degrees};
+use std::f32::consts::PI;
+
+/// A glow around bright pixels, from light scattered within the lens.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Bloom {
+    /// The fraction of the light above the threshold that is spread.
+    pub intensity: f32,
+    /// The luminance above which pixels bloom.
+    pub threshold: f32,
+    /// The standard deviation of the glow as a fraction of the image height.
+    pub radius: f32,
+}
+
+/// Star shaped streaks around bright pixels, from diffraction at the aperture blades.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Glare {
+    /// The fraction of the light above the threshold that is spread into streaks.
+    pub intensity: f32,
+    /// The luminance above which pixels cause streaks.
+    pub threshold: f32,
+    /// The number of streaks, evenly spread around each pixel.
+    pub streaks: usize,
+    /// The distance at which the streaks fade to `1/e`, as a fraction of the image height.
+    pub length: f32,
+    /// The angle of the first streak, counterclockwise from the right.
+    pub rotation: Degrees,
+}
+
+/// The post-processing effects, applied in the order of the fields.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct PostEffects {
+    pub bloom: Option<Bloom>,
+    pub glare: Option<Glare>,
+    /// The relative scale difference of the red and blue channels towards the image
+    /// corners, producing color fringes; zero disables the effect.
+    pub chromatic_aberration: f32,
+    /// The squared tangent of the angle under which the image corners are seen. Light
+    /// falls off following the cosine-fourth law; zero disables the effect.
+    pub vignette: f32,
+    /// The standard deviation of the multiplicative film grain; zero disables the effect.
+    pub grain: f32,
+}
+
+fn luminance(color: &Color) -> f32 {
+    0.2126 * color.x() + 0.7152 * color.y() + 0.0722 * color.z()
+}
+
+/// Adds the scaled pixels of another image of the same size.
+fn add(image: &mut Image, other: &Image, scale: f32) {
+    for y in 0..image.height {
+        for x in 0..image.width {
+            image.set(x, y, image.get(x, y) + scale * other.get(x, y));
+        }
+    }
+}
+
+/// Keeps the part of each pixel that exceeds the threshold luminance.
+fn bright_pass(image: &Image, threshold: f32) -> Image {
+    let mut bright = image.clone();
+    for pixel in bright.pixels_mut() {
+        let l = luminance(pixel);
+        *pixel = if l > threshold {
+            *pixel * ((l - threshold) / l)
+        } else {
+            Color::default()
+        };
+    }
+    bright
+}
+
+/// Blurs the image along one axis with a box of `2 * radius + 1` pixels.
+fn box_blur(image: &Image, radius: usize, horizontal: bool) -> Image {
+    let (length, lines) = if horizontal {
+        (image.width, image.height)
+    } else {
+        (image.height, image.width)
+    };
+    let at = |line: usize, i: usize| {
+        if horizontal {
+            (i, line)
+        } else {
+            (line, i)
+        }
+    };
+
+    let mut blurred = Image::new(image.width, image.height);
+    let scale = 1. / (2 * radius + 1) as f32;
+    for line in 0..lines {
+        // Keep a running sum over the window, clamping at the borders.
+        let get = |i: isize| {
+            let (x, y) = at(line, i.clamp(0, length as isize - 1) as usize);
+            image.get(x, y)
+        };
+        let mut sum = Color::default();
+        for i in -(radius as isize)..=radius as isize {
+            sum += get(i);
+        }
+        for i in 0..length {
+            let (x, y) = at(line, i);
+            blurred.set(x, y, sum * scale);
+            sum += get(i as isize + radius as isize + 1) - get(i as isize - radius as isize);
+        }
+    }
+    blurred
+}
+
+/// Approximates a Gaussian blur by three successive box blurs.
+fn gaussian_blur(image: &Image, sigma: f32) -> Image {
+    const PASSES: usize = 3;
+    let width = (12. * sigma * sigma / PASSES as f32 + 1.).sqrt();
+    let radius = ((width - 1.) / 2.).round().max(0.) as usize;
+    let mut blurred = image.clone();
+    for _ in 0..PASSES {
+        blurred = box_blur(&blurred, radius, true);
+        blurred = box_blur(&blurred, radius, false);
+    }
+    blurred
+}
+
+/// Smears the image along a direction with exponential falloff, using Kawase's
+/// streak filter which reaches further with every pass.
+fn streak(image: &Image, direction: (f32, f32), length: f32) -> Image {
+    const TAPS: usize = 4;
+    let attenuation = (-1. / length).exp();
+    let passes = ((3. * length).ln() / (TAPS as f32).ln()).ceil().max(1.) as u32;
+
+    let mut streaked = image.clone();
+    for pass in 0..passes {
+        let step = TAPS.pow(pass) as f32;
+        let weights = (0..TAPS)
+            .map(|tap| attenuation.powf(step * tap as f32))
+            .collect::<Vec<_>>();
+        let total: f32 = weights.iter().sum();
+
+        let source = streaked.clone();
+        for y in 0..image.height {
+            for x in 0..image.width {
+                let mut sum = Color::default();
+                for (tap, weight) in weights.iter().enumerate() {
+                    // Gather from the pixels the streak comes from.
+                    let offset = step * tap as f32;
+                    let sx = x as f32 + 0.5 - direction.0 * offset;
+                    let sy = y as f32 + 0.5 - direction.1 * offset;
+                    if sx < 0. || sy < 0. || sx >= image.width as f32 || sy >= image.height as f32 {
+                        continue;
+                    }
+                    sum += *weight * source.sample(sx, sy);
+                }
+                streaked.set(x, y, sum / total);
+            }
+        }
+    }
+    streaked
+}
+
+impl PostEffects {
+    pub fn is_empty(&self) -> bool {
+        *self == PostEffects::default()
+    }
+
+    pub fn apply(&self, image: &mut Image, rng: &Random) {
+        if let Some(bloom) = &self.bloom {
+            let bright = bright_pass(image, bloom.threshold);
+            let glow = gaussian_blur(&bright, bloom.radius * image.height as f32);
+            add(image, &glow, bloom.intensity);
+        }
+
+        if let Some(glare) = &self.glare {
+            let bright = bright_pass(image, glare.threshold);
+            let length = glare.length * image.height as f32;
+            let mut streaks = Image::new(image.width, image.height);
+            for i in 0..glare.streaks {
+                let angle =
+                    glare.rotation.0.to_radians() + 2. * PI * i as f32 / glare.streaks as f32;
+                let streak = streak(&bright, (angle.cos(), angle.sin()), length);
+                add(&mut streaks, &streak, 1. / glare.streaks as f32);
+            }
+            add(image, &streaks, glare.intensity);
+        }
+
+        let (cx, cy) = (0.5 * image.width as f32, 0.5 * image.height as f32);
+        let corner = (cx * cx + cy * cy).sqrt();
+
+        if self.chromatic_aberration != 0. {
+            // Magnify red and shrink blue around the image center.
+            let source = image.clone();
+            let scale = self.chromatic_aberration;
+            for y in 0..image.height {
+                for x in 0..image.width {
+                    let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+                    let at = |s: f32| source.sample(cx + dx * s, cy + dy * s);
+                    let (red, green, blue) = (at(1. - scale), source.get(x, y), at(1. + scale));
+                    image.set(x, y, Color::new(red.x(), green.y(), blue.z()));
+                }
+            }
+        }
+
+        if self.vignette != 0. {
+            for y in 0..image.height {
+                for x in 0..image.width {
+                    let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+                    let r2 = (dx * dx + dy * dy) / (corner * corner);
+                    // cos⁴θ = 1 / (1 + tan²θ)², with tan²θ growing quadratically towards the corners.
+                    let falloff = 1. / (1. + self.vignette * r2).powi(2);
+                    image.set(x, y, image.get(x, y) * falloff);
+                }
+            }
+        }
+
+        if self.grain != 0. {
+            for pixel in image.pixels_mut() {
+                // A sum of uniform values approximates a normal distribution.
+                let noise = (0..4).map(|_| rng.sample()).sum::<f32>() - 2.;
+                *pixel *= (1. + self.grain * noise * 3f32.sqrt()).max(0.);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    /// A dark image with a single bright pixel in the center.
+    fn point_light() -> Image {
+        let mut image = Image::new(21, 21);
+        for pixel in image.pixels_mut() {
+            *pixel = Color::new(0.1, 0.1, 0.1);
+        }
+        image.set(10, 10, Color::new(50., 50., 50.));
+        image
+    }
+
+    #[test]
+    pub fn bloom_spreads_bright_pixels() {
+        let effects = PostEffects {
+            bloom: Some(Bloom {
+                intensity: 0.5,
+                threshold: 1.,
+                radius: 0.1,
+            }),
+            ..PostEffects::default()
+        };
+        let mut image = point_light();
+        effects.apply(&mut image, &Random::default());
+        assert!(image.get(12, 10).x() > 0.2);
+        assert!(image.get(12, 10).x() > image.get(14, 10).x());
+        assert!(image.get(0, 0).x() < 0.11);
+    }
+
+    #[test]
+    pub fn glare_streaks_along_directions() {
+        let effects = PostEffects {
+            glare: Some(Glare {
+                intensity: 1.,
+                threshold: 1.,
+                streaks: 2,
+                length: 0.2,
+                rotation: Degrees(0.),
+            }),
+            ..PostEffects::default()
+        };
+        let mut image = point_light();
+        effects.apply(&mut image, &Random::default());
+        assert!(image.get(14, 10).x() > 1.);
+        assert!(image.get(6, 10).x() > 1.);
+        assert!(image.get(10, 14).x() < 0.2);
+    }
+
+    #[test]
+    pub fn vignette_and_chromatic_aberration_keep_the_center() {
+        let effects = PostEffects {
+            vignette: 1.,
+            chromatic_aberration: 0.05,
+            ..PostEffects::default()
+        };
+        let mut image = point_light();
+        effects.apply(&mut image, &Random::default());
+        assert!((image.get(10, 10).y() - 50.).abs() < 1e-3);
+        assert!((image.get(0, 0).y() - 0.1 / 4.).abs() < 0.005);
+        // The red channel is magnified, moving the light's red outwards.
+        assert!(image.get(11, 10).x() > image.get(11, 10).z());
+    }
+
+    #[test]
+    pub fn grain_keeps_the_average() {
+        let effects = PostEffects {
+            grain: 0.1,
+            ..PostEffects::default()
+        };
+        let mut image = Image::new(64, 64);
+        for pixel in image.pixels_mut() {
+            *pixel = Color::new(1., 1., 1.);
+        }
+        effects.apply(&mut image, &Random::default());
+        let mean = image.pixels_mut().map(|p| p.x()).sum::<f32>() / (64. * 64.);
+        assert!((mean - 1.).abs() < 0.01);
+        assert!(image.get(0, 0) != image.get(1, 0));
+    }
+}
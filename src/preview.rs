@@ -0,0 +1,275 @@
+//! A window showing the image while it renders, in which the camera can be moved around.
+
+use crate::camera::View;
+use crate::cli::Options;
+use crate::film::{Film, Image};
+use crate::random::Random;
+use crate::sampler::SamplerPtr;
+use crate::scenes::Scene;
+use crate::spectrum::SpectralSampler;
+use crate::tonemap::ColorPipeline;
+use crate::{
+    create_camera, develop, focus_distance, write_image, Renderer, Vec3, OUTPUT_FILE,
+    SAMPLES_PER_PIXEL, VIEW,
+};
+use minifb::{Key, MouseButton, MouseMode, Window, WindowOptions};
+use std::io::Error;
+
+/// The number of samples per pixel added before the window is updated.
+const SAMPLES_PER_PASS: usize = 1;
+
+/// The rotation in radians per pixel the mouse is dragged.
+const ORBIT_SPEED: f32 = 0.01;
+/// The movement per pixel the mouse is dragged, relative to the distance to the point looked at.
+const PAN_SPEED: f32 = 0.002;
+/// The zoom in stops per step of the mouse wheel.
+const ZOOM_SPEED: f32 = 0.1;
+/// The mouse movement in pixels a key press counts as.
+const KEY_STEP: f32 = 10.;
+
+/// Renders the image progressively while showing it in a window, starting over whenever the
+/// camera is moved. The image is written once all samples of a view are taken.
+pub fn render(
+    options: &Options,
+    scene: &Scene,
+    sampler: SamplerPtr,
+    spectral: Option<&SpectralSampler>,
+    mut distance: f32,
+    pipeline: &ColorPipeline,
+    rng: &Random,
+) -> std::io::Result<()> {
+    let (width, height) = options.size;
+    let aspect_ratio = width as f32 / height as f32;
+    let mut preview = Preview::new(width, height)?;
+    let mut view = VIEW;
+
+    loop {
+        let camera = create_camera(options, &view, aspect_ratio, distance, scene.shutter)?;
+        let renderer = Renderer {
+            scene,
+            camera: camera.as_ref(),
+            sampler: sampler.clone(),
+            spectral,
+            size: options.size,
+            heatmap: None,
+        };
+
+        let moved = match preview.accumulate(&renderer, &view, options, pipeline, rng)? {
+            Some(moved) => moved,
+            None => return Ok(()),
+        };
+
+        // Keep the previous distance when there is nothing to focus on.
+        let focus = options.focus.unwrap_or(scene.focus);
+        distance = focus_distance(focus, options, &moved, scene, rng).unwrap_or(distance);
+        view = moved;
+    }
+}
+
+/// The window and the camera controls.
+struct Preview {
+    window: Window,
+    buffer: Vec<u32>,
+    /// The mouse position when the window was last updated.
+    mouse: Option<(f32, f32)>,
+}
+
+impl Preview {
+    fn new(width: usize, height: usize) -> std::io::Result<Self> {
+        let mut window = Window::new("raytracing", width, height, WindowOptions::default())
+            .map_err(|error| Error::other(format!("cannot open the preview window: {}", error)))?;
+        window.set_target_fps(60);
+        Ok(Self {
+            window,
+            buffer: vec![0; width * height],
+            mouse: None,
+        })
+    }
+
+    fn is_open(&self) -> bool {
+        self.window.is_open() && !self.window.is_key_down(Key::Escape)
+    }
+
+    /// Renders the view of the renderer pass by pass, then keeps showing the result.
+    ///
+    /// Returns the view to render next when the camera is moved, or `None` when the window
+    /// is closed.
+    fn accumulate(
+        &mut self,
+        renderer: &Renderer,
+        view: &View,
+        options: &Options,
+        pipeline: &ColorPipeline,
+        rng: &Random,
+    ) -> std::io::Result<Option<View>> {
+        let (width, height) = options.size;
+        let mut film = Film::new(width, height, options.filter);
+        for first in (0..SAMPLES_PER_PIXEL).step_by(SAMPLES_PER_PASS) {
+            let samples = first..(first + SAMPLES_PER_PASS).min(SAMPLES_PER_PIXEL);
+            for j in 0..height {
+                for (tile, _) in &renderer.render_row(&film, j, samples.clone()) {
+                    film.add_tile(tile);
+                }
+            }
+
+            self.show(&develop(&film, &options.post_effects, rng), pipeline)?;
+            if !self.is_open() {
+                return Ok(None);
+            }
+            if let Some(moved) = self.moved_view(view) {
+                return Ok(Some(moved));
+            }
+        }
+
+        write_image(
+            OUTPUT_FILE,
+            &develop(&film, &options.post_effects, rng),
+            pipeline,
+        )?;
+        while self.is_open() {
+            self.window.update();
+            if let Some(moved) = self.moved_view(view) {
+                return Ok(Some(moved));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Displays the image, with its rows flipped to go from top to bottom.
+    fn show(&mut self, image: &Image, pipeline: &ColorPipeline) -> std::io::Result<()> {
+        for (j, row) in self.buffer.chunks_mut(image.width).enumerate() {
+            for (i, pixel) in row.iter_mut().enumerate() {
+                let [r, g, b] = pipeline.quantize(&image.get(i, image.height - 1 - j));
+                *pixel = u32::from_be_bytes([0, r, g, b]);
+            }
+        }
+        self.window
+            .update_with_buffer(&self.buffer, image.width, image.height)
+            .map_err(|error| Error::other(error.to_string()))
+    }
+
+    /// Applies the mouse and keyboard input since the last update to the view.
+    ///
+    /// Returns the moved view, or `None` if the camera did not move.
+    fn moved_view(&mut self, view: &View) -> Option<View> {
+        let mouse = self.window.get_mouse_pos(MouseMode::Pass);
+        let (dx, dy) = match (self.mouse, mouse) {
+            (Some((x0, y0)), Some((x1, y1))) => (x1 - x0, y1 - y0),
+            _ => (0., 0.),
+        };
+        self.mouse = mouse;
+
+        let (mut orbit, mut pan, mut zoom) = ((0., 0.), (0., 0.), 0.);
+        if self.window.get_mouse_down(MouseButton::Left) {
+            orbit = (dx, dy);
+        } else if self.window.get_mouse_down(MouseButton::Right)
+            || self.window.get_mouse_down(MouseButton::Middle)
+        {
+            pan = (dx, dy);
+        }
+        if let Some((_, scroll)) = self.window.get_scroll_wheel() {
+            zoom += scroll.signum();
+        }
+        for key in self.window.get_keys() {
+            match key {
+                Key::Left => orbit.0 -= KEY_STEP,
+                Key::Right => orbit.0 += KEY_STEP,
+                Key::Up => orbit.1 -= KEY_STEP,
+                Key::Down => orbit.1 += KEY_STEP,
+                Key::A => pan.0 -= KEY_STEP,
+                Key::D => pan.0 += KEY_STEP,
+                Key::W => pan.1 -= KEY_STEP,
+                Key::S => pan.1 += KEY_STEP,
+                Key::Equal | Key::NumPadPlus => zoom += 1.,
+                Key::Minus | Key::NumPadMinus => zoom -= 1.,
+                _ => {}
+            }
+        }
+
+        if orbit == (0., 0.) && pan == (0., 0.) && zoom == 0. {
+            return None;
+        }
+        Some(
+            view.orbit(-orbit.0 * ORBIT_SPEED, orbit.1 * ORBIT_SPEED)
+                .pan(-pan.0 * PAN_SPEED, pan.1 * PAN_SPEED)
+                .zoom(zoom * ZOOM_SPEED),
+        )
+    }
+}
+
+impl View {
+    /// Rotates the camera around the point looked at, by `yaw` radians around the up direction
+    /// and `pitch` radians towards it, stopping just short of looking straight down or up.
+    fn orbit(&self, yaw: f32, pitch: f32) -> View {
+        let offset = self.look_from - self.look_at;
+        let distance = offset.len();
+        let up = self.view_up.as_unit_vector();
+
+        let limit = 0.49 * std::f32::consts::PI;
+        let elevation = ((offset.dot(&up) / distance).asin() + pitch).clamp(-limit, limit);
+        let horizontal = (offset - up * offset.dot(&up)).as_unit_vector();
+        let horizontal = horizontal * yaw.cos() + up.cross(&horizontal) * yaw.sin();
+
+        let offset = (horizontal * elevation.cos() + up * elevation.sin()) * distance;
+        View {
+            look_from: self.look_at + offset,
+            ..*self
+        }
+    }
+
+    /// Moves the camera and the point looked at sideways and up, relative to their distance.
+    fn pan(&self, right: f32, up: f32) -> View {
+        let distance = (self.look_at - self.look_from).len();
+        let forward = self.forward();
+        let right_direction = forward.cross(&self.view_up).as_unit_vector();
+        let up_direction = right_direction.cross(&forward);
+        let shift: Vec3 = (right_direction * right + up_direction * up) * distance;
+        View {
+            look_from: self.look_from + shift,
+            look_at: self.look_at + shift,
+            ..*self
+        }
+    }
+
+    /// Moves the camera towards the point looked at, halving the distance every stop.
+    fn zoom(&self, stops: f32) -> View {
+        View {
+            look_from: self.look_at + (self.look_from - self.look_at) * 0.5f32.powf(stops),
+            ..*self
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    fn assert_near(a: Vec3, b: Vec3) {
+        assert!((a - b).len() < 1e-4, "{} != {}", a, b);
+    }
+
+    #[test]
+    pub fn view_moves_work() {
+        let orbited = VIEW.orbit(0.3, 0.2);
+        assert_eq!(orbited.look_at, VIEW.look_at);
+        let distance = (VIEW.look_from - VIEW.look_at).len();
+        assert!(((orbited.look_from - orbited.look_at).len() - distance).abs() < 1e-4);
+        assert_near(
+            orbited.orbit(-0.3, 0.).orbit(0., -0.2).look_from,
+            VIEW.look_from,
+        );
+
+        let panned = VIEW.pan(0.1, 0.);
+        assert_near(
+            panned.look_at - VIEW.look_at,
+            panned.look_from - VIEW.look_from,
+        );
+        assert!((panned.forward().dot(&VIEW.forward()) - 1.).abs() < 1e-5);
+
+        let zoomed = VIEW.zoom(1.);
+        assert_near(
+            zoomed.look_from - zoomed.look_at,
+            (VIEW.look_from - VIEW.look_at).half(),
+        );
+    }
+}
@@ -1,9 +1,17 @@
+mod bounding_box;
 mod color;
+mod onb;
 mod point3;
+mod quaternion;
 mod ray;
+mod transform;
 mod vec3;
 
+pub use bounding_box::BoundingBox;
 pub use color::Color;
+pub use onb::Onb;
 pub use point3::Point3;
+pub use quaternion::Quaternion;
 pub use ray::Ray;
+pub use transform::{AnimatedTransform, Keyframe, Mat4, Transform};
 pub use vec3::Vec3;
@@ -0,0 +1,92 @@
+use crate::{Point3, Ray, GRID_SCALE};
+use space_partitioning::quadtree::AABB;
+
+/// An axis-aligned bounding box in world space.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BoundingBox {
+    pub min: Point3,
+    pub max: Point3,
+}
+
+impl BoundingBox {
+    pub fn new(min: Point3, max: Point3) -> Self {
+        Self { min, max }
+    }
+
+    /// A box that contains nothing; the neutral element for [`BoundingBox::union`].
+    pub fn empty() -> Self {
+        Self {
+            min: Point3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+            max: Point3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+        }
+    }
+
+    /// A box containing all of space, used by unbounded objects such as planes.
+    pub fn infinite() -> Self {
+        Self {
+            min: Point3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+            max: Point3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+        }
+    }
+
+    /// Indicates whether the box has finite extents.
+    pub fn is_finite(&self) -> bool {
+        self.min
+            .e
+            .iter()
+            .chain(self.max.e.iter())
+            .all(|c| c.is_finite())
+    }
+
+    pub fn union(&self, other: &BoundingBox) -> Self {
+        Self {
+            min: Point3::new(
+                self.min.x().min(other.min.x()),
+                self.min.y().min(other.min.y()),
+                self.min.z().min(other.min.z()),
+            ),
+            max: Point3::new(
+                self.max.x().max(other.max.x()),
+                self.max.y().max(other.max.y()),
+                self.max.z().max(other.max.z()),
+            ),
+        }
+    }
+
+    /// Intersects the ray with the box using the slab method.
+    ///
+    /// Returns the range of the ray parameter within the box, clipped to `t_min..t_max`.
+    pub fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<(f32, f32)> {
+        let mut t0 = t_min;
+        let mut t1 = t_max;
+        for axis in 0..3 {
+            let inv_d = 1. / r.direction.e[axis];
+            let mut t_near = (self.min.e[axis] - r.origin.e[axis]) * inv_d;
+            let mut t_far = (self.max.e[axis] - r.origin.e[axis]) * inv_d;
+            if inv_d < 0. {
+                std::mem::swap(&mut t_near, &mut t_far);
+            }
+            // NaN-safe: comparisons with NaN keep the previous value.
+            if t_near > t0 {
+                t0 = t_near;
+            }
+            if t_far < t1 {
+                t1 = t_far;
+            }
+            if t1 <= t0 {
+                return None;
+            }
+        }
+        Some((t0, t1))
+    }
+
+    /// Converts the box into the (X/Z) bounds used by the quadtree.
+    pub fn to_aabb(self) -> AABB {
+        AABB::new(
+            (self.min.x() * GRID_SCALE).floor() as i32,
+            (self.min.z() * GRID_SCALE).floor() as i32,
+            (self.max.x() * GRID_SCALE).ceil() as i32,
+            (self.max.z() * GRID_SCALE).ceil() as i32,
+        )
+    }
+}
@@ -0,0 +1,71 @@
+use crate::Vec3;
+
+/// An orthonormal basis, used to express directions relative to a surface normal.
+///
+/// The `w` axis corresponds to the normal; local coordinates therefore have
+/// their `z` component along the normal, which is the convention used by the
+/// microfacet and phase function code.
+#[derive(Debug, Copy, Clone)]
+pub struct Onb {
+    pub u: Vec3,
+    pub v: Vec3,
+    pub w: Vec3,
+}
+
+impl Onb {
+    /// Builds a basis around the specified (unit length) normal.
+    ///
+    /// Uses the branchless construction from Duff et al.,
+    /// "Building an Orthonormal Basis, Revisited" (2017).
+    pub fn from_w(normal: &Vec3) -> Self {
+        let n = *normal;
+        let sign = 1f32.copysign(n.z());
+        let a = -1. / (sign + n.z());
+        let b = n.x() * n.y() * a;
+        let u = Vec3::new(1. + sign * n.x() * n.x() * a, sign * b, -sign * n.x());
+        let v = Vec3::new(b, sign + n.y() * n.y() * a, -n.y());
+        Self { u, v, w: n }
+    }
+
+    /// Transforms a world space direction into the local frame.
+    #[inline]
+    pub fn world_to_local(&self, a: &Vec3) -> Vec3 {
+        Vec3::new(a.dot(&self.u), a.dot(&self.v), a.dot(&self.w))
+    }
+
+    /// Transforms a local direction into world space.
+    #[inline]
+    pub fn local_to_world(&self, a: &Vec3) -> Vec3 {
+        a.x() * self.u + a.y() * self.v + a.z() * self.w
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    pub fn basis_is_orthonormal() {
+        for n in [
+            Vec3::new(0., 0., 1.),
+            Vec3::new(0., 0., -1.),
+            Vec3::new(1., 2., 3.).as_unit_vector(),
+            Vec3::new(-3., 0.5, -0.1).as_unit_vector(),
+        ] {
+            let onb = Onb::from_w(&n);
+            assert!((onb.u.len() - 1.).abs() < 1e-5);
+            assert!((onb.v.len() - 1.).abs() < 1e-5);
+            assert!(onb.u.dot(&onb.v).abs() < 1e-5);
+            assert!(onb.u.dot(&onb.w).abs() < 1e-5);
+            assert!(onb.v.dot(&onb.w).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    pub fn round_trip_works() {
+        let onb = Onb::from_w(&Vec3::new(1., 1., 0.).as_unit_vector());
+        let a = Vec3::new(0.3, -2., 5.);
+        let b = onb.local_to_world(&onb.world_to_local(&a));
+        assert!((a - b).len() < 1e-5);
+    }
+}
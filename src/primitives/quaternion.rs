@@ -0,0 +1,88 @@
+use crate::primitives::Mat4;
+use crate::{Degrees, Vec3};
+
+/// A unit quaternion representing a rotation.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Quaternion {
+    pub w: f32,
+    pub v: Vec3,
+}
+
+impl Quaternion {
+    pub const IDENTITY: Quaternion = Quaternion {
+        w: 1.,
+        v: Vec3::new(0., 0., 0.),
+    };
+
+    /// A counter-clockwise rotation about the specified axis.
+    pub fn from_axis_angle(axis: Vec3, angle: Degrees) -> Self {
+        let (sin, cos) = (angle.0.to_radians() * 0.5).sin_cos();
+        Self {
+            w: cos,
+            v: axis.as_unit_vector() * sin,
+        }
+    }
+
+    pub fn dot(&self, other: &Quaternion) -> f32 {
+        self.w * other.w + self.v.dot(&other.v)
+    }
+
+    /// Spherically interpolates between two rotations along the shortest arc.
+    pub fn slerp(&self, other: &Quaternion, t: f32) -> Self {
+        let mut cos_theta = self.dot(other);
+        let mut other = *other;
+        if cos_theta < 0. {
+            cos_theta = -cos_theta;
+            other = Quaternion {
+                w: -other.w,
+                v: -other.v,
+            };
+        }
+
+        let (a, b) = if cos_theta > 0.9995 {
+            // Nearly parallel; fall back to linear interpolation.
+            (1. - t, t)
+        } else {
+            let theta = cos_theta.acos();
+            let sin_theta = theta.sin();
+            (
+                ((1. - t) * theta).sin() / sin_theta,
+                (t * theta).sin() / sin_theta,
+            )
+        };
+
+        let w = a * self.w + b * other.w;
+        let v = self.v * a + other.v * b;
+        let len = (w * w + v.len_squared()).sqrt();
+        Self {
+            w: w / len,
+            v: v / len,
+        }
+    }
+
+    /// Converts the rotation into a rotation matrix.
+    pub fn to_matrix(self) -> Mat4 {
+        let (w, x, y, z) = (self.w, self.v.x(), self.v.y(), self.v.z());
+        Mat4::new([
+            [
+                1. - 2. * (y * y + z * z),
+                2. * (x * y - w * z),
+                2. * (x * z + w * y),
+                0.,
+            ],
+            [
+                2. * (x * y + w * z),
+                1. - 2. * (x * x + z * z),
+                2. * (y * z - w * x),
+                0.,
+            ],
+            [
+                2. * (x * z - w * y),
+                2. * (y * z + w * x),
+                1. - 2. * (x * x + y * y),
+                0.,
+            ],
+            [0., 0., 0., 1.],
+        ])
+    }
+}
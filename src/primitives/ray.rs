@@ -1,3 +1,5 @@
+use crate::material::Dispersion;
+use crate::stats;
 use crate::{Point3, Vec3, GRID_SCALE};
 use num_traits::MulAdd;
 use space_partitioning::intersections::IntersectsWith;
@@ -8,6 +10,15 @@ pub struct Ray {
     pub origin: Point3,
     pub direction: Vec3,
     inv_direction: Vec3,
+    /// The wavelength in nanometers this ray is restricted to, if any.
+    ///
+    /// This is set once a ray passed through a wavelength dependent material
+    /// and is carried along to all subsequent rays of the path.
+    pub wavelength: Option<f32>,
+    /// The wavelengths in nanometers carried by the color channels in spectral mode.
+    pub wavelengths: Option<Vec3>,
+    /// The point in time at which the ray was emitted, used for motion blur.
+    pub time: f32,
 }
 
 impl Ray {
@@ -17,9 +28,46 @@ impl Ray {
             origin,
             direction: unit,
             inv_direction: Vec3::new(1.0 / unit.x(), 1.0 / unit.y(), 1.0 / unit.z()),
+            wavelength: None,
+            wavelengths: None,
+            time: 0.,
         }
     }
 
+    /// Creates a ray continuing the path of this ray, e.g. after scattering.
+    pub fn spawn(&self, origin: Point3, direction: Vec3) -> Self {
+        Self {
+            wavelength: self.wavelength,
+            wavelengths: self.wavelengths,
+            time: self.time,
+            ..Self::new(origin, direction)
+        }
+    }
+
+    /// Restricts the ray to the specified wavelength in nanometers.
+    pub fn with_wavelength(mut self, wavelength: f32) -> Self {
+        self.wavelength = Some(wavelength);
+        self
+    }
+
+    /// Makes the ray carry the specified wavelengths in nanometers in its color channels.
+    pub fn with_wavelengths(mut self, wavelengths: Vec3) -> Self {
+        self.wavelengths = Some(wavelengths);
+        self
+    }
+
+    /// The wavelengths in nanometers represented by the color channels.
+    pub fn channel_wavelengths(&self) -> Vec3 {
+        let [r, g, b] = Dispersion::RGB_WAVELENGTHS;
+        self.wavelengths.unwrap_or_else(|| Vec3::new(r, g, b))
+    }
+
+    /// Sets the point in time at which the ray was emitted.
+    pub fn with_time(mut self, time: f32) -> Self {
+        self.time = time;
+        self
+    }
+
     /// Linearly interpolates the point that is `t` units
     /// away from the ray's origin, along the ray's direction.
     #[inline]
@@ -32,6 +80,7 @@ impl Ray {
 impl IntersectsWith<AABB> for Ray {
     fn intersects_with(&self, other: &AABB) -> bool {
         // https://gamedev.stackexchange.com/a/18459/10433
+        stats::count_node_visit();
 
         let t1 = (other.tl.x as f32 - self.origin.x() * GRID_SCALE) * self.inv_direction.x();
         let t2 = (other.br.x as f32 - self.origin.x() * GRID_SCALE) * self.inv_direction.x();
@@ -43,11 +92,7 @@ impl IntersectsWith<AABB> for Ray {
 
         // if tmax < 0, ray (line) is intersecting AABB, but the whole AABB is behind us
         // if tmin > tmax, ray doesn't intersect AABB
-        if (tmax < 0.) | (tmin > tmax) {
-            return false;
-        }
-
-        return true;
+        !((tmax < 0.) | (tmin > tmax))
     }
 }
 
@@ -61,10 +106,10 @@ pub mod test {
         let direction = Vec3::new(1., 1., 1.);
         let t = 2.;
 
-        let ray = Ray::new(origin.clone(), direction.clone());
+        let ray = Ray::new(origin, direction);
         let calculated_at = ray.at(t);
 
-        let expected_at = origin + t * direction;
+        let expected_at = origin + t * direction.as_unit_vector();
         assert_eq!(calculated_at, expected_at);
     }
 }
@@ -0,0 +1,486 @@
+use crate::primitives::{BoundingBox, Quaternion};
+use crate::{Degrees, Point3, Vec3};
+use std::ops::Mul;
+
+/// A row-major 4x4 matrix.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Mat4 {
+    pub m: [[f32; 4]; 4],
+}
+
+impl Mat4 {
+    pub const IDENTITY: Mat4 = Mat4 {
+        m: [
+            [1., 0., 0., 0.],
+            [0., 1., 0., 0.],
+            [0., 0., 1., 0.],
+            [0., 0., 0., 1.],
+        ],
+    };
+
+    pub const fn new(m: [[f32; 4]; 4]) -> Self {
+        Self { m }
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut t = Self::IDENTITY;
+        for (i, row) in self.m.iter().enumerate() {
+            for (j, value) in row.iter().enumerate() {
+                t.m[j][i] = *value;
+            }
+        }
+        t
+    }
+
+    /// Inverts the matrix using Gauss-Jordan elimination with partial pivoting.
+    ///
+    /// Returns `None` if the matrix is singular.
+    pub fn inverse(&self) -> Option<Self> {
+        let mut a = self.m;
+        let mut inv = Self::IDENTITY.m;
+
+        for col in 0..4 {
+            let pivot = (col..4)
+                .max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))
+                .unwrap_or(col);
+            if a[pivot][col].abs() < 1e-12 {
+                return None;
+            }
+            a.swap(col, pivot);
+            inv.swap(col, pivot);
+
+            let scale = 1. / a[col][col];
+            for j in 0..4 {
+                a[col][j] *= scale;
+                inv[col][j] *= scale;
+            }
+
+            for row in 0..4 {
+                if row == col {
+                    continue;
+                }
+                let factor = a[row][col];
+                for j in 0..4 {
+                    a[row][j] -= factor * a[col][j];
+                    inv[row][j] -= factor * inv[col][j];
+                }
+            }
+        }
+
+        Some(Self::new(inv))
+    }
+
+    #[inline]
+    pub fn transform_point(&self, p: &Point3) -> Point3 {
+        let m = &self.m;
+        let x = m[0][0] * p.x() + m[0][1] * p.y() + m[0][2] * p.z() + m[0][3];
+        let y = m[1][0] * p.x() + m[1][1] * p.y() + m[1][2] * p.z() + m[1][3];
+        let z = m[2][0] * p.x() + m[2][1] * p.y() + m[2][2] * p.z() + m[2][3];
+        let w = m[3][0] * p.x() + m[3][1] * p.y() + m[3][2] * p.z() + m[3][3];
+        if w == 1. {
+            Point3::new(x, y, z)
+        } else {
+            Point3::new(x / w, y / w, z / w)
+        }
+    }
+
+    #[inline]
+    pub fn transform_vector(&self, v: &Vec3) -> Vec3 {
+        let m = &self.m;
+        Vec3::new(
+            m[0][0] * v.x() + m[0][1] * v.y() + m[0][2] * v.z(),
+            m[1][0] * v.x() + m[1][1] * v.y() + m[1][2] * v.z(),
+            m[2][0] * v.x() + m[2][1] * v.y() + m[2][2] * v.z(),
+        )
+    }
+}
+
+impl Mul for Mat4 {
+    type Output = Mat4;
+
+    fn mul(self, rhs: Mat4) -> Mat4 {
+        let mut result = Mat4::new([[0.; 4]; 4]);
+        for i in 0..4 {
+            for j in 0..4 {
+                result.m[i][j] = (0..4).map(|k| self.m[i][k] * rhs.m[k][j]).sum();
+            }
+        }
+        result
+    }
+}
+
+/// An affine transformation together with its inverse.
+///
+/// The normal matrix is the transposed inverse, which keeps normals
+/// perpendicular to surfaces under non-uniform scaling.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Transform {
+    matrix: Mat4,
+    inverse: Mat4,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Transform {
+    /// Creates a transform from a matrix; returns `None` if it is not invertible.
+    pub fn new(matrix: Mat4) -> Option<Self> {
+        Some(Self {
+            matrix,
+            inverse: matrix.inverse()?,
+        })
+    }
+
+    pub fn identity() -> Self {
+        Self {
+            matrix: Mat4::IDENTITY,
+            inverse: Mat4::IDENTITY,
+        }
+    }
+
+    pub fn translation(offset: Vec3) -> Self {
+        let (x, y, z) = (offset.x(), offset.y(), offset.z());
+        Self {
+            matrix: Mat4::new([
+                [1., 0., 0., x],
+                [0., 1., 0., y],
+                [0., 0., 1., z],
+                [0., 0., 0., 1.],
+            ]),
+            inverse: Mat4::new([
+                [1., 0., 0., -x],
+                [0., 1., 0., -y],
+                [0., 0., 1., -z],
+                [0., 0., 0., 1.],
+            ]),
+        }
+    }
+
+    /// A (possibly non-uniform) scaling; all factors must be non-zero.
+    pub fn scaling(factors: Vec3) -> Self {
+        let (x, y, z) = (factors.x(), factors.y(), factors.z());
+        Self {
+            matrix: Mat4::new([
+                [x, 0., 0., 0.],
+                [0., y, 0., 0.],
+                [0., 0., z, 0.],
+                [0., 0., 0., 1.],
+            ]),
+            inverse: Mat4::new([
+                [1. / x, 0., 0., 0.],
+                [0., 1. / y, 0., 0.],
+                [0., 0., 1. / z, 0.],
+                [0., 0., 0., 1.],
+            ]),
+        }
+    }
+
+    /// A counter-clockwise rotation about the specified axis.
+    pub fn rotation(axis: Vec3, angle: Degrees) -> Self {
+        let a = axis.as_unit_vector();
+        let (sin, cos) = angle.0.to_radians().sin_cos();
+        let t = 1. - cos;
+        let (x, y, z) = (a.x(), a.y(), a.z());
+        let matrix = Mat4::new([
+            [
+                t * x * x + cos,
+                t * x * y - sin * z,
+                t * x * z + sin * y,
+                0.,
+            ],
+            [
+                t * x * y + sin * z,
+                t * y * y + cos,
+                t * y * z - sin * x,
+                0.,
+            ],
+            [
+                t * x * z - sin * y,
+                t * y * z + sin * x,
+                t * z * z + cos,
+                0.,
+            ],
+            [0., 0., 0., 1.],
+        ]);
+
+        // Rotations are orthogonal, so the inverse is the transpose.
+        Self {
+            matrix,
+            inverse: matrix.transpose(),
+        }
+    }
+
+    /// Returns the transform applying `self` first, followed by `next`.
+    pub fn then(&self, next: &Transform) -> Self {
+        Self {
+            matrix: next.matrix * self.matrix,
+            inverse: self.inverse * next.inverse,
+        }
+    }
+
+    #[inline]
+    pub fn point(&self, p: &Point3) -> Point3 {
+        self.matrix.transform_point(p)
+    }
+
+    /// Transforms a surface normal using the normal matrix; the result is not normalized.
+    #[inline]
+    pub fn normal(&self, n: &Vec3) -> Vec3 {
+        // Multiplying with the transposed inverse without building it.
+        let m = &self.inverse.m;
+        Vec3::new(
+            m[0][0] * n.x() + m[1][0] * n.y() + m[2][0] * n.z(),
+            m[0][1] * n.x() + m[1][1] * n.y() + m[2][1] * n.z(),
+            m[0][2] * n.x() + m[1][2] * n.y() + m[2][2] * n.z(),
+        )
+    }
+
+    #[inline]
+    pub fn inverse_point(&self, p: &Point3) -> Point3 {
+        self.inverse.transform_point(p)
+    }
+
+    #[inline]
+    pub fn inverse_vector(&self, v: &Vec3) -> Vec3 {
+        self.inverse.transform_vector(v)
+    }
+
+    /// Transforms a bounding box, returning the box around its eight transformed corners.
+    pub fn bounding_box(&self, bounds: &BoundingBox) -> BoundingBox {
+        if !bounds.is_finite() {
+            return BoundingBox::infinite();
+        }
+
+        let mut result = BoundingBox::empty();
+        for i in 0..8 {
+            let corner = Point3::new(
+                if i & 1 == 0 {
+                    bounds.min.x()
+                } else {
+                    bounds.max.x()
+                },
+                if i & 2 == 0 {
+                    bounds.min.y()
+                } else {
+                    bounds.max.y()
+                },
+                if i & 4 == 0 {
+                    bounds.min.z()
+                } else {
+                    bounds.max.z()
+                },
+            );
+            let p = self.point(&corner);
+            result = result.union(&BoundingBox::new(p, p));
+        }
+        result
+    }
+}
+
+/// The decomposed components of a transform at one point in time.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Keyframe {
+    pub translation: Vec3,
+    pub rotation: Quaternion,
+    pub scale: Vec3,
+}
+
+impl Default for Keyframe {
+    fn default() -> Self {
+        Self {
+            translation: Vec3::default(),
+            rotation: Quaternion::IDENTITY,
+            scale: Vec3::new(1., 1., 1.),
+        }
+    }
+}
+
+impl Keyframe {
+    /// Builds the transform scaling first, then rotating and finally translating.
+    pub fn to_transform(self) -> Transform {
+        let rotation = self.rotation.to_matrix();
+        Transform::scaling(self.scale)
+            .then(&Transform {
+                matrix: rotation,
+                inverse: rotation.transpose(),
+            })
+            .then(&Transform::translation(self.translation))
+    }
+}
+
+/// A transform interpolated between two keyframes over time.
+///
+/// Translation and scale are interpolated linearly, rotations spherically.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AnimatedTransform {
+    start: Keyframe,
+    end: Keyframe,
+    time0: f32,
+    time1: f32,
+}
+
+impl AnimatedTransform {
+    /// The number of steps used to approximate the bounds swept over time.
+    const BOUNDS_STEPS: usize = 32;
+
+    pub fn new(start: Keyframe, time0: f32, end: Keyframe, time1: f32) -> Self {
+        Self {
+            start,
+            end,
+            time0,
+            time1,
+        }
+    }
+
+    /// Determines the transform at the specified time, clamped to the keyframes.
+    pub fn at(&self, time: f32) -> Transform {
+        let t = if self.time1 > self.time0 {
+            ((time - self.time0) / (self.time1 - self.time0)).clamp(0., 1.)
+        } else {
+            0.
+        };
+
+        Keyframe {
+            translation: self.start.translation * (1. - t) + self.end.translation * t,
+            rotation: self.start.rotation.slerp(&self.end.rotation, t),
+            scale: self.start.scale * (1. - t) + self.end.scale * t,
+        }
+        .to_transform()
+    }
+
+    /// Determines the bounds swept by a box over the whole animation.
+    ///
+    /// The box is sampled at regular time steps, and each sample is padded by the
+    /// farthest a point of the box can move in half a step, so that the curved paths
+    /// of rotating points between the samples stay inside.
+    pub fn bounding_box(&self, bounds: &BoundingBox) -> BoundingBox {
+        let padding = self.max_speed(bounds) / (2 * Self::BOUNDS_STEPS) as f32;
+        let padding = Vec3::new(padding, padding, padding);
+        (0..=Self::BOUNDS_STEPS)
+            .map(|i| {
+                let time =
+                    self.time0 + (self.time1 - self.time0) * i as f32 / Self::BOUNDS_STEPS as f32;
+                let sample = self.at(time).bounding_box(bounds);
+                BoundingBox::new(sample.min - padding, sample.max + padding)
+            })
+            .fold(BoundingBox::empty(), |a, b| a.union(&b))
+    }
+
+    /// Bounds the speed of any point of the box, in distance per whole animation.
+    fn max_speed(&self, bounds: &BoundingBox) -> f32 {
+        let max_abs = |v: Vec3| v.e.iter().fold(0f32, |max, c| max.max(c.abs()));
+        // The farthest point of the box from the origin, which rotation and scale are about.
+        let radius = (0..8)
+            .map(|i| {
+                let pick = |bit: usize, axis: usize| {
+                    if i & bit == 0 {
+                        bounds.min.e[axis]
+                    } else {
+                        bounds.max.e[axis]
+                    }
+                };
+                Vec3::new(pick(1, 0), pick(2, 1), pick(4, 2)).len()
+            })
+            .fold(0f32, f32::max);
+        let scale = max_abs(self.start.scale).max(max_abs(self.end.scale));
+        // Slerp turns at a constant rate; quaternions hold half the rotation angle.
+        let angle = 2.
+            * self
+                .start
+                .rotation
+                .dot(&self.end.rotation)
+                .abs()
+                .min(1.)
+                .acos();
+
+        (self.end.translation - self.start.translation).len()
+            + angle * scale * radius
+            + max_abs(self.end.scale - self.start.scale) * radius
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    fn assert_near(a: Vec3, b: Vec3) {
+        assert!((a - b).len() < 1e-5, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    pub fn rotation_works() {
+        let t = Transform::rotation(Vec3::new(0., 1., 0.), Degrees(90.));
+        assert_near(t.point(&Vec3::new(1., 0., 0.)), Vec3::new(0., 0., -1.));
+        assert_near(
+            t.inverse_vector(&Vec3::new(0., 0., -1.)),
+            Vec3::new(1., 0., 0.),
+        );
+    }
+
+    #[test]
+    pub fn composition_and_inverse_work() {
+        let t = Transform::scaling(Vec3::new(2., 3., 4.))
+            .then(&Transform::rotation(Vec3::new(1., 1., 0.), Degrees(33.)))
+            .then(&Transform::translation(Vec3::new(1., -2., 5.)));
+        let p = Point3::new(0.5, -1.5, 2.);
+        assert_near(t.inverse_point(&t.point(&p)), p);
+
+        let general = Transform::new(t.matrix).unwrap();
+        assert_near(general.inverse_point(&t.point(&p)), p);
+    }
+
+    #[test]
+    pub fn normals_stay_perpendicular() {
+        let t = Transform::scaling(Vec3::new(1., 4., 1.))
+            .then(&Transform::rotation(Vec3::new(0., 0., 1.), Degrees(30.)));
+        let tangent = Vec3::new(1., -1., 0.);
+        let normal = Vec3::new(1., 1., 0.);
+        let transformed_tangent = t.point(&tangent) - t.point(&Point3::default());
+        assert!(transformed_tangent.dot(&t.normal(&normal)).abs() < 1e-5);
+    }
+
+    #[test]
+    pub fn keyframes_interpolate() {
+        let start = Keyframe::default();
+        let end = Keyframe {
+            translation: Vec3::new(2., 0., 0.),
+            rotation: Quaternion::from_axis_angle(Vec3::new(0., 1., 0.), Degrees(90.)),
+            scale: Vec3::new(3., 3., 3.),
+        };
+        let animated = AnimatedTransform::new(start, 0., end, 1.);
+
+        let p = Point3::new(1., 0., 0.);
+        assert_near(animated.at(0.).point(&p), p);
+        assert_near(animated.at(1.).point(&p), Point3::new(2., 0., -3.));
+
+        let halfway = animated.at(0.5);
+        assert_near(halfway.inverse_point(&halfway.point(&p)), p);
+    }
+
+    #[test]
+    pub fn swept_bounds_contain_rotating_points() {
+        let end = Keyframe {
+            rotation: Quaternion::from_axis_angle(Vec3::new(0., 1., 0.), Degrees(100.)),
+            ..Keyframe::default()
+        };
+        let animated = AnimatedTransform::new(Keyframe::default(), 0., end, 1.);
+        let p = Point3::new(10., 0., 0.);
+        let bounds = animated.bounding_box(&BoundingBox::new(p, p));
+
+        // The point is farthest along Z at 90°, between two of the sampled instants.
+        for i in 0..=1000 {
+            let q = animated.at(i as f32 / 1000.).point(&p);
+            for axis in 0..3 {
+                assert!(bounds.min.e[axis] <= q.e[axis] && q.e[axis] <= bounds.max.e[axis]);
+            }
+        }
+    }
+
+    #[test]
+    pub fn singular_matrix_has_no_inverse() {
+        assert!(Transform::new(Mat4::new([[0.; 4]; 4])).is_none());
+    }
+}
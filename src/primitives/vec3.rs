@@ -73,7 +73,11 @@ impl Vec3 {
 
     /// Generates a random unit vector.
     pub fn random_unit(rng: &Random) -> Self {
-        Self::random_in_unit_sphere(rng).as_unit_vector()
+        let (u, v) = rng.sample_2d();
+        let z = 1. - 2. * u;
+        let r = (1. - z * z).max(0.).sqrt();
+        let phi = 2. * std::f32::consts::PI * v;
+        Self::new(r * phi.cos(), r * phi.sin(), z)
     }
 
     /// Generates a random vector with components ranging in range `-1.0..1.0` that lies
@@ -94,18 +98,24 @@ impl Vec3 {
         if vector.dot(normal) > 0. {
             return vector;
         }
-        return -vector;
+        -vector
     }
 
+    /// Generates a random vector within the unit disk in the XY plane, using Shirley's
+    /// concentric mapping to keep well distributed samples well distributed.
     pub fn random_in_unit_disk(rng: &Random) -> Vec3 {
-        loop {
-            let x = rng.sample() * 2. - 1.;
-            let y = rng.sample() * 2. - 1.;
-            let p = Vec3::new(x, y, 0.);
-            if p.len_squared() < 1. {
-                return p;
-            }
+        let (u, v) = rng.sample_2d();
+        let (x, y) = (2. * u - 1., 2. * v - 1.);
+        if x == 0. && y == 0. {
+            return Vec3::default();
         }
+        let quarter_pi = std::f32::consts::FRAC_PI_4;
+        let (r, theta) = if x.abs() > y.abs() {
+            (x, quarter_pi * (y / x))
+        } else {
+            (y, 2. * quarter_pi - quarter_pi * (x / y))
+        };
+        Vec3::new(r * theta.cos(), r * theta.sin(), 0.)
     }
 
     /// Determines if the vector is new zero
@@ -231,8 +241,7 @@ impl Div<f32> for Vec3 {
 
     #[inline]
     fn div(self, rhs: f32) -> Self {
-        let inv = 1. / rhs;
-        Vec3::new(self.e[0] * inv, self.e[1] * inv, self.e[2] * inv)
+        Vec3::new(self.e[0] / rhs, self.e[1] / rhs, self.e[2] / rhs)
     }
 }
 
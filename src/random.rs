@@ -1,13 +1,63 @@
+use crate::sampler::SamplerPtr;
 use rand::{distributions::Uniform, prelude::*, thread_rng};
+use std::cell::Cell;
 
+/// The source of the random values of one path.
+///
+/// Without a sampler, values are drawn independently. With a sampler, each call
+/// consumes the next dimension of the pixel sample.
 pub struct Random {
     distribution: Uniform<f32>,
+    sampler: Option<PixelSample>,
+    /// The next dimension to consume.
+    dimension: Cell<usize>,
+}
+
+struct PixelSample {
+    sampler: SamplerPtr,
+    pixel: (usize, usize),
+    index: usize,
 }
 
 impl Random {
+    /// Draws the values of the sample with the specified index within a pixel from the sampler.
+    pub fn for_sample(sampler: SamplerPtr, pixel: (usize, usize), index: usize) -> Self {
+        Self {
+            sampler: Some(PixelSample {
+                sampler,
+                pixel,
+                index,
+            }),
+            ..Self::default()
+        }
+    }
+
     pub fn sample(&self) -> f32 {
-        let mut rng = thread_rng();
-        self.distribution.sample(&mut rng)
+        match &self.sampler {
+            Some(sample) => {
+                let dimension = self.dimension.replace(self.dimension.get() + 1);
+                sample.sampler.sample(sample.pixel, sample.index, dimension)
+            }
+            None => {
+                let mut rng = thread_rng();
+                self.distribution.sample(&mut rng)
+            }
+        }
+    }
+
+    /// Draws two values that are well distributed in two dimensions.
+    pub fn sample_2d(&self) -> (f32, f32) {
+        match &self.sampler {
+            Some(sample) => {
+                // Pairs start at even dimensions.
+                let dimension = (self.dimension.get() + 1) & !1;
+                self.dimension.set(dimension + 2);
+                sample
+                    .sampler
+                    .sample_2d(sample.pixel, sample.index, dimension)
+            }
+            None => (self.sample(), self.sample()),
+        }
     }
 }
 
@@ -15,6 +65,24 @@ impl Default for Random {
     fn default() -> Self {
         Self {
             distribution: Uniform::from(0.0f32..1.0),
+            sampler: None,
+            dimension: Cell::new(0),
         }
     }
 }
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use crate::sampler::Sobol;
+    use std::sync::Arc;
+
+    #[test]
+    pub fn dimensions_are_consumed_in_order() {
+        let sampler: SamplerPtr = Arc::new(Sobol);
+        let rng = Random::for_sample(sampler.clone(), (1, 2), 3);
+        assert_eq!(rng.sample(), sampler.sample((1, 2), 3, 0));
+        assert_eq!(rng.sample_2d(), sampler.sample_2d((1, 2), 3, 2));
+        assert_eq!(rng.sample(), sampler.sample((1, 2), 3, 4));
+    }
+}
@@ -0,0 +1,374 @@
+//! Generators of well-distributed sample values.
+//!
+//! A sampler provides the values of any number of sample dimensions for each sample of a
+//! pixel. Paths consume dimensions in order: the position within the pixel, the lens, the
+//! time and then the decisions at each path vertex. All samplers are deterministic and
+//! randomized per pixel by hashing, so they can be evaluated in any order on any thread.
+
+use std::sync::Arc;
+
+/// Provides the sample values of pixel samples.
+pub trait Sampler: Send + Sync {
+    /// The value in range `0.0..1.0` of a sample dimension.
+    fn sample(&self, pixel: (usize, usize), index: usize, dimension: usize) -> f32;
+
+    /// The values of two consecutive dimensions that are well distributed
+    /// in two dimensions; `dimension` is even.
+    fn sample_2d(&self, pixel: (usize, usize), index: usize, dimension: usize) -> (f32, f32) {
+        (
+            self.sample(pixel, index, dimension),
+            self.sample(pixel, index, dimension + 1),
+        )
+    }
+}
+
+pub type SamplerPtr = Arc<dyn Sampler>;
+
+/// Mixes the bits of the values into a well distributed hash.
+fn hash(values: &[u32]) -> u32 {
+    let mut h: u32 = 0x9e37_79b9;
+    for &value in values {
+        h ^= value;
+        // The finalizer of MurmurHash3.
+        h ^= h >> 16;
+        h = h.wrapping_mul(0x85eb_ca6b);
+        h ^= h >> 13;
+        h = h.wrapping_mul(0xc2b2_ae35);
+        h ^= h >> 16;
+    }
+    h
+}
+
+/// Converts the bits to a float in range `0.0..1.0`.
+fn to_unit(bits: u32) -> f32 {
+    (bits >> 8) as f32 * (1. / (1u32 << 24) as f32)
+}
+
+fn pixel_hash(pixel: (usize, usize), dimension: usize) -> u32 {
+    hash(&[pixel.0 as u32, pixel.1 as u32, dimension as u32])
+}
+
+/// Independent uniform random values, as drawn by `Random` without a sampler.
+pub struct Independent;
+
+impl Sampler for Independent {
+    fn sample(&self, pixel: (usize, usize), index: usize, dimension: usize) -> f32 {
+        to_unit(hash(&[
+            pixel.0 as u32,
+            pixel.1 as u32,
+            index as u32,
+            dimension as u32,
+        ]))
+    }
+}
+
+/// Permutes `index` within `0..length` using Kensler's hashed permutation.
+fn permute(index: u32, length: u32, seed: u32) -> u32 {
+    let mut w = length - 1;
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+    let mut i = index;
+    loop {
+        i ^= seed;
+        i = i.wrapping_mul(0xe170_893d);
+        i ^= seed >> 16;
+        i ^= (i & w) >> 4;
+        i ^= seed >> 8;
+        i = i.wrapping_mul(0x0929_eb3f);
+        i ^= seed >> 23;
+        i ^= (i & w) >> 1;
+        i = i.wrapping_mul(1 | seed >> 27);
+        i = i.wrapping_mul(0x6935_fa69);
+        i ^= (i & w) >> 11;
+        i = i.wrapping_mul(0x74dc_b303);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0x9e50_1cc3);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0xc860_a3df);
+        i &= w;
+        i ^= i >> 5;
+        if i < length {
+            return i.wrapping_add(seed) % length;
+        }
+    }
+}
+
+/// Jittered samples placing one sample into each of equally sized strata,
+/// in random order per pixel and dimension.
+pub struct Stratified {
+    samples_per_pixel: usize,
+}
+
+impl Stratified {
+    pub fn new(samples_per_pixel: usize) -> Self {
+        Self {
+            samples_per_pixel: samples_per_pixel.max(1),
+        }
+    }
+}
+
+impl Sampler for Stratified {
+    fn sample(&self, pixel: (usize, usize), index: usize, dimension: usize) -> f32 {
+        let count = self.samples_per_pixel as u32;
+        let seed = pixel_hash(pixel, dimension);
+        let stratum = permute(index as u32 % count, count, seed);
+        let jitter = to_unit(hash(&[seed, index as u32]));
+        (stratum as f32 + jitter) / count as f32
+    }
+
+    fn sample_2d(&self, pixel: (usize, usize), index: usize, dimension: usize) -> (f32, f32) {
+        // Use a grid of strata as close to square as possible.
+        let columns = (self.samples_per_pixel as f32).sqrt().floor().max(1.) as u32;
+        let rows = (self.samples_per_pixel as u32).div_ceil(columns);
+        let seed = pixel_hash(pixel, dimension);
+        let stratum = permute(index as u32 % (columns * rows), columns * rows, seed);
+        let jitter_x = to_unit(hash(&[seed, index as u32, 0]));
+        let jitter_y = to_unit(hash(&[seed, index as u32, 1]));
+        (
+            ((stratum % columns) as f32 + jitter_x) / columns as f32,
+            ((stratum / columns) as f32 + jitter_y) / rows as f32,
+        )
+    }
+}
+
+/// The Halton sequence using one prime base per dimension, shifted randomly per pixel.
+///
+/// Dimensions beyond the tabulated primes fall back to independent values.
+pub struct Halton;
+
+impl Halton {
+    const PRIMES: [u32; 32] = [
+        2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89,
+        97, 101, 103, 107, 109, 113, 127, 131,
+    ];
+
+    fn radical_inverse(mut index: u32, base: u32) -> f32 {
+        let inverse_base = 1. / base as f32;
+        let mut factor = inverse_base;
+        let mut value = 0.;
+        while index > 0 {
+            value += (index % base) as f32 * factor;
+            index /= base;
+            factor *= inverse_base;
+        }
+        value
+    }
+}
+
+impl Sampler for Halton {
+    fn sample(&self, pixel: (usize, usize), index: usize, dimension: usize) -> f32 {
+        match Self::PRIMES.get(dimension) {
+            Some(&base) => {
+                // A Cranley-Patterson rotation decorrelates the pixels.
+                let offset = to_unit(pixel_hash(pixel, dimension));
+                let value = Self::radical_inverse(index as u32, base) + offset;
+                (value - value.floor()).min(1. - f32::EPSILON)
+            }
+            None => Independent.sample(pixel, index, dimension),
+        }
+    }
+}
+
+/// The first two dimensions of the Sobol sequence, as 32-bit fractions.
+fn sobol(index: u32, dimension: usize) -> u32 {
+    if dimension == 0 {
+        return index.reverse_bits();
+    }
+    let mut value = 0;
+    let mut direction = 1u32 << 31;
+    let mut index = index;
+    while index != 0 {
+        if index & 1 != 0 {
+            value ^= direction;
+        }
+        index >>= 1;
+        direction ^= direction >> 1;
+    }
+    value
+}
+
+/// Applies a hash-based nested uniform scramble, i.e. Owen scrambling, to the bits.
+fn owen_scramble(x: u32, seed: u32) -> u32 {
+    // The Laine-Karras permutation, applied to the reversed bits.
+    let mut x = x.reverse_bits();
+    x = x.wrapping_add(seed);
+    x ^= x.wrapping_mul(0x6c50_b47c);
+    x ^= x.wrapping_mul(0xb82f_1e52);
+    x ^= x.wrapping_mul(0xc7af_e638);
+    x ^= x.wrapping_mul(0x8d22_f6e6);
+    x.reverse_bits()
+}
+
+/// Owen-scrambled Sobol values for any dimension.
+///
+/// Following Burley's practical hash-based Owen scrambling, every pair of dimensions
+/// uses the first two Sobol dimensions with the sample order shuffled per pair.
+fn scrambled_sobol(seed: u32, index: usize, dimension: usize) -> f32 {
+    let pair_seed = hash(&[seed, (dimension / 2) as u32]);
+    let shuffled = owen_scramble(index as u32, pair_seed);
+    let value = owen_scramble(
+        sobol(shuffled, dimension % 2),
+        hash(&[seed, dimension as u32, 1]),
+    );
+    to_unit(value)
+}
+
+/// The Sobol sequence with independent Owen scrambling per pixel.
+pub struct Sobol;
+
+impl Sampler for Sobol {
+    fn sample(&self, pixel: (usize, usize), index: usize, dimension: usize) -> f32 {
+        scrambled_sobol(hash(&[pixel.0 as u32, pixel.1 as u32]), index, dimension)
+    }
+}
+
+/// A Sobol sequence shared by all pixels, shifted per pixel by a blue noise mask.
+///
+/// Neighboring pixels receive very different shifts, so at low sample counts
+/// the remaining error appears as fine, high frequency noise.
+pub struct BlueNoise {
+    mask: Vec<f32>,
+}
+
+impl BlueNoise {
+    /// The width and height of the tiled mask.
+    const SIZE: usize = 64;
+
+    pub fn new() -> Self {
+        Self {
+            mask: Self::generate_mask(),
+        }
+    }
+
+    /// Generates a blue noise mask by repeatedly ranking the pixel
+    /// farthest from all previously ranked pixels, in the spirit of
+    /// Ulichney's void-and-cluster method.
+    fn generate_mask() -> Vec<f32> {
+        const SIGMA: f32 = 1.5;
+        let size = Self::SIZE;
+        let count = size * size;
+
+        // The Gaussian energy contributed to pixels at each toroidal offset.
+        let mut kernel = vec![0.; count];
+        for y in 0..size {
+            for x in 0..size {
+                let dx = x.min(size - x) as f32;
+                let dy = y.min(size - y) as f32;
+                kernel[y * size + x] = (-(dx * dx + dy * dy) / (2. * SIGMA * SIGMA)).exp();
+            }
+        }
+
+        let mut energy = vec![0f32; count];
+        let mut mask = vec![-1f32; count];
+        for rank in 0..count {
+            let (void, _) = energy
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| mask[*i] < 0.)
+                .fold(
+                    (0, f32::INFINITY),
+                    |best, (i, &e)| {
+                        if e < best.1 {
+                            (i, e)
+                        } else {
+                            best
+                        }
+                    },
+                );
+            mask[void] = (rank as f32 + 0.5) / count as f32;
+
+            let (vx, vy) = (void % size, void / size);
+            for y in 0..size {
+                for x in 0..size {
+                    let k = ((y + size - vy) % size) * size + (x + size - vx) % size;
+                    energy[y * size + x] += kernel[k];
+                }
+            }
+        }
+        mask
+    }
+}
+
+impl Sampler for BlueNoise {
+    fn sample(&self, pixel: (usize, usize), index: usize, dimension: usize) -> f32 {
+        // Shift the mask per dimension to decorrelate the dimensions.
+        let shift = hash(&[dimension as u32]) as usize;
+        let x = (pixel.0 + shift) % Self::SIZE;
+        let y = (pixel.1 + (shift >> 16)) % Self::SIZE;
+        let value = scrambled_sobol(0, index, dimension) + self.mask[y * Self::SIZE + x];
+        (value - value.floor()).min(1. - f32::EPSILON)
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    /// Checks that the first `n` samples place exactly one value into each of `n` intervals.
+    fn assert_stratified(sampler: &dyn Sampler, n: usize, dimension: usize) {
+        let mut counts = vec![0; n];
+        for index in 0..n {
+            let value = sampler.sample((3, 7), index, dimension);
+            assert!((0. ..1.).contains(&value));
+            counts[(value * n as f32) as usize] += 1;
+        }
+        assert!(counts.iter().all(|&c| c == 1), "{:?}", counts);
+    }
+
+    #[test]
+    pub fn samplers_are_stratified() {
+        assert_stratified(&Stratified::new(16), 16, 5);
+        assert_stratified(&Halton, 16, 0);
+        assert_stratified(&Halton, 9, 1);
+        for dimension in [0, 1, 6, 13] {
+            assert_stratified(&Sobol, 16, dimension);
+        }
+    }
+
+    #[test]
+    pub fn sample_2d_is_stratified() {
+        let samplers: [&dyn Sampler; 2] = [&Stratified::new(16), &Sobol];
+        for sampler in samplers {
+            let mut counts = [0; 16];
+            for index in 0..16 {
+                let (x, y) = sampler.sample_2d((1, 2), index, 4);
+                counts[(y * 4.) as usize * 4 + (x * 4.) as usize] += 1;
+            }
+            assert!(counts.iter().all(|&c| c == 1), "{:?}", counts);
+        }
+    }
+
+    #[test]
+    pub fn permute_works() {
+        let mut values = (0..10).map(|i| permute(i, 10, 1234)).collect::<Vec<_>>();
+        values.sort_unstable();
+        assert_eq!(values, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    pub fn blue_noise_mask_is_high_frequency() {
+        let sampler = BlueNoise::new();
+        let size = BlueNoise::SIZE;
+
+        // Every rank appears once.
+        let mut ranks = sampler
+            .mask
+            .iter()
+            .map(|v| (v * (size * size) as f32) as usize)
+            .collect::<Vec<_>>();
+        ranks.sort_unstable();
+        assert_eq!(ranks, (0..size * size).collect::<Vec<_>>());
+
+        // Neighbors differ more than for white noise, where the expected difference is 1/3.
+        let mut difference = 0.;
+        for y in 0..size {
+            for x in 0..size - 1 {
+                difference += (sampler.mask[y * size + x] - sampler.mask[y * size + x + 1]).abs();
+            }
+        }
+        assert!(difference / (size * (size - 1)) as f32 > 0.36);
+    }
+}
@@ -0,0 +1,764 @@
+use crate::camera::{Focus, Shutter};
+use crate::color_space::{ColorConversion, ColorSpace};
+use crate::fog::Fog;
+use crate::material::{
+    ComplexIor, Conductor, Dielectric, Dispersion, HenyeyGreenstein, Isotropic, Lambertian,
+    MaterialPtr, Metal, Parameter, Principled, RoughDielectric,
+};
+use crate::noise::ValueNoise;
+use crate::objects::{
+    Cone, ConstantMedium, Csg, Cuboid, Cylinder, Disk, Heightfield, HeterogeneousMedium, Hittable,
+    HittableList, HittablePtr, Instance, MovingSphere, Operation, Plane, Quad, SdfObject, Solid,
+    Sphere, Torus, VoxelGrid,
+};
+use crate::primitives::{AnimatedTransform, BoundingBox, Keyframe, Mat4, Quaternion, Transform};
+use crate::sdf::{FunctionField, Mandelbulb, SdfBox, SdfSphere, SdfTorus, SmoothUnion};
+use crate::texture::{Checker, SolidColor, TexturePtr};
+use crate::{Color, Degrees, Point3, Random, Vec3, GRID_SCALE};
+use space_partitioning::quadtree::QuadRect;
+use std::sync::Arc;
+
+/// The objects to render and the media surrounding them.
+pub struct Scene {
+    pub world: Box<dyn Hittable>,
+    pub fog: Option<Fog>,
+    pub shutter: Shutter,
+    /// Where the camera focuses unless overridden on the command line.
+    pub focus: Focus,
+    /// Converts the colors the scene is authored in into the working space.
+    pub input_conversion: ColorConversion,
+}
+
+impl Scene {
+    pub fn new(world: HittableList) -> Self {
+        Self {
+            world: Box::new(world),
+            fog: None,
+            shutter: Shutter::default(),
+            focus: Focus::default(),
+            input_conversion: ColorConversion::default(),
+        }
+    }
+
+    pub fn with_fog(mut self, fog: Fog) -> Self {
+        self.fog = Some(fog);
+        self
+    }
+
+    pub fn with_shutter(mut self, open: f32, close: f32) -> Self {
+        self.shutter = Shutter::new(open, close);
+        self
+    }
+
+    pub fn with_focus(mut self, focus: Focus) -> Self {
+        self.focus = focus;
+        self
+    }
+
+    /// Renders the scene in the specified working space, converting the
+    /// Rec. 709 colors of materials, lights and the background on input.
+    pub fn with_working_space(mut self, working_space: ColorSpace) -> Self {
+        self.input_conversion = ColorConversion::new(ColorSpace::Rec709, working_space);
+        self
+    }
+}
+
+/// Builds the scene with the specified name.
+///
+/// Some scenes take an argument separated by a colon, e.g. `clouds:density.vox`.
+pub fn by_name(name: &str, rng: &mut Random) -> std::io::Result<Scene> {
+    let (name, argument) = match name.split_once(':') {
+        Some((name, argument)) => (name, Some(argument)),
+        None => (name, None),
+    };
+
+    match name {
+        "random" => Ok(Scene::new(random_scene(rng))),
+        "microfacet" => Ok(Scene::new(microfacet_scene())),
+        "principled" => Ok(Scene::new(principled_scene())),
+        "glass" => Ok(Scene::new(glass_scene()).with_focus(Focus::Point(Point3::new(0., 1., 0.)))),
+        "volumes" => Ok(volumes_scene()),
+        "clouds" => Ok(Scene::new(clouds_scene(argument)?)),
+        "instances" => Ok(Scene::new(instances_scene(rng))),
+        "motion" => Ok(Scene::new(motion_scene(rng)).with_shutter(0., 1.)),
+        "primitives" => Ok(Scene::new(primitives_scene())),
+        "csg" => Ok(Scene::new(csg_scene())),
+        "sdf" => Ok(Scene::new(sdf_scene())),
+        "terrain" => Ok(Scene::new(terrain_scene(argument)?)),
+        _ => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("unknown scene: {}", name),
+        )),
+    }
+}
+
+fn empty_world() -> HittableList {
+    HittableList::new(QuadRect::new(
+        (-15. * GRID_SCALE) as i32,
+        (-15. * GRID_SCALE) as i32,
+        (30. * GRID_SCALE) as i32,
+        (30. * GRID_SCALE) as i32,
+    ))
+}
+
+fn solid(color: Color) -> TexturePtr {
+    Arc::new(Box::new(SolidColor::new(color)))
+}
+
+fn add_ground(world: &mut HittableList) {
+    let ground_material: MaterialPtr =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5), 1.0)));
+    world.add(Box::new(Plane::new(
+        Point3::new(0., 0., 0.),
+        Vec3::new(0., 1., 0.),
+        ground_material,
+    )));
+}
+
+pub fn random_scene(rng: &mut Random) -> HittableList {
+    let mut world = empty_world();
+    add_ground(&mut world);
+
+    for a in -11..11 {
+        for b in -11..11 {
+            let choose_mat = rng.sample();
+            let center = Point3::new(
+                a as f32 + 0.9 * rng.sample(),
+                0.2,
+                b as f32 + 0.9 * rng.sample(),
+            );
+
+            if (center - Point3::new(4., 0.2, 0.)).len() > 0.9 {
+                if choose_mat < 0.8 {
+                    // Diffuse
+                    let albedo = Color::random(rng) * Color::random(rng);
+                    let material: MaterialPtr = Arc::new(Box::new(Lambertian::new(albedo, 1.0)));
+                    world.add(Box::new(Sphere::new(center, 0.2, material)));
+                } else if choose_mat < 0.95 {
+                    // Metal
+                    let albedo = Color::random(rng) * 0.5 + 0.5;
+                    let fuzz = rng.sample() * 0.5;
+                    let material: MaterialPtr = Arc::new(Box::new(Metal::new(albedo, fuzz)));
+                    world.add(Box::new(Sphere::new(center, 0.2, material)));
+                } else {
+                    // Glass
+                    let material: MaterialPtr = Arc::new(Box::new(Dielectric::new(1.5)));
+                    world.add(Box::new(Sphere::new(center, 0.2, material)));
+                }
+            }
+        }
+    }
+
+    let material1: MaterialPtr = Arc::new(Box::new(Dielectric::new(1.5)));
+    world.add(Box::new(Sphere::new(
+        Point3::new(0., 1., 0.),
+        1.0,
+        material1,
+    )));
+
+    let material2: MaterialPtr = Arc::new(Box::new(Lambertian::new(Color::new(0.4, 0.2, 0.1), 1.)));
+    world.add(Box::new(Sphere::new(
+        Point3::new(-4., 1., 0.),
+        1.0,
+        material2,
+    )));
+
+    let material3: MaterialPtr = Arc::new(Box::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.0)));
+    world.add(Box::new(Sphere::new(
+        Point3::new(4., 1., 0.),
+        1.0,
+        material3,
+    )));
+
+    world
+}
+
+/// Compares the GGX conductors and rough glass at various roughness values.
+pub fn microfacet_scene() -> HittableList {
+    let mut world = empty_world();
+    add_ground(&mut world);
+
+    // A row of copper spheres with increasing roughness.
+    for i in 0..6 {
+        let roughness = i as f32 / 5.;
+        let material: MaterialPtr =
+            Arc::new(Box::new(Conductor::new(ComplexIor::COPPER, roughness)));
+        world.add(Box::new(Sphere::new(
+            Point3::new(-5. + 2. * i as f32, 0.3, 2.5),
+            0.3,
+            material,
+        )));
+    }
+
+    let gold: MaterialPtr = Arc::new(Box::new(Conductor::new(ComplexIor::GOLD, 0.3)));
+    world.add(Box::new(Sphere::new(Point3::new(-4., 1., 0.), 1.0, gold)));
+
+    let frosted_glass: MaterialPtr = Arc::new(Box::new(RoughDielectric::new(1.5, 0.2)));
+    world.add(Box::new(Sphere::new(
+        Point3::new(0., 1., 0.),
+        1.0,
+        frosted_glass,
+    )));
+
+    let aluminium: MaterialPtr = Arc::new(Box::new(Conductor::new(ComplexIor::ALUMINIUM, 0.1)));
+    world.add(Box::new(Sphere::new(
+        Point3::new(4., 1., 0.),
+        1.0,
+        aluminium,
+    )));
+
+    world
+}
+
+/// Showcases the layers of the principled material.
+pub fn principled_scene() -> HittableList {
+    let mut world = empty_world();
+
+    // A checkered ground whose roughness follows the same checker pattern.
+    let checker: TexturePtr = Arc::new(Box::new(Checker::new(
+        solid(Color::new(0.2, 0.3, 0.1)),
+        solid(Color::new(0.9, 0.9, 0.9)),
+        1.,
+    )));
+    let ground: MaterialPtr = Arc::new(Box::new(Principled::new(checker.clone()).with_roughness(
+        Parameter::Texture {
+            texture: checker.clone(),
+            channel: 0,
+            factor: 1.,
+        },
+    )));
+    world.add(Box::new(Sphere::new(
+        Point3::new(0., -1000., 0.),
+        1000.,
+        ground,
+    )));
+
+    let brushed_gold: MaterialPtr = Arc::new(Box::new(
+        Principled::new(solid(Color::new(1.0, 0.77, 0.34)))
+            .with_metallic(1.)
+            .with_roughness(0.25),
+    ));
+    world.add(Box::new(Sphere::new(
+        Point3::new(-4., 1., 0.),
+        1.0,
+        brushed_gold,
+    )));
+
+    let tinted_glass: MaterialPtr = Arc::new(Box::new(
+        Principled::new(solid(Color::new(0.85, 1.0, 0.9)))
+            .with_roughness(0.05)
+            .with_transmission(1., 1.5),
+    ));
+    world.add(Box::new(Sphere::new(
+        Point3::new(0., 1., 0.),
+        1.0,
+        tinted_glass,
+    )));
+
+    let car_paint: MaterialPtr = Arc::new(Box::new(
+        Principled::new(solid(Color::new(0.6, 0.05, 0.05)))
+            .with_roughness(0.6)
+            .with_clearcoat(1., 0.03),
+    ));
+    world.add(Box::new(Sphere::new(
+        Point3::new(4., 1., 0.),
+        1.0,
+        car_paint,
+    )));
+
+    let velvet: MaterialPtr = Arc::new(Box::new(
+        Principled::new(solid(Color::new(0.3, 0.05, 0.4)))
+            .with_roughness(1.)
+            .with_sheen(1., 0.5),
+    ));
+    world.add(Box::new(Sphere::new(
+        Point3::new(-2., 0.4, 2.5),
+        0.4,
+        velvet,
+    )));
+
+    let glowing: MaterialPtr = Arc::new(Box::new(
+        Principled::new(solid(Color::new(0.1, 0.1, 0.1)))
+            .with_emission(solid(Color::new(1.0, 0.6, 0.2)), 4.),
+    ));
+    world.add(Box::new(Sphere::new(
+        Point3::new(0., 0.4, 2.5),
+        0.4,
+        glowing,
+    )));
+
+    // Metalness and roughness taken from a glTF-style packed texture.
+    let gltf: MaterialPtr = Arc::new(Box::new(
+        Principled::from_metallic_roughness(
+            solid(Color::new(0.9, 0.9, 0.9)),
+            1.,
+            0.8,
+            Some(checker),
+        )
+        .with_specular(1.),
+    ));
+    world.add(Box::new(Sphere::new(Point3::new(2., 0.4, 2.5), 0.4, gltf)));
+
+    world
+}
+
+/// Shows tinted glass of different thickness and dispersive glass.
+pub fn glass_scene() -> HittableList {
+    let mut world = empty_world();
+    add_ground(&mut world);
+
+    // The same green glass, once thick and once thin.
+    let green_glass = || -> MaterialPtr {
+        Arc::new(Box::new(
+            Dielectric::new(1.5).with_transmittance(Color::new(0.2, 0.8, 0.3), 1.),
+        ))
+    };
+    world.add(Box::new(Sphere::new(
+        Point3::new(-4., 1., 0.),
+        1.0,
+        green_glass(),
+    )));
+    world.add(Box::new(Sphere::new(
+        Point3::new(-2., 0.25, 2.5),
+        0.25,
+        green_glass(),
+    )));
+
+    let flint: MaterialPtr = Arc::new(Box::new(
+        Dielectric::new(1.5).with_dispersion(Dispersion::SF11),
+    ));
+    world.add(Box::new(Sphere::new(Point3::new(0., 1., 0.), 1.0, flint)));
+
+    let fused_silica: MaterialPtr = Arc::new(Box::new(Dielectric::new(1.5).with_dispersion(
+        Dispersion::Cauchy {
+            a: 1.458,
+            b: 0.00354,
+        },
+    )));
+    world.add(Box::new(Sphere::new(
+        Point3::new(4., 1., 0.),
+        1.0,
+        fused_silica,
+    )));
+
+    let crown: MaterialPtr = Arc::new(Box::new(
+        Dielectric::new(1.5)
+            .with_dispersion(Dispersion::BK7)
+            .with_absorption(Color::new(0.05, 0.1, 0.6)),
+    ));
+    world.add(Box::new(Sphere::new(Point3::new(2., 0.4, 2.5), 0.4, crown)));
+
+    world
+}
+
+/// Shows smoke, a translucent sphere and a thin global haze.
+pub fn volumes_scene() -> Scene {
+    let mut world = empty_world();
+    add_ground(&mut world);
+
+    let smoke: MaterialPtr = Arc::new(Box::new(Isotropic::new(Color::new(0.2, 0.2, 0.2))));
+    let dummy: MaterialPtr = Arc::new(Box::new(Isotropic::new(Color::default())));
+    world.add(Box::new(ConstantMedium::new(
+        Box::new(Sphere::new(Point3::new(-4., 1., 0.), 1.0, dummy.clone())),
+        2.,
+        smoke,
+    )));
+
+    // A glass shell filled with a dense, forward scattering medium for a jade-like look.
+    let glass: MaterialPtr = Arc::new(Box::new(Dielectric::new(1.5)));
+    world.add(Box::new(Sphere::new(Point3::new(0., 1., 0.), 1.0, glass)));
+    let jade: MaterialPtr = Arc::new(Box::new(HenyeyGreenstein::new(
+        Color::new(0.5, 0.9, 0.6),
+        0.6,
+    )));
+    world.add(Box::new(ConstantMedium::new(
+        Box::new(Sphere::new(Point3::new(0., 1., 0.), 0.999, dummy)),
+        8.,
+        jade,
+    )));
+
+    let metal: MaterialPtr = Arc::new(Box::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.0)));
+    world.add(Box::new(Sphere::new(Point3::new(4., 1., 0.), 1.0, metal)));
+
+    let haze: MaterialPtr = Arc::new(Box::new(HenyeyGreenstein::new(
+        Color::new(0.9, 0.9, 0.9),
+        0.3,
+    )));
+    Scene::new(world).with_fog(Fog::new(0.02, haze).with_ceiling(3.))
+}
+
+/// Renders a cloud from a voxel grid file, or a procedurally generated one if no file is given.
+pub fn clouds_scene(voxel_file: Option<&str>) -> std::io::Result<HittableList> {
+    let mut world = empty_world();
+    add_ground(&mut world);
+
+    let grid = match voxel_file {
+        Some(path) => VoxelGrid::load_raw(path)?,
+        None => {
+            let noise = ValueNoise::new(7);
+            VoxelGrid::procedural(96, 48, 64, |p| {
+                // A flattened blob, eroded by noise towards its boundary.
+                let offset = p - Point3::new(0.5, 0.5, 0.5);
+                let falloff = 1. - 2. * Point3::new(offset.x(), 1.5 * offset.y(), offset.z()).len();
+                let detail = noise.fbm(&(p * 8.), 5);
+                (falloff + 0.6 * (detail - 0.5)).max(0.)
+            })
+        }
+    };
+
+    let cloud: MaterialPtr = Arc::new(Box::new(HenyeyGreenstein::new(
+        Color::new(0.95, 0.95, 0.95),
+        0.4,
+    )));
+    world.add(Box::new(HeterogeneousMedium::new(
+        Arc::new(grid),
+        BoundingBox::new(Point3::new(-3., 0.2, -2.), Point3::new(3., 2.7, 2.)),
+        12.,
+        cloud,
+    )));
+
+    Ok(world)
+}
+
+/// Places a single composite object hundreds of times with random affine transforms.
+pub fn instances_scene(rng: &mut Random) -> HittableList {
+    let mut world = empty_world();
+    add_ground(&mut world);
+
+    // A small "molecule" of three spheres, stored only once.
+    let mut molecule = HittableList::new(QuadRect::new(
+        (-2. * GRID_SCALE) as i32,
+        (-2. * GRID_SCALE) as i32,
+        (4. * GRID_SCALE) as i32,
+        (4. * GRID_SCALE) as i32,
+    ));
+    let core: MaterialPtr = Arc::new(Box::new(Conductor::new(ComplexIor::COPPER, 0.2)));
+    let shell: MaterialPtr = Arc::new(Box::new(Lambertian::new(Color::new(0.2, 0.4, 0.8), 1.)));
+    molecule.add(Box::new(Sphere::new(Point3::new(0., 0., 0.), 0.2, core)));
+    molecule.add(Box::new(Sphere::new(
+        Point3::new(0.25, 0.1, 0.),
+        0.12,
+        shell.clone(),
+    )));
+    molecule.add(Box::new(Sphere::new(
+        Point3::new(-0.25, 0.1, 0.),
+        0.12,
+        shell,
+    )));
+    let molecule: HittablePtr = Arc::new(Box::new(molecule));
+
+    for a in -11..11 {
+        for b in -11..11 {
+            let transform = Transform::scaling(Vec3::new(1., 0.5 + rng.sample(), 1.))
+                .then(&Transform::rotation(
+                    Vec3::random(rng),
+                    Degrees(360. * rng.sample()),
+                ))
+                .then(&Transform::scaling(
+                    Vec3::new(1., 1., 1.) * (0.7 + 0.6 * rng.sample()),
+                ))
+                .then(&Transform::translation(Vec3::new(
+                    a as f32 + 0.9 * rng.sample(),
+                    0.3,
+                    b as f32 + 0.9 * rng.sample(),
+                )));
+            world.add(Box::new(Instance::new(molecule.clone(), transform)));
+        }
+    }
+
+    // A large, sheared copy in the center.
+    let shear = Mat4::new([
+        [4., 2., 0., 0.],
+        [0., 4., 0., 1.2],
+        [0., 0., 4., 0.],
+        [0., 0., 0., 1.],
+    ]);
+    let sheared = Transform::new(shear).expect("shear matrix is invertible");
+    world.add(Box::new(Instance::new(molecule, sheared)));
+
+    world
+}
+
+/// Bouncing spheres and a spinning instance, to be rendered with an open shutter.
+pub fn motion_scene(rng: &mut Random) -> HittableList {
+    let mut world = empty_world();
+    add_ground(&mut world);
+
+    for a in -11..11 {
+        for b in -11..11 {
+            let center = Point3::new(
+                a as f32 + 0.9 * rng.sample(),
+                0.2,
+                b as f32 + 0.9 * rng.sample(),
+            );
+            if (center - Point3::new(4., 0.2, 0.)).len() <= 0.9 || rng.sample() > 0.5 {
+                continue;
+            }
+
+            let albedo = Color::random(rng) * Color::random(rng);
+            let material: MaterialPtr = Arc::new(Box::new(Lambertian::new(albedo, 1.0)));
+            let bounce = Vec3::new(0., 0.5 * rng.sample(), 0.);
+            world.add(Box::new(MovingSphere::new(
+                center,
+                0.,
+                center + bounce,
+                1.,
+                0.2,
+                material,
+            )));
+        }
+    }
+
+    // A dumbbell spinning a quarter turn while sliding sideways.
+    let mut dumbbell = HittableList::new(QuadRect::new(
+        (-2. * GRID_SCALE) as i32,
+        (-2. * GRID_SCALE) as i32,
+        (4. * GRID_SCALE) as i32,
+        (4. * GRID_SCALE) as i32,
+    ));
+    let metal: MaterialPtr = Arc::new(Box::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.0)));
+    dumbbell.add(Box::new(Sphere::new(
+        Point3::new(-0.7, 0., 0.),
+        0.5,
+        metal.clone(),
+    )));
+    dumbbell.add(Box::new(Sphere::new(Point3::new(0.7, 0., 0.), 0.5, metal)));
+    let dumbbell: HittablePtr = Arc::new(Box::new(dumbbell));
+
+    let start = Keyframe {
+        translation: Vec3::new(4., 1., -0.5),
+        ..Keyframe::default()
+    };
+    let end = Keyframe {
+        translation: Vec3::new(4., 1., 0.5),
+        rotation: Quaternion::from_axis_angle(Vec3::new(0., 0., 1.), Degrees(90.)),
+        ..Keyframe::default()
+    };
+    world.add(Box::new(Instance::animated(
+        dumbbell,
+        AnimatedTransform::new(start, 0., end, 1.),
+    )));
+
+    let glass: MaterialPtr = Arc::new(Box::new(Dielectric::new(1.5)));
+    world.add(Box::new(Sphere::new(Point3::new(0., 1., 0.), 1.0, glass)));
+
+    let diffuse: MaterialPtr = Arc::new(Box::new(Lambertian::new(Color::new(0.4, 0.2, 0.1), 1.)));
+    world.add(Box::new(Sphere::new(
+        Point3::new(-4., 1., 0.),
+        1.0,
+        diffuse,
+    )));
+
+    world
+}
+
+/// A row of the analytic primitives on the ground plane in front of a mirror.
+pub fn primitives_scene() -> HittableList {
+    let mut world = empty_world();
+    add_ground(&mut world);
+
+    let mirror: MaterialPtr = Arc::new(Box::new(Metal::new(Color::new(0.8, 0.85, 0.9), 0.02)));
+    world.add(Box::new(Quad::new(
+        Point3::new(-4., 0., -4.),
+        Vec3::new(0., 0., 8.),
+        Vec3::new(-0.5, 3., 0.),
+        mirror,
+    )));
+
+    let gold: MaterialPtr = Arc::new(Box::new(Conductor::new(ComplexIor::GOLD, 0.3)));
+    world.add(Box::new(Disk::new(
+        Point3::new(-2., 1.2, 0.),
+        Vec3::new(1., 0., 0.),
+        1.,
+        gold,
+    )));
+
+    let red: MaterialPtr = Arc::new(Box::new(Lambertian::new(Color::new(0.7, 0.1, 0.1), 1.)));
+    world.add(Box::new(Cuboid::new(
+        Point3::new(-0.4, 0., -3.),
+        Point3::new(0.4, 0.8, -2.2),
+        red,
+    )));
+
+    let copper: MaterialPtr = Arc::new(Box::new(Conductor::new(ComplexIor::COPPER, 0.15)));
+    world.add(Box::new(Cylinder::new(
+        Point3::new(0., 0., -1.3),
+        Vec3::new(0., 1., 0.),
+        1.2,
+        0.4,
+        copper,
+    )));
+
+    let glass: MaterialPtr = Arc::new(Box::new(Dielectric::new(1.5)));
+    world.add(Box::new(Torus::new(
+        Point3::new(0., 0.7, 0.),
+        Vec3::new(1., 1., 0.),
+        0.45,
+        0.18,
+        glass,
+    )));
+
+    let blue: MaterialPtr = Arc::new(Box::new(Lambertian::new(Color::new(0.1, 0.2, 0.6), 1.)));
+    world.add(Box::new(Cone::new(
+        Point3::new(0., 0., 1.3),
+        Vec3::new(0., 1., 0.),
+        1.2,
+        0.5,
+        0.,
+        blue,
+    )));
+
+    let white: MaterialPtr = Arc::new(Box::new(Lambertian::new(Color::new(0.8, 0.8, 0.8), 1.)));
+    world.add(Box::new(Cuboid::oriented(
+        Point3::new(0., 0.5, 2.6),
+        Vec3::new(0.35, 0.5, 0.35),
+        Quaternion::from_axis_angle(Vec3::new(0., 1., 0.), Degrees(30.)),
+        white,
+    )));
+
+    world
+}
+
+/// Shapes built using constructive solid geometry.
+pub fn csg_scene() -> HittableList {
+    let mut world = empty_world();
+    add_ground(&mut world);
+
+    // A biconvex lens: the intersection of two large spheres.
+    let glass: MaterialPtr = Arc::new(Box::new(Dielectric::new(1.5)));
+    world.add(Box::new(Csg::intersection(
+        Box::new(Sphere::new(Point3::new(1.6, 1.3, -2.2), 2., glass.clone())),
+        Box::new(Sphere::new(Point3::new(-1.6, 1.3, -2.2), 2., glass)),
+    )));
+
+    // A bowl: a hollowed sphere with its top half cut away.
+    let copper: MaterialPtr = Arc::new(Box::new(Conductor::new(ComplexIor::COPPER, 0.2)));
+    let shell = Csg::difference(
+        Box::new(Sphere::new(Point3::new(0., 0.8, 0.), 0.8, copper.clone())),
+        Box::new(Sphere::new(Point3::new(0., 0.8, 0.), 0.72, copper.clone())),
+    );
+    world.add(Box::new(Csg::difference(
+        Box::new(shell),
+        Box::new(Cuboid::new(
+            Point3::new(-1., 1.1, -1.),
+            Point3::new(1., 2., 1.),
+            copper,
+        )),
+    )));
+
+    // A die: a rounded cube with a hole drilled along each axis.
+    let ivory: MaterialPtr = Arc::new(Box::new(Lambertian::new(Color::new(0.9, 0.85, 0.7), 1.)));
+    let center = Point3::new(0., 0.6, 2.2);
+    let rounded = Csg::intersection(
+        Box::new(Cuboid::new(
+            center - Vec3::new(0.6, 0.6, 0.6),
+            center + Vec3::new(0.6, 0.6, 0.6),
+            ivory.clone(),
+        )),
+        Box::new(Sphere::new(center, 0.8, ivory.clone())),
+    );
+    let drill = |axis: Vec3| -> Box<dyn Solid> {
+        Box::new(Cylinder::new(center - axis, axis, 2., 0.3, ivory.clone()))
+    };
+    let holes = Csg::new(
+        Box::new(Csg::union(
+            drill(Vec3::new(1., 0., 0.)),
+            drill(Vec3::new(0., 1., 0.)),
+        )),
+        Operation::Union,
+        drill(Vec3::new(0., 0., 1.)),
+    );
+    world.add(Box::new(Csg::difference(
+        Box::new(rounded),
+        Box::new(holes),
+    )));
+
+    world
+}
+
+/// Surfaces given by signed distance fields, including a Mandelbulb.
+pub fn sdf_scene() -> HittableList {
+    let mut world = empty_world();
+    add_ground(&mut world);
+
+    // The fractal is defined around the origin and placed using an instance.
+    let gold: MaterialPtr = Arc::new(Box::new(Conductor::new(ComplexIor::GOLD, 0.3)));
+    let bulb: HittablePtr = Arc::new(Box::new(
+        SdfObject::new(Box::new(Mandelbulb::default()), gold).with_max_steps(512),
+    ));
+    world.add(Box::new(Instance::new(
+        bulb,
+        Transform::scaling(Vec3::new(1.1, 1.1, 1.1))
+            .then(&Transform::translation(Vec3::new(0., 1.3, 0.))),
+    )));
+
+    // Two spheres and a torus melting into each other.
+    let blob = SmoothUnion::new(
+        Box::new(SmoothUnion::new(
+            Box::new(SdfSphere::new(Point3::new(0., 0.5, -2.4), 0.45)),
+            Box::new(SdfSphere::new(Point3::new(0., 1.2, -2.2), 0.3)),
+            0.3,
+        )),
+        Box::new(SdfTorus::new(Point3::new(0., 0.3, -2.4), 0.6, 0.12)),
+        0.2,
+    );
+    let jade: MaterialPtr = Arc::new(Box::new(
+        Principled::new(solid(Color::new(0.2, 0.6, 0.4)))
+            .with_roughness(0.2)
+            .with_clearcoat(1., 0.05),
+    ));
+    world.add(Box::new(SdfObject::new(Box::new(blob), jade)));
+
+    // A rounded box and a capsule given by a plain function.
+    let white: MaterialPtr = Arc::new(Box::new(Lambertian::new(Color::new(0.8, 0.8, 0.8), 1.)));
+    world.add(Box::new(SdfObject::new(
+        Box::new(
+            SdfBox::new(Point3::new(0., 0.4, 2.2), Vec3::new(0.4, 0.4, 0.4)).with_rounding(0.1),
+        ),
+        white,
+    )));
+
+    let glass: MaterialPtr = Arc::new(Box::new(Dielectric::new(1.5)));
+    let (a, b, radius) = (Point3::new(0., 0.2, 3.2), Point3::new(0., 1.4, 2.8), 0.2);
+    let capsule = FunctionField::new(
+        BoundingBox::new(Point3::new(-radius, 0., 2.6), Point3::new(radius, 1.6, 3.4)),
+        move |p: &Point3| {
+            let (pa, ba) = (*p - a, b - a);
+            let h = (pa.dot(&ba) / ba.dot(&ba)).clamp(0., 1.);
+            (pa - ba * h).len() - radius
+        },
+    );
+    world.add(Box::new(SdfObject::new(Box::new(capsule), glass)));
+
+    world
+}
+
+/// A heightfield terrain loaded from a PGM image or generated from noise, by a lake.
+pub fn terrain_scene(heightmap_file: Option<&str>) -> std::io::Result<HittableList> {
+    let mut world = empty_world();
+
+    let bounds = BoundingBox::new(Point3::new(-15., -1., -15.), Point3::new(15., 2.5, 15.));
+    let grass: MaterialPtr = Arc::new(Box::new(Lambertian::new(Color::new(0.35, 0.45, 0.2), 1.)));
+    let terrain = match heightmap_file {
+        Some(path) => Heightfield::load_pgm(path, bounds, grass)?,
+        None => {
+            let noise = ValueNoise::new(3);
+            Heightfield::procedural(256, 256, bounds, grass, |x, z| {
+                let detail = noise.fbm(&Point3::new(x * 12., 0., z * 12.), 6);
+                // Flatten the terrain towards the center to form a valley.
+                let valley = ((x - 0.5).powi(2) + (z - 0.5).powi(2)).sqrt() * 2.;
+                (detail * (0.3 + valley)).clamp(0., 1.)
+            })
+        }
+    };
+    world.add(Box::new(terrain));
+
+    let water: MaterialPtr = Arc::new(Box::new(Metal::new(Color::new(0.5, 0.6, 0.7), 0.05)));
+    world.add(Box::new(Plane::new(
+        Point3::new(0., -0.2, 0.),
+        Vec3::new(0., 1., 0.),
+        water,
+    )));
+
+    Ok(world)
+}
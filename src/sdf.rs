@@ -0,0 +1,266 @@
+//! Signed distance fields, rendered by the [`SdfObject`](crate::objects::SdfObject) hittable.
+
+use crate::primitives::BoundingBox;
+use crate::{Point3, Vec3};
+
+/// A function returning the signed distance to the closest surface.
+///
+/// Distances are negative inside the surface. They may underestimate, but
+/// must never overestimate, the true distance or sphere tracing overshoots.
+pub trait DistanceField: Send + Sync {
+    fn distance(&self, p: &Point3) -> f32;
+
+    /// Conservative bounds of the surface.
+    fn bounding_box(&self) -> BoundingBox;
+}
+
+pub type DistanceFieldPtr = Box<dyn DistanceField>;
+
+/// A distance field given by a function and bounds supplied by the caller.
+pub struct FunctionField<F> {
+    function: F,
+    bounds: BoundingBox,
+}
+
+impl<F> FunctionField<F>
+where
+    F: Fn(&Point3) -> f32 + Send + Sync,
+{
+    pub fn new(bounds: BoundingBox, function: F) -> Self {
+        Self { function, bounds }
+    }
+}
+
+impl<F> DistanceField for FunctionField<F>
+where
+    F: Fn(&Point3) -> f32 + Send + Sync,
+{
+    fn distance(&self, p: &Point3) -> f32 {
+        (self.function)(p)
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        self.bounds
+    }
+}
+
+pub struct SdfSphere {
+    center: Point3,
+    radius: f32,
+}
+
+impl SdfSphere {
+    pub fn new(center: Point3, radius: f32) -> Self {
+        Self { center, radius }
+    }
+}
+
+impl DistanceField for SdfSphere {
+    fn distance(&self, p: &Point3) -> f32 {
+        (*p - self.center).len() - self.radius
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        let r = Vec3::new(self.radius, self.radius, self.radius);
+        BoundingBox::new(self.center - r, self.center + r)
+    }
+}
+
+/// An axis-aligned box with optionally rounded edges.
+pub struct SdfBox {
+    center: Point3,
+    half_extents: Vec3,
+    rounding: f32,
+}
+
+impl SdfBox {
+    pub fn new(center: Point3, half_extents: Vec3) -> Self {
+        Self {
+            center,
+            half_extents,
+            rounding: 0.,
+        }
+    }
+
+    /// Rounds the edges with the specified radius, keeping the outer dimensions.
+    pub fn with_rounding(mut self, radius: f32) -> Self {
+        self.rounding = radius;
+        self
+    }
+}
+
+impl DistanceField for SdfBox {
+    fn distance(&self, p: &Point3) -> f32 {
+        let d = *p - self.center;
+        let q = Vec3::new(
+            d.x().abs() - self.half_extents.x() + self.rounding,
+            d.y().abs() - self.half_extents.y() + self.rounding,
+            d.z().abs() - self.half_extents.z() + self.rounding,
+        );
+        let outside = Vec3::new(q.x().max(0.), q.y().max(0.), q.z().max(0.)).len();
+        let inside = q.x().max(q.y()).max(q.z()).min(0.);
+        outside + inside - self.rounding
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        BoundingBox::new(
+            self.center - self.half_extents,
+            self.center + self.half_extents,
+        )
+    }
+}
+
+/// A torus around the Y axis.
+pub struct SdfTorus {
+    center: Point3,
+    major_radius: f32,
+    minor_radius: f32,
+}
+
+impl SdfTorus {
+    pub fn new(center: Point3, major_radius: f32, minor_radius: f32) -> Self {
+        Self {
+            center,
+            major_radius,
+            minor_radius,
+        }
+    }
+}
+
+impl DistanceField for SdfTorus {
+    fn distance(&self, p: &Point3) -> f32 {
+        let d = *p - self.center;
+        let ring = (d.x() * d.x() + d.z() * d.z()).sqrt() - self.major_radius;
+        (ring * ring + d.y() * d.y()).sqrt() - self.minor_radius
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        let outer = self.major_radius + self.minor_radius;
+        let extent = Vec3::new(outer, self.minor_radius, outer);
+        BoundingBox::new(self.center - extent, self.center + extent)
+    }
+}
+
+/// Blends two fields using the polynomial smooth minimum.
+pub struct SmoothUnion {
+    a: DistanceFieldPtr,
+    b: DistanceFieldPtr,
+    /// The distance over which the two surfaces are blended.
+    smoothness: f32,
+}
+
+impl SmoothUnion {
+    pub fn new(a: DistanceFieldPtr, b: DistanceFieldPtr, smoothness: f32) -> Self {
+        Self { a, b, smoothness }
+    }
+}
+
+impl DistanceField for SmoothUnion {
+    fn distance(&self, p: &Point3) -> f32 {
+        let (a, b) = (self.a.distance(p), self.b.distance(p));
+        let k = self.smoothness.max(1e-6);
+        let h = (0.5 + 0.5 * (b - a) / k).clamp(0., 1.);
+        b + (a - b) * h - k * h * (1. - h)
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        // The blend grows the surface by at most a quarter of the smoothness.
+        let bounds = self.a.bounding_box().union(&self.b.bounding_box());
+        let grow = 0.25 * self.smoothness;
+        let grow = Vec3::new(grow, grow, grow);
+        BoundingBox::new(bounds.min - grow, bounds.max + grow)
+    }
+}
+
+/// The Mandelbulb fractal around the origin, fitting into the unit sphere.
+///
+/// Uses the usual distance estimator `0.5 · ln(r) · r / dr` on the
+/// spherical coordinate formula with the Y axis as the pole.
+pub struct Mandelbulb {
+    power: f32,
+    iterations: usize,
+}
+
+impl Mandelbulb {
+    pub fn new(power: f32, iterations: usize) -> Self {
+        Self { power, iterations }
+    }
+}
+
+impl Default for Mandelbulb {
+    fn default() -> Self {
+        Self::new(8., 10)
+    }
+}
+
+impl DistanceField for Mandelbulb {
+    fn distance(&self, p: &Point3) -> f32 {
+        let mut z = *p;
+        let mut dr = 1.;
+        let mut r = z.len();
+        for _ in 0..self.iterations {
+            if r > 2. {
+                break;
+            }
+
+            let theta = (z.y() / r).clamp(-1., 1.).acos() * self.power;
+            let phi = z.z().atan2(z.x()) * self.power;
+            dr = r.powf(self.power - 1.) * self.power * dr + 1.;
+
+            let zr = r.powf(self.power);
+            z =
+                zr * Vec3::new(
+                    theta.sin() * phi.cos(),
+                    theta.cos(),
+                    theta.sin() * phi.sin(),
+                ) + *p;
+            r = z.len();
+        }
+        0.5 * r.ln() * r / dr
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        // The bulb of power 8 reaches about 1.14 units from the origin.
+        BoundingBox::new(Point3::new(-1.2, -1.2, -1.2), Point3::new(1.2, 1.2, 1.2))
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    pub fn primitives_work() {
+        let sphere = SdfSphere::new(Point3::new(1., 0., 0.), 1.);
+        assert!((sphere.distance(&Point3::new(3., 0., 0.)) - 1.).abs() < 1e-6);
+        assert!((sphere.distance(&Point3::new(1., 0., 0.)) + 1.).abs() < 1e-6);
+
+        let cube = SdfBox::new(Point3::default(), Vec3::new(1., 1., 1.));
+        assert!((cube.distance(&Point3::new(2., 0., 0.)) - 1.).abs() < 1e-6);
+        assert!((cube.distance(&Point3::new(2., 2., 1.)) - 2f32.sqrt()).abs() < 1e-6);
+
+        let torus = SdfTorus::new(Point3::default(), 2., 0.5);
+        assert!(torus.distance(&Point3::new(2., 0., 0.)) < 0.);
+        assert!((torus.distance(&Point3::default()) - 1.5).abs() < 1e-6);
+    }
+
+    #[test]
+    pub fn smooth_union_blends() {
+        let union = SmoothUnion::new(
+            Box::new(SdfSphere::new(Point3::new(-1., 0., 0.), 0.9)),
+            Box::new(SdfSphere::new(Point3::new(1., 0., 0.), 0.9)),
+            0.5,
+        );
+        // The spheres do not touch, but the blend closes the gap between them.
+        assert!(union.distance(&Point3::default()) < 0.);
+        // Far away from the blend region, the distance is unchanged.
+        assert!((union.distance(&Point3::new(3., 0., 0.)) - 1.1).abs() < 1e-6);
+    }
+
+    #[test]
+    pub fn mandelbulb_is_bounded() {
+        let bulb = Mandelbulb::default();
+        assert!(bulb.distance(&Point3::new(0.1, 0.2, 0.1)) < 0.);
+        assert!(bulb.distance(&Point3::new(1.3, 0., 0.)) > 0.);
+    }
+}
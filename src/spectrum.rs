@@ -0,0 +1,209 @@
+//! Spectral rendering using hero wavelength sampling.
+//!
+//! In spectral mode every path carries three wavelengths, evenly spaced across the visible
+//! range starting at a random hero wavelength, in the channels of its colors. RGB colors
+//! of the scene are uplifted to spectra and sampled at these wavelengths, and the spectral
+//! radiance is accumulated as CIE XYZ before being converted to the working space.
+
+use crate::color_space::{adaptation_to_d65, invert, multiply, transform, ColorSpace, Matrix3};
+use crate::random::Random;
+use crate::{Color, Vec3};
+
+/// The shortest wavelength in nanometers that is sampled.
+pub const LAMBDA_MIN: f32 = 380.;
+
+/// The longest wavelength in nanometers that is sampled.
+pub const LAMBDA_MAX: f32 = 720.;
+
+/// A piecewise Gaussian with different widths left and right of its mean.
+fn gaussian(lambda: f32, mean: f32, left: f32, right: f32) -> f32 {
+    let sigma = if lambda < mean { left } else { right };
+    let t = (lambda - mean) / sigma;
+    (-0.5 * t * t).exp()
+}
+
+/// The CIE 1931 standard observer color matching functions at the specified wavelength,
+/// using the multi-lobe fit by Wyman, Sloan and Shirley.
+pub fn color_matching(lambda: f32) -> Color {
+    Color::new(
+        1.056 * gaussian(lambda, 599.8, 37.9, 31.0) + 0.362 * gaussian(lambda, 442.0, 16.0, 26.7)
+            - 0.065 * gaussian(lambda, 501.1, 20.4, 26.2),
+        0.821 * gaussian(lambda, 568.8, 46.9, 40.5) + 0.286 * gaussian(lambda, 530.9, 16.3, 31.1),
+        1.217 * gaussian(lambda, 437.0, 11.8, 36.0) + 0.681 * gaussian(lambda, 459.0, 26.0, 13.8),
+    )
+}
+
+/// The basis spectra of Smits' RGB to spectrum conversion, sampled
+/// at ten evenly spaced wavelengths from `LAMBDA_MIN` to `LAMBDA_MAX`.
+mod smits {
+    pub const WHITE: [f32; 10] = [1., 1., 0.9999, 0.9993, 0.9992, 0.9998, 1., 1., 1., 1.];
+    pub const CYAN: [f32; 10] = [
+        0.971, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0., 0., 0.,
+    ];
+    pub const MAGENTA: [f32; 10] = [1., 1., 0.9685, 0.2229, 0., 0.0458, 0.8369, 1., 1., 0.9959];
+    pub const YELLOW: [f32; 10] = [
+        0.0001, 0., 0.1088, 0.6651, 1., 1., 0.9996, 0.9586, 0.9685, 0.984,
+    ];
+    pub const RED: [f32; 10] = [
+        0.1012, 0.0515, 0., 0., 0., 0., 0.8325, 1.0149, 1.0149, 1.0149,
+    ];
+    pub const GREEN: [f32; 10] = [0., 0., 0.0273, 0.7937, 1., 0.9418, 0.1719, 0., 0., 0.0025];
+    pub const BLUE: [f32; 10] = [
+        1., 1., 0.8916, 0.3323, 0., 0., 0.0003, 0.0369, 0.0483, 0.0496,
+    ];
+}
+
+/// Linearly interpolates a basis spectrum at the specified wavelength.
+fn basis(spectrum: &[f32; 10], lambda: f32) -> f32 {
+    let x = ((lambda - LAMBDA_MIN) / (LAMBDA_MAX - LAMBDA_MIN) * 9.).clamp(0., 9.);
+    let i = (x as usize).min(8);
+    let t = x - i as f32;
+    spectrum[i] * (1. - t) + spectrum[i + 1] * t
+}
+
+/// Uplifts a linear Rec. 709 color to a smooth spectrum using Smits' method
+/// and samples it at the three specified wavelengths.
+pub fn uplift(color: &Color, wavelengths: &Vec3) -> Color {
+    let (r, g, b) = (color.x(), color.y(), color.z());
+    // Express the color as white plus one secondary and one primary.
+    let terms = if r <= g && r <= b {
+        if g <= b {
+            [
+                (r, &smits::WHITE),
+                (g - r, &smits::CYAN),
+                (b - g, &smits::BLUE),
+            ]
+        } else {
+            [
+                (r, &smits::WHITE),
+                (b - r, &smits::CYAN),
+                (g - b, &smits::GREEN),
+            ]
+        }
+    } else if g <= r && g <= b {
+        if r <= b {
+            [
+                (g, &smits::WHITE),
+                (r - g, &smits::MAGENTA),
+                (b - r, &smits::BLUE),
+            ]
+        } else {
+            [
+                (g, &smits::WHITE),
+                (b - g, &smits::MAGENTA),
+                (r - b, &smits::RED),
+            ]
+        }
+    } else if r <= g {
+        [
+            (b, &smits::WHITE),
+            (r - b, &smits::YELLOW),
+            (g - r, &smits::GREEN),
+        ]
+    } else {
+        [
+            (b, &smits::WHITE),
+            (g - b, &smits::YELLOW),
+            (r - g, &smits::RED),
+        ]
+    };
+
+    let sample = |lambda: f32| {
+        terms
+            .iter()
+            .map(|(weight, spectrum)| weight * basis(spectrum, lambda))
+            .sum()
+    };
+    Color::new(
+        sample(wavelengths.x()),
+        sample(wavelengths.y()),
+        sample(wavelengths.z()),
+    )
+}
+
+/// Samples the wavelengths of paths and converts their spectral radiance to RGB.
+pub struct SpectralSampler {
+    /// Converts the XYZ estimate into the working space, white balancing
+    /// the equal-energy spectrum to the D65 white point.
+    xyz_to_working: Matrix3,
+    /// The integral of the luminance matching function over the sampled range.
+    y_integral: f32,
+}
+
+impl SpectralSampler {
+    pub fn new(working_space: ColorSpace) -> Self {
+        // Integrate the matching functions to find the white of the equal-energy spectrum.
+        let mut white = Color::default();
+        for step in 0..(LAMBDA_MAX - LAMBDA_MIN) as usize {
+            white += color_matching(LAMBDA_MIN + step as f32 + 0.5);
+        }
+        let y_integral = white.y();
+        let white = white / y_integral;
+
+        Self {
+            xyz_to_working: multiply(&invert(&working_space.to_xyz()), &adaptation_to_d65(&white)),
+            y_integral,
+        }
+    }
+
+    /// Picks a random hero wavelength and two more at equal spacing, wrapping around.
+    pub fn sample_wavelengths(&self, rng: &Random) -> Vec3 {
+        let range = LAMBDA_MAX - LAMBDA_MIN;
+        let hero = rng.sample() * range;
+        let wavelength = |i: f32| LAMBDA_MIN + (hero + i * range / 3.) % range;
+        Vec3::new(wavelength(0.), wavelength(1.), wavelength(2.))
+    }
+
+    /// Converts radiance sampled at the specified wavelengths into the working space.
+    pub fn to_rgb(&self, radiance: &Color, wavelengths: &Vec3) -> Color {
+        let mut xyz = Color::default();
+        for (value, lambda) in radiance.e.iter().zip(wavelengths.e) {
+            xyz += *value * color_matching(lambda);
+        }
+        // Each wavelength is sampled with the uniform density `1 / range`.
+        let xyz = xyz * ((LAMBDA_MAX - LAMBDA_MIN) / 3. / self.y_integral);
+        transform(&self.xyz_to_working, &xyz)
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    /// Converts a color into a spectrum and back, averaging over all wavelengths.
+    fn round_trip(sampler: &SpectralSampler, color: &Color) -> Color {
+        let count = (LAMBDA_MAX - LAMBDA_MIN) as usize;
+        let mut sum = Color::default();
+        for step in 0..count {
+            let lambda = LAMBDA_MIN + step as f32 + 0.5;
+            let wavelengths = Vec3::new(lambda, lambda, lambda);
+            sum += sampler.to_rgb(&uplift(color, &wavelengths), &wavelengths);
+        }
+        sum / count as f32
+    }
+
+    #[test]
+    pub fn sample_wavelengths_works() {
+        let sampler = SpectralSampler::new(ColorSpace::Rec709);
+        let wavelengths = sampler.sample_wavelengths(&Random::default());
+        for lambda in wavelengths.e {
+            assert!((LAMBDA_MIN..LAMBDA_MAX).contains(&lambda));
+        }
+        let spacing = (wavelengths.y() - wavelengths.x()).rem_euclid(LAMBDA_MAX - LAMBDA_MIN);
+        assert!((spacing - (LAMBDA_MAX - LAMBDA_MIN) / 3.).abs() < 1e-3);
+    }
+
+    #[test]
+    pub fn uplifted_colors_round_trip() {
+        let sampler = SpectralSampler::new(ColorSpace::Rec709);
+        for color in [
+            Color::new(1., 1., 1.),
+            Color::new(0.8, 0.3, 0.1),
+            Color::new(0.1, 0.6, 0.3),
+            Color::new(0.2, 0.3, 0.9),
+        ] {
+            let result = round_trip(&sampler, &color);
+            assert!((result - color).len() < 0.08, "{:?} != {:?}", result, color);
+        }
+    }
+}
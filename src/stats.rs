@@ -0,0 +1,348 @@
+//! Statistics collected while rendering.
+//!
+//! Counting is disabled unless [`enable`]d, so renders without statistics don't pay for it.
+//! Every thread counts into its own set of counters, which are only summed up for the report,
+//! so counting does not contend between threads.
+
+use crate::material::MaterialKind;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter, Write as _};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The kinds of rays counted.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RayType {
+    /// Rays leaving the camera.
+    Camera,
+    /// Rays scattered by a material.
+    Bounce,
+    /// Rays testing the visibility of lights. No integrator casts these yet,
+    /// so they are reported as zero.
+    Shadow,
+}
+
+#[derive(Default)]
+struct ThreadCounters {
+    rays: [AtomicU64; 3],
+    intersection_tests: AtomicU64,
+    node_visits: AtomicU64,
+    /// The number of scattered rays, indexed by [`MaterialKind`].
+    scatters: [AtomicU64; MaterialKind::COUNT],
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+static THREADS: Mutex<Vec<Arc<ThreadCounters>>> = Mutex::new(Vec::new());
+
+/// The time spent building acceleration structures, in nanoseconds.
+static ACCELERATION_BUILD: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    static COUNTERS: Arc<ThreadCounters> = {
+        let counters = Arc::new(ThreadCounters::default());
+        THREADS.lock().unwrap().push(counters.clone());
+        counters
+    };
+}
+
+/// Increments a counter only ever written by the current thread, which avoids
+/// the cost of an atomic read-modify-write.
+fn increment(counter: &AtomicU64) {
+    counter.store(counter.load(Ordering::Relaxed) + 1, Ordering::Relaxed);
+}
+
+/// Turns on counting for the rest of the program.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Runs `f` with the counters of the current thread, if counting is enabled.
+#[inline]
+fn count(f: impl FnOnce(&ThreadCounters)) {
+    if ENABLED.load(Ordering::Relaxed) {
+        COUNTERS.with(|counters| f(counters));
+    }
+}
+
+pub fn count_ray(ray_type: RayType) {
+    count(|counters| increment(&counters.rays[ray_type as usize]));
+}
+
+/// Counts testing a ray against an object.
+pub fn count_intersection_test() {
+    count(|counters| increment(&counters.intersection_tests));
+}
+
+/// Counts testing a ray against the bounds of a node or element of an acceleration structure.
+pub fn count_node_visit() {
+    count(|counters| increment(&counters.node_visits));
+}
+
+/// Reads a counter of the current thread, which stays at zero unless counting is enabled.
+fn read(counter: impl FnOnce(&ThreadCounters) -> &AtomicU64) -> u64 {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return 0;
+    }
+    COUNTERS.with(|counters| counter(counters).load(Ordering::Relaxed))
+}
+
+/// The number of object intersection tests counted by the current thread so far.
+pub fn thread_intersection_tests() -> u64 {
+    read(|counters| &counters.intersection_tests)
+}
+
+/// The number of node visits counted by the current thread so far.
+pub fn thread_node_visits() -> u64 {
+    read(|counters| &counters.node_visits)
+}
+
+pub fn count_scatter(material: MaterialKind) {
+    count(|counters| increment(&counters.scatters[material as usize]));
+}
+
+pub fn add_acceleration_build_time(duration: Duration) {
+    ACCELERATION_BUILD.fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+}
+
+pub fn acceleration_build_time() -> Duration {
+    Duration::from_nanos(ACCELERATION_BUILD.load(Ordering::Relaxed))
+}
+
+/// The counts summed over all threads.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Counters {
+    pub camera_rays: u64,
+    pub bounce_rays: u64,
+    pub shadow_rays: u64,
+    pub intersection_tests: u64,
+    pub node_visits: u64,
+    /// The number of scattered rays per material type.
+    pub scatters: BTreeMap<&'static str, u64>,
+}
+
+impl Counters {
+    /// Sums up the counters of all threads so far.
+    pub fn collect() -> Self {
+        let mut total = Counters::default();
+        for counters in THREADS.lock().unwrap().iter() {
+            let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+            total.camera_rays += load(&counters.rays[RayType::Camera as usize]);
+            total.bounce_rays += load(&counters.rays[RayType::Bounce as usize]);
+            total.shadow_rays += load(&counters.rays[RayType::Shadow as usize]);
+            total.intersection_tests += load(&counters.intersection_tests);
+            total.node_visits += load(&counters.node_visits);
+            for (material, count) in MaterialKind::ALL.iter().zip(&counters.scatters) {
+                match load(count) {
+                    0 => {}
+                    count => *total.scatters.entry(material.name()).or_default() += count,
+                }
+            }
+        }
+        total
+    }
+
+    pub fn total_rays(&self) -> u64 {
+        self.camera_rays + self.bounce_rays + self.shadow_rays
+    }
+
+    /// The average number of rays per camera path, excluding shadow rays.
+    pub fn average_path_length(&self) -> f64 {
+        if self.camera_rays == 0 {
+            return 0.;
+        }
+        (self.camera_rays + self.bounce_rays) as f64 / self.camera_rays as f64
+    }
+}
+
+/// The statistics of a render, printed as text or exported as JSON.
+pub struct Report {
+    pub counters: Counters,
+    /// The named phases of the render and the time spent in each.
+    pub phases: Vec<(&'static str, Duration)>,
+}
+
+impl Report {
+    fn render_time(&self) -> f64 {
+        self.phases
+            .iter()
+            .find(|(name, _)| *name == "render")
+            .map_or(0., |(_, duration)| duration.as_secs_f64())
+    }
+
+    fn per_second(&self, count: u64) -> f64 {
+        match self.render_time() {
+            time if time > 0. => count as f64 / time,
+            _ => 0.,
+        }
+    }
+
+    fn rays(&self) -> [(&'static str, u64); 4] {
+        let counters = &self.counters;
+        [
+            ("camera", counters.camera_rays),
+            ("bounce", counters.bounce_rays),
+            ("shadow", counters.shadow_rays),
+            ("total", counters.total_rays()),
+        ]
+    }
+
+    pub fn to_json(&self) -> String {
+        let counters = &self.counters;
+        let object = |entries: Vec<String>| format!("{{{}}}", entries.join(", "));
+
+        let rays = object(
+            self.rays()
+                .iter()
+                .map(|(name, count)| format!("\"{}\": {}", name, count))
+                .collect(),
+        );
+        let rays_per_second = object(
+            self.rays()
+                .iter()
+                .map(|(name, count)| format!("\"{}\": {:.1}", name, self.per_second(*count)))
+                .collect(),
+        );
+        let scatters = object(
+            counters
+                .scatters
+                .iter()
+                .map(|(material, count)| format!("\"{}\": {}", material, count))
+                .collect(),
+        );
+        let phases = object(
+            self.phases
+                .iter()
+                .map(|(name, duration)| format!("\"{}\": {:.6}", name, duration.as_secs_f64()))
+                .collect(),
+        );
+
+        let mut json = String::from("{\n");
+        writeln!(json, "  \"rays\": {},", rays).unwrap();
+        writeln!(json, "  \"rays_per_second\": {},", rays_per_second).unwrap();
+        writeln!(
+            json,
+            "  \"average_path_length\": {:.4},",
+            counters.average_path_length()
+        )
+        .unwrap();
+        writeln!(
+            json,
+            "  \"intersection_tests\": {},",
+            counters.intersection_tests
+        )
+        .unwrap();
+        writeln!(json, "  \"node_visits\": {},", counters.node_visits).unwrap();
+        writeln!(json, "  \"scatters\": {},", scatters).unwrap();
+        writeln!(json, "  \"seconds\": {}", phases).unwrap();
+        json.push_str("}\n");
+        json
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let counters = &self.counters;
+        writeln!(f, "Rays:")?;
+        for (name, count) in self.rays() {
+            writeln!(
+                f,
+                "  {:<24} {:>14} ({:.0}/s)",
+                name,
+                count,
+                self.per_second(count)
+            )?;
+        }
+        writeln!(
+            f,
+            "  {:<24} {:>14.2}",
+            "average path length",
+            counters.average_path_length()
+        )?;
+
+        let per_ray = |count: u64| match counters.total_rays() {
+            0 => 0.,
+            rays => count as f64 / rays as f64,
+        };
+        writeln!(f, "Intersections:")?;
+        writeln!(
+            f,
+            "  {:<24} {:>14} ({:.2} per ray)",
+            "object tests",
+            counters.intersection_tests,
+            per_ray(counters.intersection_tests)
+        )?;
+        writeln!(
+            f,
+            "  {:<24} {:>14} ({:.2} per ray)",
+            "node visits",
+            counters.node_visits,
+            per_ray(counters.node_visits)
+        )?;
+
+        writeln!(f, "Scattered rays:")?;
+        for (material, count) in &counters.scatters {
+            writeln!(f, "  {:<24} {:>14}", material, count)?;
+        }
+
+        writeln!(f, "Time:")?;
+        for (name, duration) in &self.phases {
+            writeln!(f, "  {:<24} {:>13.3}s", name, duration.as_secs_f64())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    pub fn counts_from_all_threads_are_summed() {
+        enable();
+        let before = Counters::collect();
+        let threads = (0..4)
+            .map(|_| {
+                std::thread::spawn(|| {
+                    count_ray(RayType::Camera);
+                    count_ray(RayType::Bounce);
+                    count_ray(RayType::Bounce);
+                    count_scatter(MaterialKind::Conductor);
+                })
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        // Other tests may count concurrently, so only check for lower bounds.
+        let after = Counters::collect();
+        assert!(after.camera_rays >= before.camera_rays + 4);
+        assert!(after.bounce_rays >= before.bounce_rays + 8);
+        assert!(after.scatters["Conductor"] >= before.scatters.get("Conductor").unwrap_or(&0) + 4);
+    }
+
+    #[test]
+    pub fn report_works() {
+        let mut counters = Counters {
+            camera_rays: 10,
+            bounce_rays: 15,
+            ..Counters::default()
+        };
+        counters.scatters.insert("Lambertian", 15);
+        assert_eq!(counters.average_path_length(), 2.5);
+
+        let report = Report {
+            counters,
+            phases: vec![("render", Duration::from_secs(5))],
+        };
+        let json = report.to_json();
+        assert!(json
+            .contains("\"rays\": {\"camera\": 10, \"bounce\": 15, \"shadow\": 0, \"total\": 25}"));
+        assert!(json.contains("\"total\": 5.0}"));
+        assert!(json.contains("\"scatters\": {\"Lambertian\": 15}"));
+        assert!(json.contains("\"seconds\": {\"render\": 5.000000}"));
+        assert!(report.to_string().contains("average path length"));
+    }
+}
@@ -0,0 +1,238 @@
+//! Previews of the image drawn into the terminal while it renders.
+
+use crate::film::Image;
+use crate::tonemap::ColorPipeline;
+use crate::Color;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+/// The time between redraws of the preview.
+const INTERVAL: Duration = Duration::from_secs(2);
+
+/// How the preview is drawn.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TerminalGraphics {
+    /// Unicode upper half blocks with 24-bit colors, showing two pixels per character.
+    Blocks,
+    /// Images sent using the kitty graphics protocol, scaled to fill the character cells.
+    Kitty,
+}
+
+impl TerminalGraphics {
+    /// Uses the kitty graphics protocol if the terminal is known to support it.
+    pub fn detect() -> Self {
+        let kitty = std::env::var_os("KITTY_WINDOW_ID").is_some()
+            || std::env::var("TERM").is_ok_and(|term| term.contains("kitty"))
+            || std::env::var("TERM_PROGRAM").is_ok_and(|program| program == "WezTerm");
+        if kitty {
+            TerminalGraphics::Kitty
+        } else {
+            TerminalGraphics::Blocks
+        }
+    }
+}
+
+/// Periodically redraws a downsampled image in place, followed by a status line.
+pub struct TerminalPreview {
+    graphics: TerminalGraphics,
+    columns: usize,
+    last_draw: Option<Instant>,
+    /// The number of lines written by the last draw.
+    lines: usize,
+}
+
+impl TerminalPreview {
+    pub fn new(graphics: TerminalGraphics, columns: usize) -> Self {
+        Self {
+            graphics,
+            columns,
+            last_draw: None,
+            lines: 0,
+        }
+    }
+
+    /// Whether the preview should be redrawn.
+    pub fn is_due(&self) -> bool {
+        self.last_draw
+            .is_none_or(|last_draw| last_draw.elapsed() >= INTERVAL)
+    }
+
+    /// Draws the image over the previous preview.
+    pub fn draw(
+        &mut self,
+        image: &Image,
+        pipeline: &ColorPipeline,
+        status: &str,
+    ) -> std::io::Result<()> {
+        let columns = self.columns.min(image.width);
+        // Characters are about twice as high as wide.
+        let rows = ((columns * image.height) as f32 / image.width as f32 / 2.)
+            .ceil()
+            .max(1.) as usize;
+
+        let mut output = String::new();
+        if self.lines > 0 {
+            write!(output, "\r\x1b[{}A", self.lines).unwrap();
+        }
+        match self.graphics {
+            TerminalGraphics::Blocks => {
+                let pixels = downsample(image, columns, 2 * rows);
+                output += &blocks(&pixels, columns, pipeline);
+            }
+            TerminalGraphics::Kitty => {
+                const PIXELS_PER_COLUMN: usize = 8;
+                let width = (columns * PIXELS_PER_COLUMN).min(image.width);
+                let height = (width * image.height / image.width).max(1);
+                let pixels = downsample(image, width, height);
+                output += &kitty(&pixels, width, (columns, rows), pipeline);
+                output += &"\n".repeat(rows);
+            }
+        }
+        writeln!(output, "\x1b[2K{}", status).unwrap();
+
+        let mut stderr = std::io::stderr();
+        stderr.write_all(output.as_bytes())?;
+        stderr.flush()?;
+        self.lines = rows + 1;
+        self.last_draw = Some(Instant::now());
+        Ok(())
+    }
+}
+
+/// Averages the image into `width` by `height` pixels, ordered from the top left corner.
+fn downsample(image: &Image, width: usize, height: usize) -> Vec<Color> {
+    let mut pixels = Vec::with_capacity(width * height);
+    for y in 0..height {
+        let start = y * image.height / height;
+        let end = ((y + 1) * image.height / height).max(start + 1);
+        // Rows of the image are counted from the bottom.
+        let (bottom, top) = (image.height - end, image.height - start);
+        for x in 0..width {
+            let left = x * image.width / width;
+            let right = ((x + 1) * image.width / width).max(left + 1);
+            let mut sum = Color::default();
+            for j in bottom..top {
+                for i in left..right {
+                    sum += image.get(i, j);
+                }
+            }
+            pixels.push(sum / ((top - bottom) * (right - left)) as f32);
+        }
+    }
+    pixels
+}
+
+/// Draws pairs of pixel rows as upper half blocks, with the lower row as the background.
+fn blocks(pixels: &[Color], width: usize, pipeline: &ColorPipeline) -> String {
+    let rows = pixels.chunks(width).collect::<Vec<_>>();
+    let mut output = String::new();
+    for pair in rows.chunks(2) {
+        for x in 0..width {
+            let [r, g, b] = pipeline.quantize(&pair[0][x]);
+            write!(output, "\x1b[38;2;{};{};{}m", r, g, b).unwrap();
+            match pair.get(1) {
+                Some(lower) => {
+                    let [r, g, b] = pipeline.quantize(&lower[x]);
+                    write!(output, "\x1b[48;2;{};{};{}m", r, g, b).unwrap();
+                }
+                None => output += "\x1b[49m",
+            }
+            output.push('▀');
+        }
+        output += "\x1b[0m\n";
+    }
+    output
+}
+
+/// Sends the pixels as an image scaled to the `(columns, rows)` character cells,
+/// leaving the cursor in place.
+fn kitty(
+    pixels: &[Color],
+    width: usize,
+    (columns, rows): (usize, usize),
+    pipeline: &ColorPipeline,
+) -> String {
+    const CHUNK_SIZE: usize = 4096;
+    let data = pixels
+        .iter()
+        .flat_map(|pixel| pipeline.quantize(pixel))
+        .collect::<Vec<u8>>();
+    let encoded = base64(&data);
+    let chunks = encoded.as_bytes().chunks(CHUNK_SIZE).collect::<Vec<_>>();
+
+    let mut output = String::new();
+    for (index, chunk) in chunks.iter().enumerate() {
+        let more = (index + 1 < chunks.len()) as u8;
+        if index == 0 {
+            write!(
+                output,
+                "\x1b_Ga=T,q=2,C=1,i=1,f=24,s={},v={},c={},r={},m={};",
+                width,
+                pixels.len() / width,
+                columns,
+                rows,
+                more
+            )
+            .unwrap();
+        } else {
+            write!(output, "\x1b_Gm={};", more).unwrap();
+        }
+        output += std::str::from_utf8(chunk).unwrap();
+        output += "\x1b\\";
+    }
+    output
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut output = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let bits = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                output.push(ALPHABET[(bits >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    pub fn base64_works() {
+        assert_eq!(base64(b"Man"), "TWFu");
+        assert_eq!(base64(b"Ma"), "TWE=");
+        assert_eq!(base64(b"M"), "TQ==");
+        assert_eq!(base64(b""), "");
+    }
+
+    #[test]
+    pub fn downsampling_averages_from_the_top() {
+        let mut image = Image::new(4, 4);
+        for x in 0..4 {
+            // Light the top two rows.
+            image.set(x, 3, Color::new(1., 1., 1.));
+            image.set(x, 2, Color::new(1., 0., 0.));
+        }
+        let pixels = downsample(&image, 2, 2);
+        assert_eq!(pixels[0], Color::new(1., 0.5, 0.5));
+        assert_eq!(pixels[1], Color::new(1., 0.5, 0.5));
+        assert_eq!(pixels[2], Color::default());
+
+        let output = blocks(&pixels, 2, &ColorPipeline::default());
+        assert_eq!(output.lines().count(), 1);
+        assert_eq!(output.matches('▀').count(), 2);
+        assert!(output.contains("\x1b[48;2;0;0;0m"));
+    }
+}
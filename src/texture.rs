@@ -0,0 +1,19 @@
+mod checker;
+mod solid_color;
+
+use crate::{Color, Point3};
+pub use checker::Checker;
+pub use solid_color::SolidColor;
+use std::sync::Arc;
+
+pub type TexturePtr = Arc<Box<dyn Texture>>;
+
+pub trait Texture: Send + Sync {
+    /// Looks up the texture's color at the specified surface coordinates.
+    ///
+    /// # Arguments
+    /// * `u`: The horizontal texture coordinate in range `0.0..=1.0`.
+    /// * `v`: The vertical texture coordinate in range `0.0..=1.0`.
+    /// * `p`: The hit point in world space, used by solid textures.
+    fn value(&self, u: f32, v: f32, p: &Point3) -> Color;
+}
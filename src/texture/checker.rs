@@ -0,0 +1,33 @@
+use crate::texture::{Texture, TexturePtr};
+use crate::{Color, Point3};
+
+/// A solid checkerboard pattern alternating between two textures.
+pub struct Checker {
+    even: TexturePtr,
+    odd: TexturePtr,
+    /// The number of checker cells per world unit.
+    frequency: f32,
+}
+
+impl Checker {
+    pub fn new(even: TexturePtr, odd: TexturePtr, frequency: f32) -> Self {
+        Self {
+            even,
+            odd,
+            frequency,
+        }
+    }
+}
+
+impl Texture for Checker {
+    fn value(&self, u: f32, v: f32, p: &Point3) -> Color {
+        let x = (self.frequency * p.x()).floor() as i64;
+        let y = (self.frequency * p.y()).floor() as i64;
+        let z = (self.frequency * p.z()).floor() as i64;
+        if (x + y + z) % 2 == 0 {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+}
@@ -0,0 +1,283 @@
+//! The pipeline turning rendered linear radiance into displayable 8-bit colors.
+
+use crate::color_space::{transform, ColorConversion, ColorSpace, Matrix3};
+use crate::random::Random;
+use crate::Color;
+
+/// Compresses scene-referred radiance into the displayable range `0.0..=1.0`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ToneMapping {
+    /// Clamps each channel, blowing out highlights.
+    Clamp,
+    /// Reinhard's operator applied to the luminance. Luminances at or above `white`
+    /// map to white; without a white point, highlights approach white asymptotically.
+    Reinhard { white: Option<f32> },
+    /// Stephen Hill's fit of the ACES reference and sRGB output transforms.
+    Aces,
+    /// Troy Sobotka's AgX, desaturating highlights towards white instead of skewing hues.
+    Agx,
+}
+
+/// The opto-electronic transfer function encoding linear values for display.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TransferFunction {
+    /// The piecewise sRGB curve.
+    Srgb,
+    /// The ITU-R BT.709 camera curve.
+    Rec709,
+    /// A plain power law with the specified gamma.
+    Gamma(f32),
+}
+
+/// Rec. 709 luminance of a linear color.
+fn luminance(color: &Color) -> f32 {
+    0.2126 * color.x() + 0.7152 * color.y() + 0.0722 * color.z()
+}
+
+fn map_channels(c: &Color, f: impl Fn(f32) -> f32) -> Color {
+    Color::new(f(c.x()), f(c.y()), f(c.z()))
+}
+
+impl ToneMapping {
+    pub fn apply(&self, color: &Color) -> Color {
+        let mapped = match self {
+            ToneMapping::Clamp => *color,
+            ToneMapping::Reinhard { white } => {
+                let l = luminance(color);
+                if l <= 0. {
+                    return Color::default();
+                }
+                let mapped = match white {
+                    Some(white) => l * (1. + l / (white * white)) / (1. + l),
+                    None => l / (1. + l),
+                };
+                *color * (mapped / l)
+            }
+            ToneMapping::Aces => {
+                const INPUT: Matrix3 = [
+                    [0.59719, 0.35458, 0.04823],
+                    [0.07600, 0.90834, 0.01566],
+                    [0.02840, 0.13383, 0.83777],
+                ];
+                const OUTPUT: Matrix3 = [
+                    [1.60475, -0.53108, -0.07367],
+                    [-0.10208, 1.10813, -0.00605],
+                    [-0.00327, -0.07276, 1.07602],
+                ];
+                let v = transform(&INPUT, color);
+                let v = map_channels(&v, |v| {
+                    let a = v * (v + 0.024_578_6) - 0.000_090_537;
+                    let b = v * (0.983_729 * v + 0.432_951) + 0.238_081;
+                    a / b
+                });
+                transform(&OUTPUT, &v)
+            }
+            ToneMapping::Agx => {
+                const INSET: Matrix3 = [
+                    [0.842_479, 0.078_434, 0.079_224],
+                    [0.042_328, 0.878_469, 0.079_166],
+                    [0.042_376, 0.078_434, 0.879_143],
+                ];
+                const OUTSET: Matrix3 = [
+                    [1.196_879, -0.098_021, -0.099_030],
+                    [-0.052_897, 1.151_903, -0.098_961],
+                    [-0.052_972, -0.098_043, 1.151_074],
+                ];
+                const MIN_EV: f32 = -12.473_93;
+                const MAX_EV: f32 = 4.026_069;
+
+                // Encode the inset color logarithmically and apply the sigmoid,
+                // which yields display-encoded values with a gamma of about 2.2.
+                let v = transform(&INSET, color);
+                let v = map_channels(&v, |v| {
+                    let x =
+                        (v.max(1e-10).log2().clamp(MIN_EV, MAX_EV) - MIN_EV) / (MAX_EV - MIN_EV);
+                    let x2 = x * x;
+                    let x4 = x2 * x2;
+                    15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x
+                        + 0.4298 * x2
+                        + 0.1191 * x
+                        - 0.00232
+                });
+                let v = transform(&OUTSET, &v);
+                map_channels(&v, |v| v.max(0.).powf(2.2))
+            }
+        };
+        map_channels(&mapped, |v| v.clamp(0., 1.))
+    }
+}
+
+impl TransferFunction {
+    /// Encodes a linear value in range `0.0..=1.0`.
+    pub fn encode(&self, value: f32) -> f32 {
+        match *self {
+            TransferFunction::Srgb => {
+                if value <= 0.003_130_8 {
+                    12.92 * value
+                } else {
+                    1.055 * value.powf(1. / 2.4) - 0.055
+                }
+            }
+            TransferFunction::Rec709 => {
+                if value < 0.018 {
+                    4.5 * value
+                } else {
+                    1.099 * value.powf(0.45) - 0.099
+                }
+            }
+            TransferFunction::Gamma(gamma) => value.powf(1. / gamma),
+        }
+    }
+}
+
+/// Exposes, tone maps, encodes and quantizes linear colors.
+pub struct ColorPipeline {
+    /// The exposure adjustment in stops.
+    exposure: f32,
+    tone_mapping: ToneMapping,
+    transfer: TransferFunction,
+    /// Whether to add triangular noise of one quantization step before quantizing,
+    /// trading banding in smooth gradients for fine grain.
+    dither: bool,
+    /// Converts from the working space to the output space.
+    output_conversion: ColorConversion,
+    rng: Random,
+}
+
+impl Default for ColorPipeline {
+    fn default() -> Self {
+        Self {
+            exposure: 0.,
+            tone_mapping: ToneMapping::Clamp,
+            transfer: TransferFunction::Srgb,
+            dither: false,
+            output_conversion: ColorConversion::default(),
+            rng: Random::default(),
+        }
+    }
+}
+
+impl ColorPipeline {
+    pub fn with_exposure(mut self, stops: f32) -> Self {
+        self.exposure = stops;
+        self
+    }
+
+    pub fn with_tone_mapping(mut self, tone_mapping: ToneMapping) -> Self {
+        self.tone_mapping = tone_mapping;
+        self
+    }
+
+    pub fn with_transfer(mut self, transfer: TransferFunction) -> Self {
+        self.transfer = transfer;
+        self
+    }
+
+    pub fn with_dithering(mut self, dither: bool) -> Self {
+        self.dither = dither;
+        self
+    }
+
+    /// Converts rendered colors from the working space into the output space's primaries.
+    ///
+    /// Tone mapping is applied in the output space; the `Aces` and `Agx` operators are
+    /// designed for Rec. 709 primaries and only approximate their look in wider gamuts.
+    pub fn with_color_spaces(mut self, working: ColorSpace, output: ColorSpace) -> Self {
+        self.output_conversion = ColorConversion::new(working, output);
+        self
+    }
+
+    /// Converts a linear color into display-encoded values in range `0.0..=1.0`.
+    pub fn encode(&self, color: &Color) -> Color {
+        // Treat invalid samples as black rather than letting them poison the pixel.
+        let color = map_channels(color, |v| if v.is_finite() { v.max(0.) } else { 0. });
+        let exposed = color * 2f32.powf(self.exposure);
+        // Colors outside the output gamut are clipped.
+        let converted = map_channels(&self.output_conversion.apply(&exposed), |v| v.max(0.));
+        let mapped = self.tone_mapping.apply(&converted);
+        map_channels(&mapped, |v| self.transfer.encode(v))
+    }
+
+    /// Converts a linear color into 8-bit values.
+    pub fn quantize(&self, color: &Color) -> [u8; 3] {
+        let encoded = self.encode(color);
+        let mut values = [0; 3];
+        for (value, encoded) in values.iter_mut().zip(encoded.e) {
+            let noise = if self.dither {
+                self.rng.sample() + self.rng.sample() - 1.
+            } else {
+                0.
+            };
+            *value = (encoded * 255. + noise).round().clamp(0., 255.) as u8;
+        }
+        values
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    pub fn transfer_functions_work() {
+        let srgb = TransferFunction::Srgb;
+        assert_eq!(srgb.encode(0.), 0.);
+        assert!((srgb.encode(1.) - 1.).abs() < 1e-6);
+        assert!((srgb.encode(0.18) - 0.4613).abs() < 1e-3);
+        assert!((TransferFunction::Rec709.encode(1.) - 1.).abs() < 1e-6);
+        assert_eq!(TransferFunction::Gamma(2.).encode(0.25), 0.5);
+    }
+
+    #[test]
+    pub fn tone_mappings_compress_highlights() {
+        for tone_mapping in [
+            ToneMapping::Reinhard { white: None },
+            ToneMapping::Reinhard { white: Some(4.) },
+            ToneMapping::Aces,
+            ToneMapping::Agx,
+        ] {
+            let mut previous = -1.;
+            for stops in -6..8 {
+                let value = 2f32.powi(stops);
+                let mapped = tone_mapping.apply(&Color::new(value, value, value));
+                assert!(
+                    mapped.x() >= previous,
+                    "{:?} is not monotonic",
+                    tone_mapping
+                );
+                assert!(
+                    (mapped.x() - mapped.y()).abs() < 1e-2,
+                    "{:?} tints gray",
+                    tone_mapping
+                );
+                assert!(mapped.x() <= 1.);
+                previous = mapped.x();
+            }
+            assert!(previous > 0.8, "{:?} does not reach white", tone_mapping);
+        }
+
+        let reinhard = ToneMapping::Reinhard { white: Some(4.) };
+        assert!((reinhard.apply(&Color::new(4., 4., 4.)).x() - 1.).abs() < 1e-6);
+    }
+
+    #[test]
+    pub fn quantize_works() {
+        let pipeline = ColorPipeline::default();
+        assert_eq!(pipeline.quantize(&Color::new(0., 1., 7.)), [0, 255, 255]);
+        assert_eq!(pipeline.quantize(&Color::new(f32::NAN, -1., 0.)), [0, 0, 0]);
+
+        let pipeline = ColorPipeline::default()
+            .with_exposure(-1.)
+            .with_transfer(TransferFunction::Gamma(1.));
+        assert_eq!(pipeline.quantize(&Color::new(1., 1., 1.)), [128, 128, 128]);
+
+        // Dithering keeps the average value.
+        let pipeline = ColorPipeline::default()
+            .with_transfer(TransferFunction::Gamma(1.))
+            .with_dithering(true);
+        let sum: f32 = (0..1000)
+            .map(|_| pipeline.quantize(&Color::new(0.2, 0.2, 0.2))[0] as f32)
+            .sum();
+        assert!((sum / 1000. - 51.).abs() < 0.5);
+    }
+}